@@ -0,0 +1,57 @@
+//! Lookup of packages in the [AUR](https://aur.archlinux.org) via its RPC interface.
+
+use crate::{http, json};
+
+/// Returns the version (`[epoch:]pkgver-pkgrel`) of the AUR package `name`, `None` if
+/// there is no such package.
+pub fn version(name: &str) -> Result<Option<String>, String> {
+    let url = format!(
+        "https://aur.archlinux.org/rpc/v5/info?arg[]={}",
+        http::encode(name)
+    );
+    let response = http::get(&url, &[])?;
+    if response.status != 200 {
+        return Err(format!("{url}: HTTP {}", response.status));
+    }
+    info_version(&response.body, name)
+}
+
+/// Returns the version of `name` in the RPC response `body`.
+fn info_version(body: &str, name: &str) -> Result<Option<String>, String> {
+    let doc = json::parse(body)?;
+    if let Some(error) = doc.value.get("error").and_then(|e| e.value.as_str()) {
+        return Err(format!("the AUR refused the request: {error}"));
+    }
+    let results = doc
+        .value
+        .get("results")
+        .and_then(|results| results.value.as_array())
+        .unwrap_or_default();
+    Ok(results
+        .iter()
+        .find(|result| {
+            result
+                .value
+                .get("Name")
+                .is_some_and(|n| n.value.as_str() == Some(name))
+        })
+        .and_then(|result| result.value.get("Version")?.value.as_str())
+        .map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info() {
+        let body = r#"{"resultcount":1,"results":[{"Name":"foo","PackageBase":"foo","Version":"1:1.2.3-2"}],"type":"multiinfo","version":5}"#;
+        assert_eq!(info_version(body, "foo"), Ok(Some("1:1.2.3-2".to_string())));
+        assert_eq!(info_version(body, "bar"), Ok(None));
+        assert!(info_version(
+            r#"{"error":"Incorrect request type specified.","type":"error"}"#,
+            "foo"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,386 @@
+use std::{
+    env,
+    fs::{read_to_string, remove_file, rename, set_permissions},
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+use verinc::{
+    bumpversion,
+    config::{self, Config, ManagedFile},
+    format::Format,
+    Position, Version,
+};
+
+use crate::{
+    dir_of, error, file, hooks::Hooks, jobs, relative, set_context, value, Failure, JSON_ERRORS,
+    VERBOSITY,
+};
+
+/// Runs `verinc bump-all` (or `verinc bump`, which also takes the component as an
+/// argument). Returns `None` if the usage should be printed instead.
+pub(crate) fn run(mut iter: impl Iterator<Item = String>) -> Option<()> {
+    let mut version = None;
+    let mut path = None;
+    let mut options = file::Options::default();
+    let mut unsafe_hooks = false;
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--major" => version = Some(Version::Major),
+            "--minor" => version = Some(Version::Minor),
+            "--patch" => version = Some(Version::Patch),
+            "--config" => path = Some(PathBuf::from(value(&mut iter, "config"))),
+            "-f" | "--force" => options.force = true,
+            "--durable" => options.durable = true,
+            "--no-undo" => options.no_undo = true,
+            "--unsafe-hooks" => unsafe_hooks = true,
+            "-j" | "--jobs" => verinc::parallel::set_jobs(jobs(&mut iter)),
+            "--json-errors" => JSON_ERRORS.store(true, Ordering::Relaxed),
+            "-q" | "--quiet" => VERBOSITY.store(0, Ordering::Relaxed),
+            name => version = Some(config::component(name)?),
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            let found = cwd.ancestors().find_map(|dir| {
+                let native = dir.join(config::FILE_NAME);
+                if native.is_file() {
+                    Some(native)
+                } else {
+                    bumpversion::find(dir)
+                }
+            });
+            match found {
+                Some(path) => path,
+                None => error(
+                    Failure::Io,
+                    &format!(
+                        "Error: Cannot find {} or a bumpversion configuration!",
+                        config::FILE_NAME
+                    ),
+                ),
+            }
+        }
+    };
+
+    set_context(&path.to_string_lossy(), &Position::All);
+    let Ok(content) = read_to_string(&path) else {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot open file '{}'!", path.display()),
+        );
+    };
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    if name == config::FILE_NAME {
+        let config = match Config::parse(&content) {
+            Ok(config) => config,
+            Err(err) => error(
+                Failure::Parse,
+                &format!("Error: Cannot parse '{}': {}!", path.display(), err),
+            ),
+        };
+        let version = version.or(config.bump.clone()).unwrap_or(Version::Patch);
+        let mut hooks = Hooks::new(dir_of(&path), unsafe_hooks);
+        hooks.configure(&config);
+        bump_all(dir_of(&path), &config, version, options, &hooks);
+    } else {
+        match bumpversion::Config::parse(&name, &content) {
+            Ok(Some(config)) => {
+                let version = version.unwrap_or(Version::Patch);
+                bump_bumpversion(&path, &config, version, options)
+            }
+            Ok(None) => error(
+                Failure::Parse,
+                &format!(
+                    "Error: No bumpversion configuration in '{}'!",
+                    path.display()
+                ),
+            ),
+            Err(err) => error(
+                Failure::Parse,
+                &format!("Error: Cannot parse '{}': {}!", path.display(), err),
+            ),
+        }
+    }
+
+    Some(())
+}
+
+/// Runs `verinc sync`: bumps the version of the first file (at position 0 or of
+/// `--format`) and writes it into the following ones at `--position`, which have to
+/// carry the same version. Returns `None` if the usage should be printed instead.
+pub(crate) fn sync(mut iter: impl Iterator<Item = String>) -> Option<()> {
+    let mut version = Version::Patch;
+    let mut format = None;
+    let mut position = Position::Nth(0);
+    let mut options = file::Options::default();
+    let mut paths = Vec::new();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--major" => version = Version::Major,
+            "--minor" => version = Version::Minor,
+            "--patch" => version = Version::Patch,
+            "--format" => match Format::parse(&value(&mut iter, "format")) {
+                // There is no author for the changelog entry.
+                Some(Format::DebChangelog(_)) | None => {
+                    error(Failure::Usage, "Error: Invalid format!")
+                }
+                found => format = found,
+            },
+            "-p" | "--position" => {
+                position = match Position::parse(&value(&mut iter, "position")) {
+                    Some(position) => position,
+                    None => error(Failure::Usage, "Error: Invalid position!"),
+                };
+            }
+            "-f" | "--force" => options.force = true,
+            "--durable" => options.durable = true,
+            "--no-undo" => options.no_undo = true,
+            "--json-errors" => JSON_ERRORS.store(true, Ordering::Relaxed),
+            "-q" | "--quiet" => VERBOSITY.store(0, Ordering::Relaxed),
+            arg if arg.starts_with('-') => return None,
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() < 2 {
+        return None;
+    }
+
+    let files = paths
+        .into_iter()
+        .enumerate()
+        .map(|(idx, path)| ManagedFile {
+            path,
+            position: if idx == 0 {
+                Position::Nth(0)
+            } else {
+                position.clone()
+            },
+            context: None,
+            line_match: None,
+            format: if idx == 0 { format.take() } else { None },
+        })
+        .collect();
+    let config = Config {
+        files,
+        bump: None,
+        tokens: Vec::new(),
+        pre_hooks: Vec::new(),
+        post_hooks: Vec::new(),
+        hooks_dir: None,
+    };
+    set_context(&config.files[0].path, &Position::All);
+    let hooks = Hooks::new(Path::new(""), false);
+    bump_all(Path::new(""), &config, version, options, &hooks);
+    Some(())
+}
+
+fn read(path: &Path) -> String {
+    match read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => error(
+            Failure::Io,
+            &format!("Error: Cannot open file '{}'!", path.display()),
+        ),
+    }
+}
+
+/// Bumps every declared location. Nothing is written unless all of them carry the same
+/// version as the first one. The hooks get the first file.
+fn bump_all(dir: &Path, config: &Config, version: Version, options: file::Options, hooks: &Hooks) {
+    if config.files.is_empty() {
+        error(
+            Failure::Parse,
+            &format!("Error: No files declared in {}!", config::FILE_NAME),
+        );
+    }
+
+    let _project = file::ProjectLock::acquire(dir);
+    let mut current: Option<String> = None;
+    let mut contents = Vec::new();
+    let mut locks = Vec::new();
+
+    for file in &config.files {
+        set_context(&file.path, &file.position);
+        let path = dir.join(&file.path);
+        let Some((lock, content)) = file::Locked::open(&path) else {
+            error(
+                Failure::Io,
+                &format!("Error: Cannot open file '{}'!", path.display()),
+            );
+        };
+        locks.push(lock);
+
+        let versions = file.versions(&content);
+        if versions.is_empty() {
+            error(
+                Failure::NoMatch,
+                &format!("Error: No version found in '{}'!", file.path),
+            );
+        }
+        for found in versions {
+            match &current {
+                Some(current) if *current != found => error(
+                    Failure::Guard,
+                    &format!(
+                        "Error: '{}' has version {found} but '{}' has {current}!",
+                        file.path, config.files[0].path
+                    ),
+                ),
+                Some(_) => {}
+                None => current = Some(found),
+            }
+        }
+
+        contents.push((path, file, content));
+    }
+
+    let current = current.unwrap();
+    let Ok(next) = verinc::next(&current, version) else {
+        error(
+            Failure::Parse,
+            &format!("Error: Invalid version '{current}'!"),
+        );
+    };
+
+    let updates = contents
+        .into_iter()
+        .map(|(path, file, content)| (path, file.set(&content, &next)))
+        .collect();
+    let first = dir.join(&config.files[0].path);
+    hooks.pre(&first, &current, &next, false);
+    locks.iter().for_each(file::Locked::verify);
+    let options = file::Options {
+        locked: &locks,
+        ..options
+    };
+    write_all(dir, updates, &current, &next, options);
+    // A post hook may bump the files again.
+    drop(locks);
+    hooks.post(&first, &current, &next, false);
+}
+
+/// Bumps the files of a bumpversion configuration and its `current_version`.
+fn bump_bumpversion(
+    path: &Path,
+    config: &bumpversion::Config,
+    version: Version,
+    options: file::Options,
+) {
+    let _project = file::ProjectLock::acquire(dir_of(path));
+    let current = &config.current_version;
+    let Ok(next) = verinc::next(current, version) else {
+        error(
+            Failure::Parse,
+            &format!("Error: Invalid version '{current}'!"),
+        );
+    };
+
+    let mut updates: Vec<(PathBuf, String)> = Vec::new();
+    for rule in &config.rules {
+        for file in rule.paths(dir_of(path)) {
+            let content = match updates.iter().position(|(p, _)| *p == file) {
+                Some(idx) => updates.remove(idx).1,
+                None => read(&file),
+            };
+            let Some(result) = rule.apply(&content, current, &next) else {
+                error(
+                    Failure::NoMatch,
+                    &format!(
+                        "Error: '{}' does not contain '{}'!",
+                        file.display(),
+                        rule.search.replace("{current_version}", current)
+                    ),
+                );
+            };
+            updates.push((file, result));
+        }
+    }
+
+    // The configuration itself may be among the files (e.g. setup.cfg).
+    let content = match updates.iter().position(|(p, _)| p == path) {
+        Some(idx) => updates.remove(idx).1,
+        None => read(path),
+    };
+    match bumpversion::Config::parse(&path.to_string_lossy(), &content) {
+        Ok(Some(config)) => updates.push((path.to_path_buf(), config.set_current_version(&next))),
+        _ => error(
+            Failure::Parse,
+            &format!("Error: Cannot update '{}'!", path.display()),
+        ),
+    }
+
+    write_all(dir_of(path), updates, current, &next, options);
+}
+
+/// Writes all of the files at once, nothing is written if any of them fails. The files
+/// keep their mode. If durable, the renames are recorded in a journal in `dir`.
+fn write_all(
+    dir: &Path,
+    updates: Vec<(PathBuf, String)>,
+    current: &str,
+    next: &str,
+    options: file::Options,
+) {
+    let permissions: Vec<_> = updates
+        .iter()
+        .map(|(path, _)| file::check(path, options.force))
+        .collect();
+
+    let files: Vec<_> = updates.iter().zip(permissions).collect();
+    let written = verinc::parallel::map(&files, |((path, content), permissions)| {
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".verinc-tmp");
+        let tmp = PathBuf::from(tmp);
+
+        let written =
+            file::create(&tmp, content, options.durable).and_then(|_| match permissions {
+                Some(permissions) => set_permissions(&tmp, permissions.clone()),
+                None => Ok(()),
+            });
+        (tmp, written.is_ok())
+    });
+
+    if let Some(idx) = written.iter().position(|(_, ok)| !ok) {
+        for (tmp, _) in &written {
+            let _ = remove_file(tmp);
+        }
+        error(
+            Failure::Io,
+            &format!("Error: Cannot write '{}'!", updates[idx].0.display()),
+        );
+    }
+    let pending: Vec<_> = written.into_iter().map(|(tmp, _)| tmp).collect();
+    let log = file::record_undo(&updates, options);
+
+    let journal = options
+        .durable
+        .then(|| file::Journal::begin(dir, &updates, options));
+    for (idx, ((path, _), tmp)) in updates.iter().zip(&pending).enumerate() {
+        if let Err(err) = rename(tmp, path) {
+            // The journal, if any, is kept as the files written so far stay.
+            for tmp in &pending[idx..] {
+                let _ = remove_file(tmp);
+            }
+            error(
+                Failure::Io,
+                &format!("Error: Cannot write '{}': {err}!", path.display()),
+            );
+        }
+        if options.durable {
+            file::sync_dir(dir_of(path));
+        }
+
+        info!("{}: {current} -> {next}", relative(path).display());
+    }
+    if let Some(journal) = journal {
+        journal.finish();
+    }
+    file::save_undo(log);
+}
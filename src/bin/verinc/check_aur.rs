@@ -0,0 +1,56 @@
+use std::{cmp::Ordering, fs::read_to_string, process, sync::atomic::Ordering as Atomic};
+
+use verinc::pkgbuild;
+
+use crate::{error, set_context, Failure, JSON_ERRORS};
+
+/// Runs `verinc check-aur`. Returns `None` if the usage should be printed instead.
+pub(crate) fn run(iter: impl Iterator<Item = String>) -> Option<()> {
+    let mut path = None;
+    for arg in iter {
+        match arg.as_str() {
+            "--offline" => verinc::set_offline(true),
+            "--json-errors" => JSON_ERRORS.store(true, Atomic::Relaxed),
+            arg if arg.starts_with('-') || path.is_some() => return None,
+            _ => path = Some(arg),
+        }
+    }
+    let path = path.unwrap_or_else(|| "PKGBUILD".to_string());
+
+    set_context(&path, &verinc::Position::Nth(0));
+    let Ok(content) = read_to_string(&path) else {
+        error(Failure::Io, &format!("Error: Cannot open file '{path}'!"));
+    };
+    let local = match pkgbuild::full_version(&content) {
+        Ok(version) => version,
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot parse '{path}': {err}!"),
+        ),
+    };
+    let Some(name) = pkgbuild::package_name(&content) else {
+        error(Failure::Parse, &format!("Error: No pkgname in '{path}'!"));
+    };
+
+    let aur = match verinc::aur::version(&name) {
+        Ok(Some(version)) => version,
+        Ok(None) => error(
+            Failure::NoMatch,
+            &format!("Error: There is no package '{name}' in the AUR!"),
+        ),
+        Err(err) => error(Failure::Network, &format!("Error: {err}!")),
+    };
+
+    match pkgbuild::vercmp(&local, &aur) {
+        Ordering::Equal => println!("{name} {local} is up to date with the AUR"),
+        Ordering::Greater => {
+            println!("{name} {local} is newer than {aur} in the AUR, push it");
+            process::exit(Failure::Guard as i32);
+        }
+        Ordering::Less => {
+            println!("{name} {local} is older than {aur} in the AUR, pull or bump it");
+            process::exit(Failure::Guard as i32);
+        }
+    }
+    Some(())
+}
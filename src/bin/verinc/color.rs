@@ -0,0 +1,111 @@
+use std::{
+    env,
+    io::{stdout, IsTerminal},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use verinc::Selector;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const RED: &str = "31";
+const GREEN: &str = "32";
+const CYAN: &str = "36";
+const RESET: &str = "\x1b[0m";
+
+/// Sets the --color mode, `None` if it is invalid. With `auto`, colors are used if stdout
+/// is a terminal and `NO_COLOR` is not set.
+pub(crate) fn set(mode: &str) -> Option<()> {
+    let enabled = match mode {
+        "always" => true,
+        "never" => false,
+        "auto" => stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        _ => return None,
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+    Some(())
+}
+
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Returns `version` in bold green for --list.
+pub(crate) fn version(version: &str) -> String {
+    if !enabled() {
+        return version.to_string();
+    }
+    format!("\x1b[{GREEN};1m{version}{RESET}")
+}
+
+/// Colors the lines of the unified `diff`. The versions on the changed lines are bold and
+/// the part of a new version which differs from the old one on the matching removed
+/// line is reversed as well.
+pub(crate) fn diff(diff: &str, selector: &Selector) -> String {
+    if !enabled() {
+        return diff.to_string();
+    }
+
+    let mut out = String::with_capacity(diff.len() * 2);
+    let mut removed = Vec::new();
+    let mut added = 0;
+    for line in diff.split_inclusive('\n') {
+        let text = line.trim_end_matches('\n');
+        let colored = if text.starts_with("---") || text.starts_with("+++") {
+            format!("\x1b[1m{text}{RESET}")
+        } else if text.starts_with("@@") {
+            format!("\x1b[{CYAN}m{text}{RESET}")
+        } else if let Some(rest) = text.strip_prefix('-') {
+            if added > 0 {
+                removed.clear();
+                added = 0;
+            }
+            removed.push(rest);
+            versions(text, RED, selector, None)
+        } else if text.starts_with('+') {
+            let old = removed.get(added).map(|old| selector.list_versions(old));
+            added += 1;
+            versions(text, GREEN, selector, old.as_deref())
+        } else {
+            removed.clear();
+            added = 0;
+            text.to_string()
+        };
+        out += &colored;
+        out += &line[text.len()..];
+    }
+    out
+}
+
+/// Returns `line` in `color` with the versions in bold. The part of each version which
+/// differs from the one at the same index of `old` is reversed.
+fn versions(line: &str, color: &str, selector: &Selector, old: Option<&[&str]>) -> String {
+    let mut out = format!("\x1b[{color}m");
+    let mut last = 0;
+    // The prefix of the diff line is not a part of the content.
+    for (idx, span) in selector.spans(&line[1..]).into_iter().enumerate() {
+        let (start, end) = (span.start + 1, span.end + 1);
+        let version = &line[start..end];
+        let same = old
+            .and_then(|old| old.get(idx))
+            .map_or(version.len(), |old| common_prefix(old, version));
+
+        out += &line[last..start];
+        out += &format!("\x1b[1m{}", &version[..same]);
+        if same < version.len() {
+            out += &format!("\x1b[7m{}", &version[same..]);
+        }
+        out += &format!("{RESET}\x1b[{color}m");
+        last = end;
+    }
+    out += &line[last..];
+    out += RESET;
+    out
+}
+
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((idx, _), _)| idx)
+}
@@ -0,0 +1,521 @@
+use std::{
+    env,
+    fmt::Write as _,
+    fs::{self, File, Permissions},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use sha2::{Digest, Sha256};
+use verinc::undo::{self, Log};
+
+use crate::{dir_of, error, now, Failure};
+
+const JOURNAL: &str = ".verinc-journal";
+
+/// How files are written.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Options<'a> {
+    /// Write read-only files.
+    pub force: bool,
+    /// Flush the files and their directories to disk and keep a journal of multi-file
+    /// writes.
+    pub durable: bool,
+    /// Keep the modification time of the existing files.
+    pub keep_mtime: bool,
+    /// Copy the existing files to the file name with this suffix first.
+    pub backup: Option<&'a str>,
+    /// Do not record the changes in the undo log.
+    pub no_undo: bool,
+    /// The files locked when they were read, which are read and written through their
+    /// handles.
+    pub locked: &'a [Locked],
+}
+
+/// Returns the permissions of `path` which allow the owner to write it.
+fn writable(permissions: &Permissions) -> Permissions {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Permissions::from_mode(permissions.mode() | 0o200)
+    }
+    #[cfg(not(unix))]
+    {
+        let mut permissions = permissions.clone();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        permissions
+    }
+}
+
+/// Fails unless `path` may be written. Returns the permissions of an existing file.
+pub(crate) fn check(path: &Path, force: bool) -> Option<Permissions> {
+    if verinc::path::is_reserved(path) {
+        error(
+            Failure::Io,
+            &format!(
+                "Error: '{}' is a reserved device name on Windows!",
+                path.display()
+            ),
+        );
+    }
+
+    let permissions = fs::metadata(path).ok()?.permissions();
+    if permissions.readonly() && !force {
+        error(
+            Failure::Guard,
+            &format!(
+                "Error: '{}' is read-only, use --force to write it anyway!",
+                path.display()
+            ),
+        );
+    }
+    Some(permissions)
+}
+
+/// Creates (or truncates) `path` with `content`, flushing it to disk if `durable`.
+pub(crate) fn create(path: &Path, content: &str, durable: bool) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(content.as_bytes())?;
+    if durable {
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Replaces `path` with `content` written to a temporary file next to it, flushed to disk
+/// and renamed over it, so that a crash leaves either the old or the new content.
+fn replace(path: &Path, content: &str) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".verinc-tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let result = create(&tmp, content, true)
+        .and_then(|()| match fs::metadata(path) {
+            Ok(metadata) => fs::set_permissions(&tmp, metadata.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Flushes the entries of `dir` to disk so that new and renamed files survive a crash.
+pub(crate) fn sync_dir(dir: &Path) {
+    // Directories cannot be opened (and don't need to be synced) on Windows.
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+/// Returns the lock of `path` among the [`Options::locked`] files.
+fn locked<'a>(path: &Path, options: Options<'a>) -> Option<&'a Locked> {
+    options.locked.iter().find(|locked| locked.path == path)
+}
+
+/// Reads `path`, through its lock if it's locked as Windows doesn't let the other
+/// handles read a locked file.
+fn read(path: &Path, options: Options) -> io::Result<String> {
+    match locked(path, options) {
+        Some(locked) => locked.read(),
+        None => fs::read_to_string(path),
+    }
+}
+
+/// Writes `content` to `path`. Read-only files are refused unless `force` is given, in
+/// which case they are made writable just for the write. Existing files are rewritten in
+/// place, so they keep their mode (and their mtime with `keep_mtime`), or replaced by a
+/// renamed copy with the same mode if `durable`.
+pub(crate) fn write(path: &Path, content: &str, options: Options) {
+    let modified = options
+        .keep_mtime
+        .then(|| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .flatten();
+    let permissions = check(path, options.force).filter(Permissions::readonly);
+    if let Some(permissions) = &permissions {
+        if fs::set_permissions(path, writable(permissions)).is_err() {
+            error(
+                Failure::Io,
+                &format!("Error: Cannot make '{}' writable!", path.display()),
+            );
+        }
+    }
+
+    let result = match locked(path, options) {
+        _ if options.durable => replace(path, content),
+        Some(locked) => locked.rewrite(content),
+        None => create(path, content, false),
+    };
+    let result = result.and_then(|()| match modified {
+        Some(modified) => File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(modified),
+        None => Ok(()),
+    });
+    if options.durable {
+        sync_dir(dir_of(path));
+    }
+
+    if let Some(permissions) = permissions {
+        let _ = fs::set_permissions(path, permissions);
+    }
+    if let Err(err) = result {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot write '{}': {err}!", path.display()),
+        );
+    }
+}
+
+fn hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Opens `path` for reading and writing, or just for reading if it cannot be written
+/// (e.g. a read-only file). Returns whether it is writable too.
+fn open(path: &Path) -> Option<(File, bool)> {
+    match File::options().read(true).write(true).open(path) {
+        Ok(file) => Some((file, true)),
+        Err(_) => File::open(path).ok().map(|file| (file, false)),
+    }
+}
+
+/// A file which holds an advisory lock from the moment it is read until it is written (or
+/// dropped), so that concurrent runs wait for each other. The lock is mandatory on
+/// Windows, so the file is read and written through the locked handle.
+#[derive(Debug)]
+pub(crate) struct Locked {
+    path: PathBuf,
+    file: File,
+    writable: bool,
+    stamp: Option<(SystemTime, u64)>,
+    hash: u64,
+}
+
+impl Locked {
+    /// Locks and reads `path`. Returns `None` if it cannot be read.
+    pub(crate) fn open(path: &Path) -> Option<(Self, String)> {
+        let (mut file, mut writable) = open(path)?;
+        // Locking is best effort, some file systems don't support it.
+        while !locked_by_parent(dir_of(path)) && file.lock().is_ok() && !is_same(&file, path) {
+            // Replaced by a rename while waiting, the lock is of the old file.
+            (file, writable) = open(path)?;
+        }
+
+        let mut content = String::new();
+        file.read_to_string(&mut content).ok()?;
+
+        let locked = Locked {
+            path: path.to_path_buf(),
+            file,
+            writable,
+            stamp: stamp(path),
+            hash: hash(&content),
+        };
+        Some((locked, content))
+    }
+
+    /// Reads the file again.
+    fn read(&self) -> io::Result<String> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(0))?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    /// Replaces the content of the file with `content`. A file which was opened read-only
+    /// (and made writable with --force since) is unlocked and written by its path.
+    fn rewrite(&self, content: &str) -> io::Result<()> {
+        if !self.writable {
+            let _ = self.file.unlock();
+            return create(&self.path, content, false);
+        }
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(content.as_bytes())
+    }
+
+    /// Fails if somebody else modified the file since it was read.
+    pub(crate) fn verify(&self) {
+        if self.stamp.is_some() && stamp(&self.path) == self.stamp {
+            return;
+        }
+        let current = self.read().ok();
+        if current.map(|c| hash(&c)) != Some(self.hash) {
+            error(
+                Failure::Guard,
+                &format!(
+                    "Error: '{}' was modified by someone else while being bumped!",
+                    self.path.display()
+                ),
+            );
+        }
+    }
+}
+
+/// Returns whether `file` is still the one at `path`.
+fn is_same(file: &File, path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (file.metadata(), fs::metadata(path)) {
+            (Ok(a), Ok(b)) => (a.dev(), a.ino()) == (b.dev(), b.ino()),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (file, path);
+        true
+    }
+}
+
+/// Set for the commands run while holding a [`ProjectLock`] to its directory, so that
+/// a verinc run by a hook doesn't wait for the lock forever. Such a run takes neither
+/// the project lock nor the locks of the files below the directory.
+pub(crate) const LOCKED_VAR: &str = "VERINC_LOCKED";
+
+/// The directory of the [`ProjectLock`] held by this process.
+static HELD: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Returns the directory locked by this run or by the one which runs it as a hook.
+pub(crate) fn project_locked() -> Option<PathBuf> {
+    let held = HELD.lock().unwrap_or_else(|err| err.into_inner()).clone();
+    held.or_else(|| env::var_os(LOCKED_VAR).map(PathBuf::from))
+}
+
+/// Returns whether `path` is below the directory locked by the run which runs this one
+/// as a hook.
+fn locked_by_parent(path: &Path) -> bool {
+    let Some(locked) = env::var_os(LOCKED_VAR) else {
+        return false;
+    };
+    let path = if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    };
+    verinc::path::canonicalize(path).is_ok_and(|path| path.starts_with(locked))
+}
+
+/// An advisory lock of a whole project held for a run, so that concurrent runs don't
+/// interleave their edits of the files they share (a changelog, a release-please
+/// manifest or the requirements in a workspace).
+pub(crate) struct ProjectLock(Option<File>);
+
+impl ProjectLock {
+    /// Locks the directory `dir`, waiting for the runs which hold it. Locking is best
+    /// effort, directories cannot be opened on Windows.
+    pub(crate) fn acquire(dir: &Path) -> ProjectLock {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        if locked_by_parent(dir) {
+            return ProjectLock(None);
+        }
+        let dir = verinc::path::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let Ok(file) = File::open(&dir) else {
+            return ProjectLock(None);
+        };
+        if file.lock().is_err() {
+            return ProjectLock(None);
+        }
+        *HELD.lock().unwrap_or_else(|err| err.into_inner()) = Some(dir);
+        ProjectLock(Some(file))
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            *HELD.lock().unwrap_or_else(|err| err.into_inner()) = None;
+        }
+    }
+}
+
+fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .fold(String::new(), |mut out, b| {
+            let _ = write!(out, "{b:02x}");
+            out
+        })
+}
+
+/// A write-ahead journal listing the files a run is about to write together with the
+/// checksums of their old and new content. It is removed once all of them are written,
+/// so if it is still around, the run was interrupted and the files may be half-bumped.
+pub(crate) struct Journal(PathBuf);
+
+impl Journal {
+    pub(crate) fn begin(dir: &Path, updates: &[(PathBuf, String)], options: Options) -> Journal {
+        let path = dir.join(JOURNAL);
+        if path.exists() {
+            error(Failure::Guard, &interrupted(&path));
+        }
+
+        let mut journal =
+            "# verinc: files of an unfinished run (old and new sha256), check them and remove this file\n"
+                .to_string();
+        for (file, new) in updates {
+            let old = read(file, options).map_or("-".to_string(), |old| sha256(old.as_bytes()));
+            let _ = writeln!(
+                journal,
+                "{old} {} {}",
+                sha256(new.as_bytes()),
+                file.display()
+            );
+        }
+
+        if create(&path, &journal, true).is_err() {
+            error(
+                Failure::Io,
+                &format!("Error: Cannot write '{}'!", path.display()),
+            );
+        }
+        sync_dir(dir);
+        Journal(path)
+    }
+
+    pub(crate) fn finish(self) {
+        let _ = fs::remove_file(&self.0);
+        sync_dir(dir_of(&self.0));
+    }
+}
+
+/// Describes the state of the files of the journal at `path`.
+fn interrupted(path: &Path) -> String {
+    let journal = fs::read_to_string(path).unwrap_or_default();
+    let mut msg = format!(
+        "Error: A previous run did not finish, see '{}':",
+        path.display()
+    );
+
+    for line in journal.lines().filter(|l| !l.starts_with('#')) {
+        let mut parts = line.splitn(3, ' ');
+        let (Some(old), Some(new), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let current = fs::read(file).map_or("-".to_string(), |c| sha256(&c));
+        let state = if current == new {
+            "written"
+        } else if current == old {
+            "not written"
+        } else {
+            "changed since"
+        };
+        let _ = write!(msg, "\n  {file}: {state}");
+    }
+
+    msg.push_str("\nCheck the files and remove the journal to continue!");
+    msg
+}
+
+/// Copies `path` (if it exists) next to it with `suffix` appended to its name.
+fn backup(path: &Path, suffix: &str, options: Options) {
+    if !path.exists() {
+        return;
+    }
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    let copied = match locked(path, options) {
+        Some(locked) => locked.read().and_then(|content| {
+            fs::write(&name, content)?;
+            fs::set_permissions(&name, fs::metadata(path)?.permissions())
+        }),
+        None => fs::copy(path, &name).map(drop),
+    };
+    if let Err(err) = copied {
+        error(
+            Failure::Io,
+            &format!(
+                "Error: Cannot back up '{}' to '{}': {err}!",
+                path.display(),
+                Path::new(&name).display()
+            ),
+        );
+    }
+}
+
+/// Returns the undo log of the current directory with the changes `updates` make to the
+/// existing files recorded, as one bump per run of verinc. It is to be saved with
+/// [`save_undo`] once they are written.
+pub(crate) fn record_undo(updates: &[(PathBuf, String)], options: Options) -> Option<Log> {
+    if options.no_undo {
+        return None;
+    }
+    let bump = format!("{}-{}", now(), process::id());
+    let Ok(mut log) = Log::open(Path::new("")) else {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot read the undo log '{}'!", undo::PATH),
+        );
+    };
+    for (path, new) in updates {
+        // Created files are left alone by undo.
+        let Ok(old) = read(path, options) else {
+            continue;
+        };
+        let path = verinc::path::canonicalize(path).unwrap_or_else(|_| path.clone());
+        log.record(undo::edits(&bump, &path, &old, new));
+    }
+    Some(log)
+}
+
+/// Saves the undo log returned by [`record_undo`].
+pub(crate) fn save_undo(log: Option<Log>) {
+    let Some(log) = log else {
+        return;
+    };
+    if let Err(err) = log.save() {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot save the undo log, {err}!"),
+        );
+    }
+}
+
+/// Writes all of `updates`, keeping a [`Journal`] in `dir` if `durable`.
+pub(crate) fn write_all(dir: &Path, updates: &[(PathBuf, String)], options: Options) {
+    for (path, _) in updates {
+        check(path, options.force);
+    }
+    let log = record_undo(updates, options);
+    if let Some(suffix) = options.backup {
+        for (path, _) in updates {
+            backup(path, suffix, options);
+        }
+    }
+
+    let journal = options
+        .durable
+        .then(|| Journal::begin(dir, updates, options));
+    for (path, content) in updates {
+        write(path, content, options);
+    }
+    if let Some(journal) = journal {
+        journal.finish();
+    }
+    save_undo(log);
+}
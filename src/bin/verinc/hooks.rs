@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use verinc::{config::Config, hook::Sandbox};
+
+use crate::{error, file, Failure};
+
+/// The commands run before and after a bump, each in the directory it's configured for
+/// which must be in the project directory.
+pub(crate) struct Hooks {
+    pre: Vec<(String, PathBuf)>,
+    post: Vec<(String, PathBuf)>,
+    sandbox: Sandbox,
+}
+
+impl Hooks {
+    /// Returns no hooks for the project in `root`.
+    pub(crate) fn new(root: &Path, unsafe_hooks: bool) -> Self {
+        let mut sandbox = Sandbox::new(root);
+        sandbox.unsafe_hooks = unsafe_hooks;
+        Hooks {
+            pre: Vec::new(),
+            post: Vec::new(),
+            sandbox,
+        }
+    }
+
+    /// Adds the hooks of `config` in the project directory, which run in its `hooks.dir`.
+    pub(crate) fn configure(&mut self, config: &Config) {
+        let dir = self
+            .sandbox
+            .root
+            .join(config.hooks_dir.as_deref().unwrap_or(Path::new("")));
+        self.add(&config.pre_hooks, &config.post_hooks, &dir);
+    }
+
+    /// Adds the `pre` and `post` hooks which run in `dir`.
+    pub(crate) fn add(&mut self, pre: &[String], post: &[String], dir: &Path) {
+        let with_dir = |command: &String| (command.clone(), dir.to_path_buf());
+        self.pre.extend(pre.iter().map(with_dir));
+        self.post.extend(post.iter().map(with_dir));
+    }
+
+    /// Runs the hooks which come before writing the bump of `file` from `old` to `new`,
+    /// exits if one of them fails.
+    pub(crate) fn pre(&self, file: &Path, old: &str, new: &str, dry_run: bool) {
+        for hook in &self.pre {
+            if let Err(err) = self.run("pre", hook, file, old, new, dry_run) {
+                error(
+                    Failure::Guard,
+                    &format!("Error: {err}, nothing was written!"),
+                );
+            }
+        }
+    }
+
+    /// Runs the hooks which come after the bump was written, exits if one of them fails.
+    pub(crate) fn post(&self, file: &Path, old: &str, new: &str, dry_run: bool) {
+        for hook in &self.post {
+            if let Err(err) = self.run("post", hook, file, old, new, dry_run) {
+                error(Failure::Other, &format!("Error: {err}!"));
+            }
+        }
+    }
+
+    /// Runs `command` in `dir` with `$OLD_VERSION`, `$NEW_VERSION` and `$FILE` (absolute)
+    /// set.
+    fn run(
+        &self,
+        when: &str,
+        (command, dir): &(String, PathBuf),
+        file: &Path,
+        old: &str,
+        new: &str,
+        dry_run: bool,
+    ) -> Result<(), String> {
+        if dry_run {
+            info!("Would run the {when} hook '{command}'");
+            return Ok(());
+        }
+        status!("Running the {when} hook '{command}'");
+        let file = verinc::path::canonicalize(file).unwrap_or_else(|_| PathBuf::from(file));
+        let file = file.to_string_lossy();
+        let locked = file::project_locked().unwrap_or_default();
+        let locked = locked.to_string_lossy();
+        let mut vars = vec![("OLD_VERSION", old), ("NEW_VERSION", new), ("FILE", &file)];
+        if !locked.is_empty() {
+            vars.push((file::LOCKED_VAR, &locked));
+        }
+        self.sandbox.run(command, dir, &vars)
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::{self, BufRead, Write};
+
+use verinc::Change;
+
+use crate::{color, error, Args, Failure};
+
+enum Answer {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+/// Asks about each version `result` changes in `content` and returns `content` with
+/// just the accepted ones replaced.
+pub(crate) fn confirm(args: &Args, file: &str, content: &str, result: &str) -> String {
+    let changes = args.selector.changes(content, result);
+    if apply(content, &changes, |_| true) != result {
+        error(
+            Failure::Usage,
+            "Error: --interactive cannot be combined with bumps which change more than the versions!",
+        );
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut all = false;
+    let mut quit = false;
+    apply(content, &changes, |change| {
+        if quit {
+            return false;
+        }
+        if all {
+            return true;
+        }
+        match ask(&mut stdin, args, file, content, change) {
+            Answer::Yes => true,
+            Answer::No => false,
+            Answer::All => {
+                all = true;
+                true
+            }
+            Answer::Quit => {
+                quit = true;
+                false
+            }
+        }
+    })
+}
+
+/// Returns `content` with the `changes` for which `accept` returns true made.
+pub(crate) fn apply(
+    content: &str,
+    changes: &[Change],
+    mut accept: impl FnMut(&Change) -> bool,
+) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for change in changes {
+        out += &content[last..change.span.start];
+        out += if accept(change) {
+            &change.new
+        } else {
+            &change.old
+        };
+        last = change.span.end;
+    }
+    out += &content[last..];
+    out
+}
+
+/// Shows the line of `change` and asks until one of the answers is given. The end of the
+/// input quits.
+fn ask(
+    stdin: &mut impl BufRead,
+    args: &Args,
+    file: &str,
+    content: &str,
+    change: &Change,
+) -> Answer {
+    let start = content[..change.span.start]
+        .rfind('\n')
+        .map_or(0, |idx| idx + 1);
+    let end = content[change.span.end..]
+        .find('\n')
+        .map_or(content.len(), |idx| change.span.end + idx);
+    let number = content[..start].matches('\n').count() + 1;
+    let diff = format!(
+        "-{}\n+{}{}{}\n",
+        &content[start..end],
+        &content[start..change.span.start],
+        change.new,
+        &content[change.span.end..end]
+    );
+
+    eprint!("{file}:{number}\n{}", color::diff(&diff, &args.selector));
+    loop {
+        eprint!("Bump {} to {} [y,n,a,q]? ", change.old, change.new);
+        let _ = io::stderr().flush();
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).unwrap_or(0) == 0 {
+            eprintln!();
+            return Answer::Quit;
+        }
+        match line.trim() {
+            "y" => return Answer::Yes,
+            "n" => return Answer::No,
+            "a" => return Answer::All,
+            "q" => return Answer::Quit,
+            _ => eprintln!("y - bump this version\nn - keep it\na - bump it and all of the following ones\nq - keep it and all of the following ones"),
+        }
+    }
+}
@@ -0,0 +1,1944 @@
+/// Prints a message about what is being done to stdout, or to stderr with --shell-output
+/// and --report whose stdout must only contain their output. Nothing with --quiet.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if crate::verbosity() == 0 {
+        } else if crate::MACHINE_OUTPUT.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+/// Prints a message about the progress to stderr unless --quiet.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if crate::verbosity() > 0 {
+            eprintln!($($arg)*)
+        }
+    };
+}
+
+/// Prints a message to stderr with --verbose.
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if crate::verbosity() > 1 {
+            eprintln!($($arg)*)
+        }
+    };
+}
+
+mod bump_all;
+mod check_aur;
+mod color;
+mod file;
+mod hooks;
+mod interactive;
+mod notify;
+mod pick;
+mod pr;
+mod self_update;
+mod stats;
+mod undo;
+mod watcher;
+mod workspace;
+
+use std::{
+    env, fs, mem,
+    path::{Path, PathBuf},
+    process::exit,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hooks::Hooks;
+use stats::Stats;
+use verinc::{
+    config::Config,
+    digest::Digests,
+    format::{Entry, Format},
+    Change, Components, Position, Regex, Selector, VPrefix, Version,
+};
+
+fn usage() {
+    eprintln!(
+        "Usage: verinc [flags] <file or glob>
+       verinc [flags] -r <dir> [--include <glob>]... [--exclude <glob>]...
+       verinc bump-all [--major|--minor|--patch] [--config <path>] [--force]
+                       [--durable] [--no-undo] [--unsafe-hooks] [--jobs <n>]
+                       [--json-errors] [--quiet]
+       verinc bump [major|minor|patch] [bump-all flags]
+       verinc sync [--major|--minor|--patch] [--format <format>] [--position <pos>]
+                   [--force] [--durable] [--no-undo] [--json-errors] [--quiet]
+                   <primary> <secondary>...
+       verinc auto [flags] <file or glob>
+       verinc check-aur [--offline] [--json-errors] [PKGBUILD]
+       verinc undo [--dry-run] [--json-errors]
+       verinc self-update [--check] [--dry-run] [--verify-with <verifier>]
+
+Increments X.Y.Z version in the given file. A pre-release like 1.2.3-rc.1 is dropped
+by the bump (to 1.2.3 with --patch, 1.3.0 with --minor). With bump-all, increments the
+version in all of the files declared in .verinc.toml (found in the current directory
+or above), provided they all agree on the current version. bump is the same but also
+takes the component as an argument, both default to the bump of .verinc.toml. Existing
+bumpversion configurations (.bumpversion.cfg, setup.cfg or pyproject.toml) are used as
+well. With sync, increments the first version of the primary file (or the one found by
+--format) and writes the result into the secondary files at --position (0 by default),
+which have to carry the same version as the primary one. With auto, the component is
+derived from the Conventional Commits since the latest vX.Y.Z tag (a breaking change is
+major, feat minor and fix patch). check-aur compares [epoch:]pkgver-pkgrel of the
+PKGBUILD (in the current directory by default) with the version of the package in the
+AUR and exits with 6 unless they agree. undo reverts the files written by the last
+bump, as recorded in .verinc/undo of the current directory (which keeps the last 10),
+unless they have changed since. With self-update, replaces this binary with the newest
+release on GitHub after checking its sha256 (and its signature with --verify-with),
+--check only tells whether there is one.
+
+A glob like 'pkgs/*/PKGBUILD' (quoted, * and ? in any component) is expanded by verinc
+itself and every file it matches is bumped in turn. With -r, so is every file below
+the directory (whose name matches --include, if given) which has a version.
+
+Options:
+ -h, --help        print this help
+ --color           when to highlight the versions in the output of --list and --diff,
+                   always, never or auto (the default, if stdout is a terminal and
+                   $NO_COLOR is not set)
+ -q, --quiet       do not print what is being done (nor old -> new)
+ -v, --verbose     also print which file is read, the versions found in it, which
+                   of them are selected and why lookalikes were skipped
+ -l, --list        list versions found in the file, use the index with --position
+ -i, --interactive show each version which would be bumped (all of them unless
+                   --position is given) and ask whether to bump it
+ --pick            pick the versions to bump from a list in the terminal, starting
+                   with the one at --position
+ -p, --position    position of the version to increment, a range of them (1-3), a
+                   list of both (0,2,5-7), counted from the end (-1 or \"last\" for the
+                   last one) or \"all\" (defaults to 0)
+ -s, --stdout      do not modify file in-place but print to stdout
+ --shell-output    print OLD_VERSION='...' NEW_VERSION='...' CHANGED=1 (or 0) which
+                   can be passed to eval, everything else goes to stderr
+ --report json     print a JSON object per line for every replaced version (file,
+                   position, old, new and the byte offsets start and end of the old
+                   one), everything else goes to stderr
+ -n, --dry-run     do not write anything but print which files would be written and
+                   which downloads, commits, pushes, pull requests and webhook
+                   requests would be made
+ --diff            like --dry-run but print a unified diff of the files which would
+                   be written (which can be applied with git apply)
+ -r, --recursive   bump the files below the given directory instead of a single
+                   file and print which were changed, hidden directories are skipped
+ --include         only files whose name matches the glob (e.g. PKGBUILD or
+                   '*.toml'), can be repeated
+ --exclude         skip files and directories whose name matches the glob, can be
+                   repeated
+ --fail-on-empty   exit with an error (5) if the glob matches no file instead of
+                   just saying so
+ -f, --force       write read-only files (their mode is restored afterwards)
+ --keep-mtime      keep the modification time of the files which are written (their
+                   mode is always kept)
+ --no-undo         do not record the changes in .verinc/undo of the current directory
+ --watch           after the bump keep running and bump each of the files again
+                   whenever it is changed (by something else than verinc)
+ --backup          copy the files to <file>.bak before writing them
+ --backup-suffix   like --backup but with the given suffix (e.g. .orig or ~)
+ --durable         flush the written files to disk and keep a journal
+                   (.verinc-journal) while writing multiple files, so that an
+                   interrupted run is detected by the next one
+ -j, --jobs        how many downloads (checksums, signatures, watch pages),
+                   workspace manifests and files written by bump-all are handled
+                   at once (defaults to the number of CPUs), the files matched by
+                   a glob or -r are bumped one after another
+ --json-errors     print failures as JSON objects with the kind (see the exit
+                   status below), the file, the position and the message
+ --offline         fail right away instead of using the network (upstreams,
+                   downloads, pull requests and webhooks)
+ --proxy           proxy for all requests, e.g. \"http://proxy:3128\" (defaults to
+                   $HTTPS_PROXY or $HTTP_PROXY, hosts in $NO_PROXY are reached
+                   directly)
+ --stats           print the files and bytes scanned, the versions found and replaced,
+                   the network requests and the time spent per phase to stderr
+                   (added to the JSON with --hook)
+ --token-file      file with the API token (or \"github = <token>\", \"gitlab = ...\"
+                   and \"registry = ...\" lines), overrides the [tokens] references
+                   of .verinc.toml and $GITHUB_TOKEN, $GITLAB_TOKEN or
+                   $REGISTRY_TOKEN
+ --line            only consider the versions on the given line (starting at 1) or
+                   range of lines (10-20), --position counts just those
+ --match           only consider the versions on the lines matching the regex
+                   ('^pkgver='), --position counts just those
+ --exclude-lines   skip the versions on the lines matching the regex
+                   ('sha256sums'), --position counts just the rest
+ --regex           find the versions with a custom regex with major, minor, patch
+                   (and fourth) named groups, only those are replaced
+                   ('v(?<major>\\d+)_(?<minor>\\d+)'), just for incrementing
+ --include-ips     also consider versions which look like a part of an IP address
+                   (e.g. 192.168.1.10), they are skipped by default
+ --skip-urls       skip versions inside of http(s):// URLs
+ --components      number of components of the versions, 2 (MAJOR.MINOR, --patch
+                   increments the minor version), 3 (the default) or 4
+                   (MAJOR.MINOR.PATCH.BUILD, dotted quads are no longer skipped as
+                   IP addresses)
+ --with-v          make the v of v1.2.3 a part of the version, so that it counts
+                   towards the position and is kept
+ --add-v           like --with-v but also add the v to the versions written
+ --strip-v         like --with-v but remove the v from the versions written
+ --zero-pad        also match components with leading zeros and keep their width
+                   (1.09.0 to 1.10.0, 1.002.0 to 1.003.0)
+ --strip-build     drop the build metadata (1.2.3+20240101) when incrementing, it is
+                   kept by default
+ --from-tag        increment the version of the latest vX.Y.Z Git tag instead of
+                   the one in the file and write the result into the file
+ --from-env        set the version to the value of the given environment variable
+ --set             set the version to the given one instead of incrementing it (an
+                   epoch of the old version is kept unless the new one has one)
+ --hook            run as a release hook of cargo-release or semantic-release: the
+                   version is read from $NEW_VERSION (unless --from-env is given),
+                   nothing is written if $DRY_RUN is \"true\" and the result is
+                   printed as JSON
+ --major           increment major version
+ --minor           increment minor version
+ --patch           increment patch version (default)
+ --set-major, --set-minor, --set-patch
+                   set just that component to the given number instead, can be
+                   combined
+ --finalize        drop the pre-release of the version (1.3.0-rc.4 to 1.3.0) instead
+                   of incrementing it
+ --rel             increment just pkgrel= (2 to 3, 2.1 to 3) with --format pkgbuild
+                   or Release: with --format rpmspec, for rebuilds
+ --dec             decrement the version at the given level instead (down to zero,
+                   the lower components are kept)
+ --step            increment by the given number instead of 1 (1.2.3 to 1.2.6 with
+                   --step 3), releasing a pre-release takes one step
+ --build           increment the fourth component of MAJOR.MINOR.PATCH.BUILD versions
+                   (implies --components 4 unless given)
+ --pre             increment the number at the end of the pre-release (1.0.0-rc.2 to
+                   1.0.0-rc.3, 1.0.0_beta3 to 1.0.0_beta4), versions without one are
+                   kept
+ --epoch           increment the epoch of EPOCH:X.Y.Z (1 if there is none) and reset
+                   the rest to zeros, other bumps keep the epoch (versions after a v
+                   get none, as in v2.0.0)
+ --format          bump just the version of the project according to the format of
+                   the file instead of the version at --position: cargo
+                   ([package].version of Cargo.toml), npm (the top-level \"version\"
+                   of package.json), yaml (the value at --path), pkgbuild (pkgver=,
+                   which also resets pkgrel= to 1), rpmspec (Version:, which also
+                   resets Release: to 1), debchangelog (adds a stanza for the new
+                   version to debian/changelog, by $DEBFULLNAME <$DEBEMAIL> or the
+                   Git user), xml (the text of the element at --path), ini (the
+                   value of --key), dockerfile-from (the tag of the FROM --image,
+                   the first one with a tag by default) or dockerfile-label (the
+                   LABEL --key, defaults to version)
+ --path            dot separated keys leading to the version with --format yaml
+                   (e.g. metadata.version, defaults to version) or the path of the
+                   element with --format xml (defaults to /project/version, the
+                   version of a Maven pom.xml, not of its parent or dependencies)
+ --key             section.key of the version with --format ini (defaults to
+                   metadata.version, as in setup.cfg) or the label with --format
+                   dockerfile-label
+ --image           name of the image (nginx or docker.io/library/nginx) whose tag
+                   is bumped with --format dockerfile-from
+ --changelog       with --format rpmspec, add an entry with the message to the
+                   %changelog (by $RPM_PACKAGER or the Git user), with --format
+                   debchangelog, the message of the stanza (defaults to \"New
+                   upstream release.\")
+ --plugin          name of a WASM plugin in ~/.config/verinc/plugins (or a path)
+                   which bumps the file or computes the next version
+ --script          rhai script which selects and transforms the versions at the
+                   position (use with -p all to let it decide on all of them)
+ --upstream        set the version to the newest release of an upstream, e.g.
+                   \"docker:library/nginx\" (of the variant of the tag, like
+                   -alpine), \"debian:debian/watch\" (evaluates the watch file),
+                   \"uscan:.\" (asks uscan), \"github:owner/repo\" (the newest
+                   release, or tag without releases), \"crates:name\" (the newest
+                   version on crates.io which was not yanked) or \"pypi:name\"
+                   (the newest release on PyPI)
+ --from-github     same as --upstream github:<owner/repo>
+ --from-crates     same as --upstream crates:<name>
+ --from-pypi       same as --upstream pypi:<name>
+ --nvchecker       set the version of each file to the newver nvchecker found for
+                   its entry (named as the pkgname of a PKGBUILD or the directory
+                   of the file) given the nvchecker.toml, files without one are
+                   skipped
+ --cache-ttl       seconds for which the upstream versions are cached in
+                   ~/.cache/verinc (defaults to 3600), with --offline the cached
+                   versions are used regardless of their age
+ --no-cache        always ask the upstream
+ --flatpak         download the sources of a Flatpak manifest whose url changed and
+                   update their sha256
+ --update-sums     download the sources of a PKGBUILD whose url changed and update
+                   their sha256sums, sha512sums and b2sums
+ --srcinfo         also update pkgver, pkgrel, source and the checksums in the
+                   .SRCINFO next to the PKGBUILD
+ --verify          URL of the new upstream release ({{version}} is replaced) whose
+                   signature is checked before anything is written
+ --verify-with     \"gpg\", \"gpg:<keyring>\", \"minisign:<public key>\" or
+                   \"signify:<public key file>\" (defaults to \"gpg\")
+ --verify-signature
+                   URL of the signature (defaults to the release URL with .asc,
+                   .minisig or .sig appended)
+ --workspace       update references to the bumped crate or package in the other
+                   Cargo.toml or package.json files of its workspace
+ --cargo-update    run \"cargo update -p <crate>\" after bumping a Cargo.toml
+ --release-please  path to a release-please manifest whose entry for the package
+                   containing the file is updated as well
+ --sbom            path to a CycloneDX or SPDX document whose main component gets
+                   the new version as well, can be given multiple times
+ --changelog-file  path to a CHANGELOG.md (Keep a Changelog) which gets a
+                   \"## [new] - YYYY-MM-DD\" section above the latest release
+ --move-unreleased move what is under \"## [Unreleased]\" of --changelog-file to
+                   the new section
+ --commit          commit the written files to the current branch after the bump
+                   (implies --require-clean)
+ --require-clean   refuse to write anything if the Git repository of the files has
+                   uncommitted changes (untracked files are fine)
+ --commit-message  message of the commit (defaults to \"chore: bump to {{new}}\"),
+                   {{old}}, {{new}}, {{file}} and {{report}} are replaced
+ --pre-hook        shell command which runs in the project directory (of
+                   .verinc.toml or the file) before anything is written, with
+                   $OLD_VERSION, $NEW_VERSION and $FILE set, the bump is aborted if
+                   it fails, can be repeated (after the pre hooks of .verinc.toml)
+ --post-hook       like --pre-hook but runs after the files were written (and
+                   before --commit)
+ --unsafe-hooks    run the hooks with the whole environment, outside of the
+                   project directory and without a timeout (by default they just
+                   get PATH, HOME, the locale and alike and are killed after 5m)
+ --open-pr         commit the changes to a new branch, push it and open a pull
+                   (merge) request on GitHub or GitLab, uses GITHUB_TOKEN or
+                   GITLAB_TOKEN
+ --pr-branch       name of the branch (defaults to \"verinc/{{new}}\")
+ --pr-title        title of the pull request and the commit message (defaults
+                   to \"Bump version to {{new}}\")
+ --pr-body         body of the pull request (defaults to \"{{report}}\"), {{old}},
+                   {{new}}, {{file}} and {{report}} are replaced in all of the above
+ --webhook         URL which receives a JSON notification after a successful bump
+ --webhook-template
+                   the JSON to send (defaults to a document with the file, old
+                   and new version, the changes and a \"text\" summary), {{old}},
+                   {{new}}, {{file}}, {{report}} and {{changes}} are replaced
+
+Exit status:
+ 0 success, 1 other failure, 2 invalid arguments, 3 cannot read or write a file,
+ 4 cannot parse a file or version, 5 no version found, 6 refused by a check (e.g.
+ read-only file, bad signature, versions which don't agree), 7 network failure
+ (files matched by a glob or -r without a version are skipped, the others are bumped
+ and the exit status is 5)
+
+Examples
+ # Increment patch version of the first version found in-place
+ verinc foo.txt
+
+ # List all versions found together with their index
+ verinc --list foo.txt
+
+ # Increment major version of the third version found and print to stdout
+ verinc --major --stdout --position 2 file
+
+ # Write the next minor version after the latest Git tag into the file
+ verinc --minor --from-tag foo.txt
+
+ # As a cargo-release pre-release-hook or a semantic-release prepareCmd
+ verinc --hook README.md
+ NEXT=${{nextRelease.version}} verinc --hook --from-env NEXT README.md
+
+ # Pin the image in a Dockerfile to the newest nginx release
+ verinc --upstream docker:library/nginx Dockerfile
+
+ # Bump a python-* PKGBUILD to the newest release on PyPI
+ verinc --format pkgbuild --from-pypi requests --update-sums PKGBUILD
+
+ # Apply what `nvchecker` found to the PKGBUILDs of its entries
+ verinc --nvchecker nvchecker.toml --format pkgbuild 'pkgs/*/PKGBUILD'
+
+ # Bump a Flathub manifest together with the checksum of its source
+ verinc --flatpak org.example.Foo.yaml
+
+ # Bump a PKGBUILD together with the checksums of its sources
+ verinc --update-sums PKGBUILD
+
+ # Bump a PKGBUILD, reset its pkgrel and keep the .SRCINFO in sync
+ verinc --format pkgbuild --srcinfo PKGBUILD
+
+ # Advance the pkgrel of the PKGBUILD each time it is edited
+ verinc --rel --format pkgbuild --watch PKGBUILD
+
+ # Refuse the bump unless the new release is signed by a trusted key
+ verinc --upstream debian:. --verify 'https://example.com/foo-{{version}}.tar.gz' PKGBUILD
+
+ # Bump according to the Conventional Commits since the latest tag
+ verinc auto Cargo.toml
+
+ # Bump the version everywhere the project declares it
+ verinc bump-all --minor
+ verinc bump minor
+
+ # Bump Cargo.toml and write its new version into the README and the docs
+ verinc sync --minor --format cargo Cargo.toml README.md docs/install.md
+
+ # Bump a workspace crate and all requirements on it
+ verinc --minor --workspace --cargo-update crates/core/Cargo.toml
+ verinc --minor --workspace packages/core/package.json
+
+ # Bump the file and its entry in the release-please manifest
+ verinc --release-please .release-please-manifest.json packages/foo/package.json
+
+ # Bump the crate and move the unreleased changes under a new heading
+ verinc --minor --changelog-file CHANGELOG.md --move-unreleased Cargo.toml
+
+ # Bump the crate and keep its SBOM in sync
+ verinc --minor --sbom sbom.cdx.json Cargo.toml
+
+ # Revert a bump of the wrong version
+ verinc --position 1 foo.txt
+ verinc undo
+
+ # Bump the version and commit the change
+ verinc --minor --commit --commit-message 'Release {{new}}' Cargo.toml
+
+ # Pin the newest nginx release and open a pull request with the change
+ verinc --upstream docker:library/nginx --open-pr Dockerfile
+"
+    );
+}
+
+fn list_versions(selector: &Selector, content: &str) {
+    for (idx, ver) in selector.list_versions(content).iter().enumerate() {
+        println!("{idx}: {}", color::version(ver));
+    }
+}
+
+/// Returns the directory containing `path`.
+fn dir_of(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Returns `path` relative to the current directory if it is inside of it, so that the
+/// output doesn't depend on where the files were found from.
+fn relative(path: &Path) -> PathBuf {
+    env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .map_or_else(|| path.to_path_buf(), Path::to_path_buf)
+}
+
+/// Returns a human readable list of `changes`.
+fn report(file: &str, changes: &[Change]) -> String {
+    let report: Vec<_> = changes
+        .iter()
+        .map(|change| format!("- {file}: {} -> {}", change.old, change.new))
+        .collect();
+    report.join("\n")
+}
+
+#[cfg(feature = "wasm")]
+fn with_plugin(name: &str, args: &Args, content: &str) -> String {
+    use verinc::{plugin::Plugin, FileFormat};
+
+    let Some(path) = verinc::plugin::find(name) else {
+        error(Failure::Io, &format!("Error: Cannot find plugin '{name}'!"));
+    };
+    let plugin = match Plugin::load(&path) {
+        Ok(plugin) => plugin,
+        Err(err) => error(
+            Failure::Other,
+            &format!("Error: Cannot load plugin '{name}': {err}!"),
+        ),
+    };
+
+    if plugin.is_format() {
+        match plugin.bump(content, &args.version) {
+            Ok(result) => result,
+            Err(err) => error(Failure::Other, &format!("Error: {err}!")),
+        }
+    } else if plugin.is_scheme() {
+        let result = args.selector.inc_with(
+            content,
+            args.position.clone(),
+            args.version.clone(),
+            &plugin,
+        );
+        result.unwrap_or_else(|err| error(Failure::Other, &format!("Error: {err}!")))
+    } else {
+        error(
+            Failure::Other,
+            &format!("Error: Plugin '{name}' is neither a file format nor a version scheme!"),
+        )
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+fn with_plugin(_: &str, _: &Args, _: &str) -> String {
+    error(
+        Failure::Usage,
+        "Error: verinc was built without plugin support (the \"wasm\" feature)!",
+    )
+}
+
+#[cfg(feature = "scripting")]
+use verinc::script::Script;
+
+#[cfg(not(feature = "scripting"))]
+type Script = std::convert::Infallible;
+
+#[cfg(feature = "scripting")]
+fn load_script(path: &str) -> Script {
+    match Script::load(Path::new(path)) {
+        Ok(script) => script,
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot load script '{path}': {err}!"),
+        ),
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+fn load_script(_: &str) -> Script {
+    error(
+        Failure::Usage,
+        "Error: verinc was built without script support (the \"scripting\" feature)!",
+    )
+}
+
+#[cfg(feature = "scripting")]
+fn run_script(script: &Script, args: &Args, content: &str) -> String {
+    match script.inc(
+        content,
+        &args.selector,
+        args.position.clone(),
+        args.version.clone(),
+    ) {
+        Ok(result) => result,
+        Err(err) => error(Failure::Other, &format!("Error: {err}!")),
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+fn run_script(script: &Script, _: &Args, _: &str) -> String {
+    match *script {}
+}
+
+#[cfg(feature = "scripting")]
+fn script_after(script: &Script, changes: &[Change]) {
+    if let Err(err) = script.after(changes) {
+        error(Failure::Other, &format!("Error: {err}!"));
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+fn script_after(script: &Script, _: &[Change]) {
+    match *script {}
+}
+
+/// Checks the signature of the release of the new version, exits if it is not valid.
+fn verify(args: &Args, url: &str, changes: &[Change]) {
+    use verinc::signature::{self, Verifier};
+
+    let Some(change) = changes.first() else {
+        return;
+    };
+    let verifier = match Verifier::parse(&args.verify_with) {
+        Ok(verifier) => verifier,
+        Err(err) => error(Failure::Usage, &format!("Error: {err}!")),
+    };
+
+    let vars = [("version", change.new.as_str())];
+    let url = verinc::template::render(url, &vars);
+    let signature_url = match &args.verify_signature {
+        Some(template) => verinc::template::render(template, &vars),
+        None => format!("{url}{}", verifier.extension()),
+    };
+
+    if args.dry_run {
+        return info!(
+            "Would download {url} and {signature_url} and check the signature ({})",
+            args.verify_with
+        );
+    }
+    if let Err(err) = signature::verify(&url, &signature_url, &verifier) {
+        error(Failure::Guard, &format!("Error: {err}!"));
+    }
+}
+
+/// Reads the project configuration at `path` and passes its `[tokens]` on.
+fn project_config(path: &Path) -> Config {
+    let config = std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|content| Config::parse(&content));
+    match config {
+        Ok(config) => {
+            verinc::token::set_references(config.tokens.clone());
+            config
+        }
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot parse '{}': {err}!", path.display()),
+        ),
+    }
+}
+
+/// Returns the newest version of the upstream `spec`, cached unless disabled. `current`
+/// is the version in the file.
+fn upstream(args: &Args, spec: &str, current: &str) -> Result<String, String> {
+    match verinc::cache::Cache::new().filter(|_| !args.no_cache) {
+        Some(mut cache) => {
+            if let Some(ttl) = args.cache_ttl {
+                cache.ttl = std::time::Duration::from_secs(ttl);
+            }
+            verinc::upstream::latest_cached(spec, current, &cache)
+        }
+        None => verinc::upstream::latest(spec, current),
+    }
+}
+
+/// Returns the version in the environment variable `var`.
+fn env_version(selector: &Selector, var: &str) -> String {
+    let Ok(version) = env::var(var) else {
+        error(Failure::Usage, &format!("Error: ${var} is not set!"));
+    };
+    checked_version(selector, &version, &format!("${var}"))
+}
+
+/// Returns `version` without the `v` prefix, exits if it is not a version.
+fn checked_version(selector: &Selector, version: &str, source: &str) -> String {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    if selector.next(version, Version::Patch).is_err() {
+        error(
+            Failure::Parse,
+            &format!("Error: Invalid version '{version}' in {source}!"),
+        );
+    }
+    version.to_string()
+}
+
+/// Prints what would be written (or its diff with --diff) and done after the bump.
+fn dry_run(
+    args: &Args,
+    file: &str,
+    content: &str,
+    result: &str,
+    changes: &[Change],
+    updates: &[(PathBuf, String)],
+) {
+    for (path, new) in updates {
+        if !args.diff {
+            info!("Would write {}", relative(path).display());
+            continue;
+        }
+        let old = if path == Path::new(file) {
+            content.to_string()
+        } else {
+            fs::read_to_string(path).unwrap_or_default()
+        };
+        let name = relative(path).to_string_lossy().replace('\\', "/");
+        let name = name.trim_start_matches("./");
+        let diff = verinc::diff::unified(&old, new, &format!("a/{name}"), &format!("b/{name}"));
+        let diff = color::diff(&diff, &args.selector);
+        if MACHINE_OUTPUT.load(Ordering::Relaxed) {
+            eprint!("{diff}");
+        } else {
+            print!("{diff}");
+        }
+    }
+
+    let mut written: Vec<_> = updates.iter().map(|(path, _)| path.clone()).collect();
+
+    if args.cargo_update {
+        let name = workspace::crate_name(file, result);
+        info!("Would run \"cargo update -p {name}\"");
+        written.push(workspace::lock_file(Path::new(file)));
+    }
+
+    if args.script.is_some() {
+        info!("Would call after() of the script");
+    }
+
+    if args.commit && !written.is_empty() {
+        pr::dry_run_commit(args, file, changes, &written);
+    }
+    if args.open_pr && !written.is_empty() {
+        pr::dry_run(args, file, changes, &written);
+    }
+
+    if let Some(url) = &args.webhook {
+        if let Some(payload) = notify::payload(&args.webhook_template, file, changes) {
+            info!("Would POST to {url}:\n  {payload}");
+        }
+    }
+}
+
+/// Prints the old and the new version as shell assignments which are safe to `eval`.
+fn shell_output(args: &Args, content: &str, changes: &[Change]) {
+    let (old, new) = match changes.first() {
+        Some(change) => (change.old.as_str(), change.new.as_str()),
+        None => {
+            let versions = args.selector.list_versions(content);
+            let version = versions
+                .get(args.position.resolve(versions.len() as u32).first() as usize)
+                .copied()
+                .unwrap_or_default();
+            (version, version)
+        }
+    };
+    println!(
+        "OLD_VERSION={} NEW_VERSION={} CHANGED={}",
+        shell_quote(old),
+        shell_quote(new),
+        u8::from(!changes.is_empty())
+    );
+}
+
+/// Prints a JSON object per line for each of `changes`.
+fn json_report(file: &str, changes: &[Change]) {
+    use verinc::webhook::escape;
+
+    for change in changes {
+        println!(
+            "{{\"file\": \"{}\", \"position\": {}, \"old\": \"{}\", \"new\": \"{}\", \"start\": {}, \"end\": {}}}",
+            escape(file),
+            change.position,
+            escape(&change.old),
+            escape(&change.new),
+            change.span.start,
+            change.span.end
+        );
+    }
+}
+
+/// Quotes `s` for a POSIX shell.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Prints the result of a release hook as JSON.
+fn hook_result(file: &str, changes: &[Change], dry_run: bool, stats: Option<&Stats>) {
+    use verinc::webhook::{changes as json_changes, escape};
+
+    let (old, new) = changes
+        .first()
+        .map_or(("", ""), |c| (c.old.as_str(), c.new.as_str()));
+    let stats = stats.map_or(String::new(), |stats| {
+        format!(", \"stats\": {}", stats.json())
+    });
+    println!(
+        "{{\"file\": \"{}\", \"old\": \"{}\", \"new\": \"{}\", \"changes\": {}, \"dry_run\": {dry_run}{stats}}}",
+        escape(file),
+        escape(old),
+        escape(new),
+        json_changes(changes)
+    );
+}
+
+/// Prints the versions found in `content`, which of them are selected and the matches
+/// which were skipped.
+fn explain(args: &Args, content: &str) {
+    let line = |offset: usize| content[..offset].matches('\n').count() + 1;
+    for (range, reason) in args.selector.skipped(content) {
+        verbose!(
+            "Skipped {} on line {}: {reason}",
+            &content[range.clone()],
+            line(range.start)
+        );
+    }
+    let spans = args.selector.spans(content);
+    let position = args.position.resolve(spans.len() as u32);
+    for (idx, span) in spans.into_iter().enumerate() {
+        let selected = position.contains(idx as u32);
+        verbose!(
+            "Found {} at position {idx} on line {}{}",
+            &content[span.clone()],
+            line(span.start),
+            if selected { " (selected)" } else { "" }
+        );
+    }
+}
+
+/// Returns the error to report if nothing was bumped because there is no version at the
+/// position.
+fn no_match(args: &Args, file: &str, content: &str) -> Option<String> {
+    let count = args.selector.list_versions(content).len();
+    if count == 0 {
+        return Some(format!("Error: No version found in '{file}'!"));
+    }
+    if let Position::FromEnd(n) = args.position {
+        if n as usize > count {
+            return Some(format!("Error: No version at position -{n} in '{file}'!"));
+        }
+    } else if !matches!(args.position, Position::All) {
+        let pos = args.position.first();
+        if pos as usize >= count {
+            return Some(format!("Error: No version at position {pos} in '{file}'!"));
+        }
+    }
+    None
+}
+
+/// Returns the component called for by the commits since the latest tag of the
+/// repository containing `path` and prints why.
+fn auto_version(path: &str) -> Version {
+    let dir = Some(dir_of(Path::new(path)))
+        .filter(|dir| dir.is_dir())
+        .unwrap_or(Path::new("."));
+    let tag = match verinc::git::latest_tag(dir) {
+        Ok(tag) => tag.map(|tag| format!("v{tag}")),
+        Err(err) => error(Failure::Other, &format!("Error: Cannot run git: {err}!")),
+    };
+    let messages = match verinc::git::messages_since(dir, tag.as_deref()) {
+        Ok(messages) => messages,
+        Err(err) => error(Failure::Other, &format!("Error: {err}!")),
+    };
+    let since = tag.unwrap_or_else(|| "the first commit".to_string());
+
+    for message in &messages {
+        let subject = message.lines().next().unwrap_or_default();
+        match verinc::conventional::component(message) {
+            Some(version) => status!("{}: {subject}", format!("{version:?}").to_lowercase()),
+            None => verbose!("none: {subject}"),
+        }
+    }
+    match verinc::conventional::highest(messages.iter().map(String::as_str)) {
+        Some(version) => {
+            let name = format!("{version:?}").to_lowercase();
+            status!(
+                "Bumping {name}, the highest of {} commits since {since}",
+                messages.len()
+            );
+            version
+        }
+        None => error(
+            Failure::NoMatch,
+            &format!("Error: No breaking, feat or fix commit since {since}!"),
+        ),
+    }
+}
+
+/// Returns the new version nvchecker found for the entry of `file`, `None` if it has
+/// none.
+fn nvchecker_version(selector: &Selector, config: &str, file: &str) -> Option<String> {
+    let nvchecker = match verinc::nvchecker::Nvchecker::load(Path::new(config)) {
+        Ok(nvchecker) => nvchecker,
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot read the nvchecker results: {err}!"),
+        ),
+    };
+    let pkgname = fs::read_to_string(file)
+        .ok()
+        .and_then(|content| verinc::pkgbuild::package_name(&content));
+    let dir = verinc::path::canonicalize(dir_of(Path::new(file)))
+        .ok()
+        .and_then(|dir| Some(dir.file_name()?.to_string_lossy().into_owned()));
+
+    let Some(name) = pkgname
+        .into_iter()
+        .chain(dir)
+        .find(|name| nvchecker.entries.contains(name))
+    else {
+        status!("No nvchecker entry for {file}, skipped");
+        return None;
+    };
+    match nvchecker.version(&name) {
+        Some(version) => Some(checked_version(
+            selector,
+            version,
+            &format!("the newver of {name}"),
+        )),
+        None => {
+            status!("nvchecker has not checked {name} yet, {file} skipped");
+            None
+        }
+    }
+}
+
+fn tag_version(path: &str, version: Version) -> String {
+    match verinc::git::latest_tag(dir_of(Path::new(path))) {
+        Ok(Some(tag)) => match verinc::next(&tag, version) {
+            Ok(next) => next,
+            Err(_) => error(Failure::Parse, &format!("Error: Invalid tag 'v{tag}'!")),
+        },
+        Ok(None) => error(Failure::NoMatch, "Error: No vX.Y.Z tag found!"),
+        Err(err) => error(Failure::Other, &format!("Error: Cannot run git: {err}!")),
+    }
+}
+
+/// The class of a failure, its value is the exit status. The values are stable so that
+/// scripts can branch on them.
+#[derive(Debug, Clone, Copy)]
+enum Failure {
+    /// Anything not covered below, e.g. a failing script or plugin.
+    Other = 1,
+    /// Invalid arguments.
+    Usage = 2,
+    /// A file cannot be read or written.
+    Io = 3,
+    /// A file, version or another input cannot be parsed.
+    Parse = 4,
+    /// No version (or another expected item) was found.
+    NoMatch = 5,
+    /// A check refused the bump, e.g. a read-only file, a bad signature or versions
+    /// which don't agree.
+    Guard = 6,
+    /// A request to a remote service failed.
+    Network = 7,
+}
+
+impl Failure {
+    fn kind(self) -> &'static str {
+        match self {
+            Failure::Other => "other",
+            Failure::Usage => "usage",
+            Failure::Io => "io",
+            Failure::Parse => "parse",
+            Failure::NoMatch => "no_match",
+            Failure::Guard => "guard",
+            Failure::Network => "network",
+        }
+    }
+}
+
+/// Set by --json-errors.
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+/// 0 with --quiet, 1 by default and 2 with --verbose.
+static VERBOSITY: AtomicU8 = AtomicU8::new(1);
+
+fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// Set by --shell-output and --report.
+static MACHINE_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// The file (and the position in it) being bumped, reported by JSON errors.
+static CONTEXT: Mutex<Option<(String, Option<u32>)>> = Mutex::new(None);
+
+fn set_context(file: &str, position: &Position) {
+    let position = match position {
+        Position::Nth(pos) => Some(*pos),
+        _ => None,
+    };
+    if let Ok(mut context) = CONTEXT.lock() {
+        *context = Some((file.to_string(), position));
+    }
+}
+
+fn error(failure: Failure, msg: &str) -> ! {
+    print_error(failure, msg);
+    exit(failure as i32);
+}
+
+/// Prints the error like [`error`] but lets the run go on.
+fn print_error(failure: Failure, msg: &str) {
+    let msg = &verinc::token::redact(msg);
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        use verinc::webhook::escape;
+
+        let context = CONTEXT.lock().ok().and_then(|context| context.clone());
+        let (file, position) = match context {
+            Some((file, position)) => (
+                format!("\"{}\"", escape(&file)),
+                position.map_or("null".to_string(), |pos| pos.to_string()),
+            ),
+            None => ("null".to_string(), "null".to_string()),
+        };
+        let msg = msg.strip_prefix("Error: ").unwrap_or(msg);
+        let msg = msg.strip_suffix('!').unwrap_or(msg);
+        eprintln!(
+            "{{\"error\": {{\"kind\": \"{}\", \"code\": {}, \"file\": {file}, \"position\": {position}, \"message\": \"{}\"}}}}",
+            failure.kind(),
+            failure as i32,
+            escape(msg)
+        );
+    } else {
+        eprintln!("{}", msg);
+    }
+}
+
+struct Args {
+    list: bool,
+    interactive: bool,
+    /// The versions selected when --pick starts.
+    pick: Option<Position>,
+    stdout: bool,
+    shell_output: bool,
+    report: bool,
+    fail_on_empty: bool,
+    recursive: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    force: bool,
+    durable: bool,
+    backup: Option<String>,
+    keep_mtime: bool,
+    no_undo: bool,
+    watch: bool,
+    dry_run: bool,
+    diff: bool,
+    offline: bool,
+    no_cache: bool,
+    proxy: Option<String>,
+    stats: bool,
+    token_file: Option<String>,
+    cache_ttl: Option<u64>,
+    workspace: bool,
+    flatpak: bool,
+    update_sums: bool,
+    srcinfo: bool,
+    verify: Option<String>,
+    verify_with: String,
+    verify_signature: Option<String>,
+    cargo_update: bool,
+    manifest: Option<String>,
+    sboms: Vec<String>,
+    changelog_file: Option<String>,
+    move_unreleased: bool,
+    from_tag: bool,
+    from_env: Option<String>,
+    set: Option<String>,
+    dec: bool,
+    step: u64,
+    finalize: bool,
+    rel: bool,
+    set_components: Vec<(Version, u64)>,
+    hook: bool,
+    upstream: Option<String>,
+    plugin: Option<String>,
+    format: Option<Format>,
+    nvchecker: Option<String>,
+    regex: Option<Regex>,
+    script: Option<String>,
+    pre_hooks: Vec<String>,
+    post_hooks: Vec<String>,
+    unsafe_hooks: bool,
+    commit: bool,
+    require_clean: bool,
+    commit_message: String,
+    open_pr: bool,
+    pr_branch: String,
+    pr_title: String,
+    pr_body: String,
+    webhook: Option<String>,
+    webhook_template: String,
+    selector: Selector,
+    position: Position,
+    version: Version,
+}
+
+fn value(iter: &mut impl Iterator<Item = String>, what: &str) -> String {
+    match iter.next() {
+        Some(value) => value,
+        None => error(Failure::Usage, &format!("Error: Missing {what}!")),
+    }
+}
+
+/// Parses the command line and returns the options together with the file. Returns
+/// `None` if the usage should be printed instead.
+fn parse_args(mut iter: impl Iterator<Item = String>) -> Option<(Args, String)> {
+    let mut args = Args {
+        list: false,
+        interactive: false,
+        pick: None,
+        stdout: false,
+        shell_output: false,
+        report: false,
+        fail_on_empty: false,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        force: false,
+        durable: false,
+        backup: None,
+        keep_mtime: false,
+        no_undo: false,
+        watch: false,
+        dry_run: false,
+        diff: false,
+        offline: false,
+        no_cache: false,
+        proxy: None,
+        stats: false,
+        token_file: None,
+        cache_ttl: None,
+        workspace: false,
+        flatpak: false,
+        update_sums: false,
+        srcinfo: false,
+        verify: None,
+        verify_with: "gpg".to_string(),
+        verify_signature: None,
+        cargo_update: false,
+        manifest: None,
+        sboms: Vec::new(),
+        changelog_file: None,
+        move_unreleased: false,
+        from_tag: false,
+        from_env: None,
+        set: None,
+        dec: false,
+        step: 1,
+        finalize: false,
+        rel: false,
+        set_components: Vec::new(),
+        hook: false,
+        upstream: None,
+        plugin: None,
+        format: None,
+        nvchecker: None,
+        regex: None,
+        script: None,
+        pre_hooks: Vec::new(),
+        post_hooks: Vec::new(),
+        unsafe_hooks: false,
+        commit: false,
+        require_clean: false,
+        commit_message: "chore: bump to {new}".to_string(),
+        open_pr: false,
+        pr_branch: "verinc/{new}".to_string(),
+        pr_title: "Bump version to {new}".to_string(),
+        pr_body: "{report}".to_string(),
+        webhook: None,
+        webhook_template: notify::TEMPLATE.to_string(),
+        selector: Selector::default(),
+        position: Position::Nth(0),
+        version: Version::Patch,
+    };
+
+    color::set("auto");
+    let mut components = None;
+    let mut position = false;
+    let mut path = None;
+    let mut key = None;
+    let mut image = None;
+    let mut changelog = None;
+    let mut file = None;
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return None,
+            "--color" => color_mode(&value(&mut iter, "color")),
+            _ if arg.starts_with("--color=") => color_mode(&arg["--color=".len()..]),
+            "-q" | "--quiet" => VERBOSITY.store(0, Ordering::Relaxed),
+            "-v" | "--verbose" => VERBOSITY.store(2, Ordering::Relaxed),
+            "-s" | "--stdout" => args.stdout = true,
+            "-l" | "--list" => args.list = true,
+            "-i" | "--interactive" => args.interactive = true,
+            "--pick" => args.pick = Some(Position::All),
+            "--shell-output" => args.shell_output = true,
+            "--report" => match value(&mut iter, "report format").as_str() {
+                "json" => args.report = true,
+                _ => error(Failure::Usage, "Error: Invalid report format!"),
+            },
+            "--fail-on-empty" => args.fail_on_empty = true,
+            "-r" | "--recursive" => {
+                args.recursive = true;
+                file = Some(value(&mut iter, "directory"));
+            }
+            "--include" => args.include.push(value(&mut iter, "glob")),
+            "--exclude" => args.exclude.push(value(&mut iter, "glob")),
+            "-f" | "--force" => args.force = true,
+            "--durable" => args.durable = true,
+            "--keep-mtime" => args.keep_mtime = true,
+            "--no-undo" => args.no_undo = true,
+            "--watch" => args.watch = true,
+            "--backup" => args.backup = Some(".bak".to_string()),
+            "--backup-suffix" => args.backup = Some(value(&mut iter, "suffix")),
+            "-n" | "--dry-run" => args.dry_run = true,
+            "--diff" => {
+                args.dry_run = true;
+                args.diff = true;
+            }
+            "--json-errors" => JSON_ERRORS.store(true, Ordering::Relaxed),
+            "--offline" => args.offline = true,
+            "--no-cache" => args.no_cache = true,
+            "--stats" => args.stats = true,
+            "-j" | "--jobs" => verinc::parallel::set_jobs(jobs(&mut iter)),
+            "--proxy" => args.proxy = Some(value(&mut iter, "proxy")),
+            "--token-file" => args.token_file = Some(value(&mut iter, "token file")),
+            "--cache-ttl" => match value(&mut iter, "TTL").parse() {
+                Ok(ttl) => args.cache_ttl = Some(ttl),
+                Err(_) => error(Failure::Usage, "Error: Invalid cache TTL!"),
+            },
+            "-p" | "--position" => {
+                position = true;
+                args.position = match Position::parse(&value(&mut iter, "position")) {
+                    Some(position) => position,
+                    None => error(Failure::Usage, "Error: Invalid position!"),
+                };
+            }
+            "--line" => {
+                let line = value(&mut iter, "line");
+                let (start, end) = line.split_once('-').unwrap_or((&line, &line));
+                args.selector.lines = match (start.parse(), end.parse()) {
+                    (Ok(start), Ok(end)) if 0 < start && start <= end => Some(start..=end),
+                    _ => error(Failure::Usage, "Error: Invalid line!"),
+                };
+            }
+            "--match" => match Regex::new(&value(&mut iter, "match pattern")) {
+                Ok(regex) => args.selector.line_match = Some(regex),
+                Err(err) => error(
+                    Failure::Usage,
+                    &format!("Error: Invalid match pattern!\n{err}"),
+                ),
+            },
+            "--regex" => match Regex::new(&value(&mut iter, "regex")) {
+                Ok(regex) => args.regex = Some(regex),
+                Err(err) => error(Failure::Usage, &format!("Error: Invalid regex!\n{err}")),
+            },
+            "--exclude-lines" => match Regex::new(&value(&mut iter, "exclude pattern")) {
+                Ok(regex) => args.selector.line_exclude = Some(regex),
+                Err(err) => error(
+                    Failure::Usage,
+                    &format!("Error: Invalid exclude pattern!\n{err}"),
+                ),
+            },
+            "--include-ips" => args.selector.skip_ips = false,
+            "--skip-urls" => args.selector.skip_urls = true,
+            "--with-v" => args.selector.v_prefix = VPrefix::Keep,
+            "--add-v" => args.selector.v_prefix = VPrefix::Add,
+            "--strip-v" => args.selector.v_prefix = VPrefix::Strip,
+            "--zero-pad" => args.selector.zero_pad = true,
+            "--strip-build" => args.selector.strip_build = true,
+            "--components" => {
+                components = Some(match value(&mut iter, "components").as_str() {
+                    "2" => Components::Two,
+                    "3" => Components::Three,
+                    "4" => Components::Four,
+                    _ => error(Failure::Usage, "Error: Invalid number of components!"),
+                })
+            }
+            "--from-tag" => args.from_tag = true,
+            "--from-env" => args.from_env = Some(value(&mut iter, "variable")),
+            "--set" => args.set = Some(value(&mut iter, "version")),
+            "--dec" => args.dec = true,
+            "--step" => {
+                args.step = match number(&mut iter) {
+                    0 => error(Failure::Usage, "Error: The step must be at least 1!"),
+                    step => step,
+                }
+            }
+            "--finalize" => args.finalize = true,
+            "--rel" => args.rel = true,
+            "--set-major" => args
+                .set_components
+                .push((Version::Major, number(&mut iter))),
+            "--set-minor" => args
+                .set_components
+                .push((Version::Minor, number(&mut iter))),
+            "--set-patch" => args
+                .set_components
+                .push((Version::Patch, number(&mut iter))),
+            "--hook" => args.hook = true,
+            "--upstream" => args.upstream = Some(value(&mut iter, "upstream")),
+            "--from-github" => {
+                args.upstream = Some(format!("github:{}", value(&mut iter, "repository")))
+            }
+            "--from-crates" => {
+                args.upstream = Some(format!("crates:{}", value(&mut iter, "crate")))
+            }
+            "--nvchecker" => args.nvchecker = Some(value(&mut iter, "nvchecker config")),
+            "--from-pypi" => args.upstream = Some(format!("pypi:{}", value(&mut iter, "project"))),
+            "--plugin" => args.plugin = Some(value(&mut iter, "plugin")),
+            "--path" => path = Some(value(&mut iter, "path")),
+            "--key" => key = Some(value(&mut iter, "key")),
+            "--image" => image = Some(value(&mut iter, "image")),
+            "--changelog" => changelog = Some(value(&mut iter, "changelog message")),
+            "--format" => match Format::parse(&value(&mut iter, "format")) {
+                Some(format) => args.format = Some(format),
+                None => error(Failure::Usage, "Error: Invalid format!"),
+            },
+            "--script" => args.script = Some(value(&mut iter, "script")),
+            "--flatpak" => args.flatpak = true,
+            "--update-sums" => args.update_sums = true,
+            "--srcinfo" => args.srcinfo = true,
+            "--verify" => args.verify = Some(value(&mut iter, "URL")),
+            "--verify-with" => args.verify_with = value(&mut iter, "verifier"),
+            "--verify-signature" => args.verify_signature = Some(value(&mut iter, "URL")),
+            "--workspace" => args.workspace = true,
+            "--cargo-update" => args.cargo_update = true,
+            "--release-please" => args.manifest = Some(value(&mut iter, "manifest")),
+            "--sbom" => args.sboms.push(value(&mut iter, "SBOM")),
+            "--changelog-file" => args.changelog_file = Some(value(&mut iter, "changelog")),
+            "--move-unreleased" => args.move_unreleased = true,
+            "--pre-hook" => args.pre_hooks.push(value(&mut iter, "hook")),
+            "--post-hook" => args.post_hooks.push(value(&mut iter, "hook")),
+            "--unsafe-hooks" => args.unsafe_hooks = true,
+            "--commit" => args.commit = true,
+            "--require-clean" => args.require_clean = true,
+            "--commit-message" => args.commit_message = value(&mut iter, "message"),
+            "--open-pr" => args.open_pr = true,
+            "--pr-branch" => args.pr_branch = value(&mut iter, "branch"),
+            "--pr-title" => args.pr_title = value(&mut iter, "title"),
+            "--pr-body" => args.pr_body = value(&mut iter, "body"),
+            "--webhook" => args.webhook = Some(value(&mut iter, "webhook")),
+            "--webhook-template" => args.webhook_template = value(&mut iter, "template"),
+            "--major" => args.version = Version::Major,
+            "--minor" => args.version = Version::Minor,
+            "--patch" => args.version = Version::Patch,
+            "--build" => args.version = Version::Build,
+            "--epoch" => args.version = Version::Epoch,
+            "--pre" => args.version = Version::Pre,
+            _ if arg.starts_with('-') || args.recursive => usage_error(),
+            _ => {
+                file = Some(arg);
+                break;
+            }
+        }
+    }
+
+    let filtered = args.selector.lines.is_some()
+        || args.selector.line_match.is_some()
+        || args.selector.line_exclude.is_some();
+    if args.format.is_some() && (position || filtered) {
+        error(
+            Failure::Usage,
+            "Error: --format cannot be combined with --position, --line, --match or --exclude-lines!",
+        );
+    }
+    let other_op = args.set.is_some()
+        || args.dec
+        || args.finalize
+        || !args.set_components.is_empty()
+        || args.from_tag
+        || args.from_env.is_some()
+        || args.upstream.is_some()
+        || args.nvchecker.is_some()
+        || args.plugin.is_some()
+        || args.script.is_some()
+        || args.format.is_some();
+    if args.regex.is_some() && other_op {
+        error(
+            Failure::Usage,
+            "Error: --regex can only be combined with --major, --minor, --patch, --build and --step!",
+        );
+    }
+    if args.watch && (args.commit || args.open_pr || args.hook) {
+        error(
+            Failure::Usage,
+            "Error: --watch cannot be combined with --commit, --open-pr or --hook!",
+        );
+    }
+    if args.commit && args.open_pr {
+        error(
+            Failure::Usage,
+            "Error: --commit cannot be combined with --open-pr, which commits to a new branch!",
+        );
+    }
+    if args.rel && !matches!(args.format, Some(Format::Pkgbuild | Format::Rpmspec(_))) {
+        error(
+            Failure::Usage,
+            "Error: --rel needs --format pkgbuild or rpmspec!",
+        );
+    }
+    match (&mut args.format, changelog) {
+        (Some(Format::Rpmspec(entry)), Some(message)) => {
+            *entry = Some(Entry {
+                author: author(env::var("RPM_PACKAGER").ok(), "$RPM_PACKAGER"),
+                time: now(),
+                message,
+            })
+        }
+        (Some(Format::DebChangelog(entry)), message) => {
+            let name = env::var("DEBFULLNAME").or_else(|_| env::var("NAME"));
+            let email = env::var("DEBEMAIL").or_else(|_| env::var("EMAIL"));
+            let from_env = match (name, email) {
+                (Ok(name), Ok(email)) => Some(format!("{name} <{email}>")),
+                _ => None,
+            };
+            entry.author = author(from_env, "$DEBFULLNAME and $DEBEMAIL");
+            entry.time = now();
+            if let Some(message) = message {
+                entry.message = message;
+            }
+        }
+        (_, Some(_)) => error(
+            Failure::Usage,
+            "Error: --changelog needs --format rpmspec or debchangelog!",
+        ),
+        _ => {}
+    }
+    match (&mut args.format, path) {
+        (Some(Format::Yaml(keys) | Format::Xml(keys)), Some(path)) => *keys = path,
+        (_, Some(_)) => error(Failure::Usage, "Error: --path needs --format yaml or xml!"),
+        _ => {}
+    }
+    match (&mut args.format, key) {
+        (Some(Format::Ini(keys) | Format::DockerLabel(keys)), Some(key)) => *keys = key,
+        (_, Some(_)) => error(
+            Failure::Usage,
+            "Error: --key needs --format ini or dockerfile-label!",
+        ),
+        _ => {}
+    }
+    match (&mut args.format, image) {
+        (Some(Format::DockerFrom(name)), Some(image)) => *name = Some(image),
+        (_, Some(_)) => error(
+            Failure::Usage,
+            "Error: --image needs --format dockerfile-from!",
+        ),
+        _ => {}
+    }
+    if args.interactive && !position {
+        args.position = Position::All;
+    }
+    // All of the versions are bumped, the picked ones are kept.
+    if let Some(pick) = &mut args.pick {
+        *pick = mem::replace(&mut args.position, Position::All);
+    }
+    args.selector.components = components.unwrap_or(match args.version {
+        Version::Build => Components::Four,
+        _ => Components::Three,
+    });
+    match file {
+        Some(file) => Some((args, file)),
+        None => usage_error(),
+    }
+}
+
+/// Returns the author of changelog entries, `from_env` (read from `vars`) or the Git
+/// user.
+fn author(from_env: Option<String>, vars: &str) -> String {
+    match from_env.or_else(|| verinc::git::author(Path::new("."))) {
+        Some(author) => author,
+        None => error(
+            Failure::Usage,
+            &format!("Error: Set {vars} or the Git user.name and user.email!"),
+        ),
+    }
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Parses the value of --set-major, --step and the like.
+fn number(iter: &mut impl Iterator<Item = String>) -> u64 {
+    match value(iter, "number").parse() {
+        Ok(number) => number,
+        Err(_) => error(Failure::Usage, "Error: Invalid number!"),
+    }
+}
+
+fn color_mode(mode: &str) {
+    if color::set(mode).is_none() {
+        error(Failure::Usage, "Error: Invalid color mode!");
+    }
+}
+
+/// Parses the value of --jobs.
+fn jobs(iter: &mut impl Iterator<Item = String>) -> usize {
+    match value(iter, "number of jobs").parse() {
+        Ok(jobs) if jobs > 0 => jobs,
+        _ => error(Failure::Usage, "Error: Invalid number of jobs!"),
+    }
+}
+
+fn usage_error() -> ! {
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        error(Failure::Usage, "Error: Invalid arguments, see --help!");
+    }
+    usage();
+    exit(Failure::Usage as i32);
+}
+
+/// Returns `content` with the versions at the position bumped as the arguments say.
+fn bump(args: &Args, file: &str, script: Option<&Script>, content: &str) -> String {
+    let result = if let Some(spec) = &args.upstream {
+        let versions = args.selector.list_versions(content);
+        let first = args.position.resolve(versions.len() as u32).first();
+        let current = versions.get(first as usize).copied().unwrap_or_default();
+        match upstream(args, spec, current) {
+            Ok(latest) => args.selector.set(content, args.position.clone(), &latest),
+            Err(err) => error(Failure::Network, &format!("Error: {err}!")),
+        }
+    } else if let Some(version) = &args.set {
+        let version = checked_version(&args.selector, version, "--set");
+        args.selector.set(content, args.position.clone(), &version)
+    } else if let Some(script) = script {
+        Ok(run_script(script, args, content))
+    } else if let Some(name) = &args.plugin {
+        Ok(with_plugin(name, args, content))
+    } else if let Some(var) = args
+        .from_env
+        .as_deref()
+        .or(args.hook.then_some("NEW_VERSION"))
+    {
+        let next = env_version(&args.selector, var);
+        args.selector.set(content, args.position.clone(), &next)
+    } else if args.from_tag {
+        let next = tag_version(file, args.version.clone());
+        args.selector.set(content, args.position.clone(), &next)
+    } else if let Some(config) = &args.nvchecker {
+        match nvchecker_version(&args.selector, config, file) {
+            Some(next) => args.selector.set(content, args.position.clone(), &next),
+            None => Ok(content.to_string()),
+        }
+    } else if !args.set_components.is_empty() {
+        let mut components = args.set_components.iter();
+        components.try_fold(content.to_string(), |result, (version, value)| {
+            args.selector
+                .set_component(&result, args.position.clone(), version.clone(), *value)
+        })
+    } else if args.finalize {
+        args.selector.finalize(content, args.position.clone())
+    } else if args.dec {
+        args.selector
+            .dec(content, args.position.clone(), args.version.clone())
+    } else if let Some(regex) = &args.regex {
+        let result = args.selector.inc_with_regex(
+            content,
+            regex,
+            args.position.clone(),
+            args.version.clone(),
+            args.step,
+        );
+        Ok(result.unwrap_or_else(|err| error(Failure::Usage, &format!("Error: {err}!"))))
+    } else {
+        args.selector.inc(
+            content,
+            args.position.clone(),
+            args.version.clone(),
+            args.step,
+        )
+    };
+    result.unwrap_or_else(|err| {
+        error(
+            Failure::Parse,
+            &format!("Error: Cannot bump '{file}': {err}!"),
+        )
+    })
+}
+
+/// Bumps `file` and returns whether it was (or would be) changed. Fails (after reporting
+/// it) if there is no version to bump, so that the other files are bumped anyway.
+fn run(args: &Args, file: &str, stats: &mut Stats) -> Result<bool, Failure> {
+    set_context(file, &args.position);
+
+    verinc::set_offline(args.offline);
+    verinc::set_proxy(args.proxy.as_deref());
+    verinc::token::set_token_file(args.token_file.as_deref().map(Path::new));
+    let config = verinc::config::find(dir_of(Path::new(file)))
+        .map(|path| (dir_of(&path).to_path_buf(), project_config(&path)));
+    let (root, mut hooks) = match config {
+        Some((root, config)) => {
+            let mut hooks = Hooks::new(&root, args.unsafe_hooks);
+            hooks.configure(&config);
+            (root, hooks)
+        }
+        None => {
+            let root = dir_of(Path::new(file)).to_path_buf();
+            let hooks = Hooks::new(&root, args.unsafe_hooks);
+            (root, hooks)
+        }
+    };
+    hooks.add(&args.pre_hooks, &args.post_hooks, &root);
+
+    if args.report && (args.shell_output || args.list || args.stdout || args.hook) {
+        error(
+            Failure::Usage,
+            "Error: --report cannot be combined with --shell-output, --list, --stdout or --hook!",
+        );
+    }
+    if args.shell_output && (args.list || args.stdout || args.hook) {
+        error(
+            Failure::Usage,
+            "Error: --shell-output cannot be combined with --list, --stdout or --hook!",
+        );
+    }
+    MACHINE_OUTPUT.store(args.shell_output || args.report, Ordering::Relaxed);
+    verinc::set_quiet(verbosity() == 0 || args.interactive);
+
+    // These run after the file is written, refuse them before touching anything.
+    if args.offline && !args.dry_run && (args.open_pr || args.webhook.is_some()) {
+        error(
+            Failure::Network,
+            "Error: --open-pr and --webhook need the network, which --offline disables!",
+        );
+    }
+    // Held until the run is over.
+    let _project =
+        (!args.list && !args.stdout && !args.dry_run).then(|| file::ProjectLock::acquire(&root));
+    verbose!("Reading {file}");
+    let Some((locked, content)) = file::Locked::open(Path::new(file)) else {
+        error(Failure::Io, &format!("Error: Cannot open file '{}'!", file));
+    };
+    if verbosity() > 1 {
+        explain(args, &content);
+    }
+    stats.scanned(&content);
+    stats.matches += args.selector.list_versions(&content).len() as u64;
+    stats.phase("read");
+
+    if args.list {
+        list_versions(&args.selector, &content);
+        return Ok(false);
+    }
+
+    let script = args.script.as_deref().map(load_script);
+    let result = match &args.format {
+        Some(format) if args.rel => match format.inc_rel(&content) {
+            Ok(result) => result,
+            Err(err) => error(
+                Failure::Parse,
+                &format!("Error: Cannot parse '{file}': {err}!"),
+            ),
+        },
+        Some(format) => {
+            let bump = |version: &str| {
+                if args.selector.list_versions(version).is_empty() {
+                    error(
+                        Failure::Parse,
+                        &format!("Error: Invalid version '{version}' in '{file}'!"),
+                    );
+                }
+                bump(args, file, script.as_ref(), version)
+            };
+            match format.bump(&content, bump) {
+                Ok(result) => result,
+                Err(err) => error(
+                    Failure::Parse,
+                    &format!("Error: Cannot parse '{file}': {err}!"),
+                ),
+            }
+        }
+        None => bump(args, file, script.as_ref(), &content),
+    };
+    let result = if args.interactive {
+        interactive::confirm(args, file, &content, &result)
+    } else if let Some(preselected) = &args.pick {
+        pick::pick(args, preselected, &content, &result)
+    } else {
+        result
+    };
+
+    let result = if args.flatpak {
+        let hash = |url: &str| {
+            if args.dry_run {
+                info!("Would download {url} to compute its sha256");
+                Ok("0".repeat(64))
+            } else {
+                verinc::flatpak::sha256(url)
+            }
+        };
+        match verinc::flatpak::update(&content, &result, hash) {
+            Ok(result) => result,
+            Err(err) => error(Failure::Network, &format!("Error: {err}!")),
+        }
+    } else if args.update_sums {
+        let hash = |url: &str| {
+            if args.dry_run {
+                info!("Would download {url} to compute its checksums");
+                Ok(Digests {
+                    size: 0,
+                    sha256: "0".repeat(64),
+                    sha512: "0".repeat(128),
+                    b2: "0".repeat(128),
+                })
+            } else {
+                verinc::pkgbuild::digests(url)
+            }
+        };
+        match verinc::pkgbuild::update_sums(&content, &result, hash) {
+            Ok(result) => result,
+            Err(err) => error(Failure::Network, &format!("Error: {err}!")),
+        }
+    } else {
+        result
+    };
+
+    let changed = result != content;
+    if !changed {
+        if let Some(msg) = no_match(args, file, &content) {
+            print_error(Failure::NoMatch, &msg);
+            return Err(Failure::NoMatch);
+        }
+    }
+
+    let changes = match &args.format {
+        Some(format) => format.changes(&content, &result),
+        None => args.selector.changes(&content, &result),
+    };
+    stats.replacements += changes.len() as u64;
+    stats.phase("bump");
+    if let Some(url) = &args.verify {
+        verify(args, url, &changes);
+        stats.phase("verify");
+    }
+
+    if args.stdout {
+        println!("{}", result);
+        return Ok(changed);
+    }
+
+    if args.hook && (args.dry_run || env::var("DRY_RUN").is_ok_and(|dry| dry == "true")) {
+        stats.phase("integrations");
+        hook_result(file, &changes, true, args.stats.then_some(stats));
+        return Ok(changed);
+    }
+
+    let manifest = args.manifest.as_ref().and_then(|manifest| {
+        Some((
+            manifest,
+            workspace::release_please(manifest, file, &changes)?,
+        ))
+    });
+    let sboms: Vec<_> = args
+        .sboms
+        .iter()
+        .filter_map(|path| Some((path, workspace::sbom(path, &changes)?)))
+        .collect();
+
+    // Not touching the file keeps its mtime, which matters for make and file watchers.
+    let mut updates = Vec::new();
+    if result != content {
+        updates.push((PathBuf::from(file), result.clone()));
+    }
+    if let Some((path, content)) = manifest {
+        updates.push((PathBuf::from(path), content));
+    }
+    for (path, content) in sboms {
+        updates.push((PathBuf::from(path), content));
+    }
+    if let Some(path) = &args.changelog_file {
+        if let Some(content) = workspace::changelog(path, &changes, args.move_unreleased) {
+            updates.push((PathBuf::from(path), content));
+        }
+    }
+    if args.srcinfo && result != content {
+        updates.extend(workspace::srcinfo(file, &result));
+    }
+    if args.workspace {
+        for (path, content) in workspace::dependents(file, &content, &result) {
+            match updates.iter_mut().find(|(p, _)| *p == path) {
+                Some(update) => update.1 = content,
+                None => updates.push((path, content)),
+            }
+        }
+    }
+
+    for (_, content) in updates.iter().filter(|(path, _)| path != Path::new(file)) {
+        stats.scanned(content);
+    }
+
+    let (old, new) = changes
+        .first()
+        .map_or(("", ""), |change| (&*change.old, &*change.new));
+    hooks.pre(Path::new(file), old, new, args.dry_run);
+    locked.verify();
+    if args.dry_run {
+        dry_run(args, file, &content, &result, &changes, &updates);
+        hooks.post(Path::new(file), old, new, true);
+        if args.shell_output {
+            shell_output(args, &content, &changes);
+        }
+        if args.report {
+            json_report(file, &changes);
+        }
+        return Ok(changed);
+    }
+    let options = file::Options {
+        force: args.force,
+        durable: args.durable,
+        keep_mtime: args.keep_mtime,
+        backup: args.backup.as_deref(),
+        no_undo: args.no_undo,
+        locked: std::slice::from_ref(&locked),
+    };
+    file::write_all(dir_of(Path::new(file)), &updates, options);
+    // A post hook may bump the file again.
+    drop(locked);
+    stats.phase("write");
+    let mut written: Vec<_> = updates.into_iter().map(|(path, _)| path).collect();
+
+    if args.cargo_update {
+        written.push(workspace::cargo_update(file, &result));
+    }
+    hooks.post(Path::new(file), old, new, false);
+
+    if let Some(script) = &script {
+        script_after(script, &changes);
+    }
+
+    if args.commit && !written.is_empty() {
+        pr::commit(args, file, &changes, &written);
+    }
+    if args.open_pr && !written.is_empty() {
+        pr::open(args, file, &changes, &written);
+    }
+
+    if let Some(url) = &args.webhook {
+        notify::webhook(url, &args.webhook_template, file, &changes);
+    }
+
+    stats.phase("integrations");
+    if args.hook {
+        hook_result(file, &changes, false, args.stats.then_some(stats));
+    }
+    if args.shell_output {
+        shell_output(args, &content, &changes);
+    }
+    if args.report {
+        json_report(file, &changes);
+    }
+    Ok(changed)
+}
+
+/// Returns the files `arg` refers to, the ones it matches if it's a glob or the ones
+/// with a version below it with -r.
+fn files(args: &Args, arg: &str) -> Vec<String> {
+    if args.recursive {
+        return match verinc::glob::walk(Path::new(arg), &args.include, &args.exclude) {
+            Ok(files) => files
+                .iter()
+                .filter(|file| {
+                    fs::read_to_string(file)
+                        .is_ok_and(|content| !args.selector.list_versions(&content).is_empty())
+                })
+                .map(|file| file.to_string_lossy().into_owned())
+                .collect(),
+            Err(err) => error(Failure::Io, &format!("Error: Cannot read '{arg}': {err}!")),
+        };
+    }
+    if !verinc::glob::is_pattern(arg) || Path::new(arg).exists() {
+        return vec![arg.to_string()];
+    }
+
+    let files: Vec<_> = match verinc::glob::files(arg) {
+        Ok(files) => files
+            .iter()
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect(),
+        Err(err) => error(
+            Failure::Io,
+            &format!("Error: Cannot expand '{arg}': {err}!"),
+        ),
+    };
+    if files.is_empty() {
+        if args.fail_on_empty {
+            error(
+                Failure::NoMatch,
+                &format!("Error: No file matches '{arg}'!"),
+            );
+        }
+        status!("No file matches '{arg}'");
+    }
+    for file in &files {
+        status!("Matched {file}");
+    }
+    files
+}
+
+/// Refuses to continue if any of the repositories containing `files` has uncommitted
+/// changes, so that the bump is not mixed up with them.
+fn require_clean(files: &[String]) {
+    let mut dirs: Vec<_> = files.iter().map(|file| dir_of(Path::new(file))).collect();
+    dirs.sort();
+    dirs.dedup();
+    for dir in dirs {
+        match verinc::git::uncommitted(dir) {
+            Ok(changed) if changed.is_empty() => {}
+            Ok(changed) => {
+                for path in &changed {
+                    verbose!("Uncommitted: {path}");
+                }
+                error(
+                    Failure::Guard,
+                    &format!(
+                        "Error: The repository has uncommitted changes ({}), commit or stash them first!",
+                        changed.join(", ")
+                    ),
+                );
+            }
+            Err(err) => error(Failure::Other, &format!("Error: {err}!")),
+        }
+    }
+}
+
+fn main() {
+    let mut iter = env::args().skip(1).peekable();
+    if iter
+        .next_if(|arg| arg == "bump-all" || arg == "bump")
+        .is_some()
+    {
+        if bump_all::run(iter).is_none() {
+            usage_error();
+        }
+        return;
+    }
+    if iter.next_if(|arg| arg == "check-aur").is_some() {
+        if check_aur::run(iter).is_none() {
+            usage_error();
+        }
+        return;
+    }
+    if iter.next_if(|arg| arg == "sync").is_some() {
+        if bump_all::sync(iter).is_none() {
+            usage_error();
+        }
+        return;
+    }
+    if iter.next_if(|arg| arg == "undo").is_some() {
+        if undo::run(iter).is_none() {
+            usage_error();
+        }
+        return;
+    }
+    if iter.next_if(|arg| arg == "self-update").is_some() {
+        if self_update::run(iter).is_none() {
+            usage_error();
+        }
+        return;
+    }
+
+    let auto = iter.next_if(|arg| arg == "auto").is_some();
+    match parse_args(iter) {
+        Some((mut args, file)) => {
+            if auto {
+                args.version = auto_version(&file);
+            }
+            let mut stats = Stats::new();
+            let files = files(&args, &file);
+            if (args.require_clean || args.commit) && !args.stdout && !args.list {
+                require_clean(&files);
+            }
+            let mut failure = None;
+            let changed: Vec<_> = files
+                .iter()
+                .filter(|file| {
+                    run(&args, file, &mut stats).unwrap_or_else(|err| {
+                        failure = Some(err);
+                        false
+                    })
+                })
+                .collect();
+            if args.recursive {
+                let verb = if args.dry_run {
+                    "Would change"
+                } else {
+                    "Changed"
+                };
+                status!("{verb} {} of {} files:", changed.len(), files.len());
+                for file in changed {
+                    status!("  {file}");
+                }
+            }
+            if args.stats && !args.hook {
+                stats.print();
+            }
+            if args.watch {
+                watcher::watch(&files, |file| {
+                    let _ = run(&args, file, &mut stats);
+                });
+            }
+            if let Some(failure) = failure {
+                exit(failure as i32);
+            }
+        }
+        None => usage(),
+    }
+}
@@ -0,0 +1,39 @@
+use verinc::{
+    template::render,
+    webhook::{self, escape},
+    Change,
+};
+
+use crate::{error, report, Failure};
+
+pub(crate) const TEMPLATE: &str = r#"{"file": "{file}", "old": "{old}", "new": "{new}", "changes": {changes}, "text": "{file}: {old} -> {new}"}"#;
+
+/// Sends the report of `changes` to the webhook at `url`. Nothing is sent if nothing
+/// changed.
+pub(crate) fn webhook(url: &str, template: &str, file: &str, changes: &[Change]) {
+    if let Some(payload) = payload(template, file, changes) {
+        if let Err(err) = webhook::send(url, &payload) {
+            error(Failure::Network, &format!("Error: {err}!"));
+        }
+    }
+}
+
+/// Returns the rendered `template` or `None` if nothing changed.
+pub(crate) fn payload(template: &str, file: &str, changes: &[Change]) -> Option<String> {
+    let first = changes.first()?;
+
+    let report = escape(&report(file, changes));
+    let file = escape(file);
+    let old = escape(&first.old);
+    let new = escape(&first.new);
+    let changes = webhook::changes(changes);
+    let vars = [
+        ("file", file.as_str()),
+        ("old", old.as_str()),
+        ("new", new.as_str()),
+        ("report", report.as_str()),
+        ("changes", changes.as_str()),
+    ];
+
+    Some(render(template, &vars))
+}
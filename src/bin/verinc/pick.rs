@@ -0,0 +1,151 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    ops::Range,
+    process::{Command, Stdio},
+};
+
+use verinc::Position;
+
+use crate::{color, error, interactive, Args, Failure};
+
+/// How many characters of the line are shown on each side of a version.
+const CONTEXT: usize = 30;
+
+/// The terminal in which the picker runs, its settings are restored when dropped.
+struct Terminal {
+    tty: File,
+    saved: String,
+}
+
+impl Terminal {
+    /// Switches the controlling terminal to reading single keys without echo.
+    fn open() -> Option<Terminal> {
+        let tty = File::options()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .ok()?;
+        let saved = stty(&tty, &["-g"])?;
+        stty(&tty, &["-icanon", "-echo", "min", "1"])?;
+        Some(Terminal {
+            tty,
+            saved: saved.trim().to_string(),
+        })
+    }
+
+    fn key(&mut self) -> Option<u8> {
+        let mut byte = [0];
+        self.tty.read_exact(&mut byte).ok()?;
+        Some(byte[0])
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        stty(&self.tty, &[&self.saved]);
+    }
+}
+
+/// Runs `stty` on `tty` and returns its output.
+fn stty(tty: &File, args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::from(tty.try_clone().ok()?))
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Lets the user pick which of the versions `result` changes in `content` to bump,
+/// starting with the ones at `preselected`, and returns `content` with just those
+/// replaced.
+pub(crate) fn pick(args: &Args, preselected: &Position, content: &str, result: &str) -> String {
+    let spans = args.selector.spans(content);
+    let preselected = preselected.resolve(spans.len() as u32);
+    let mut selected: Vec<_> = (0..spans.len())
+        .map(|idx| preselected.contains(idx as u32))
+        .collect();
+    let Some(mut terminal) = Terminal::open() else {
+        error(Failure::Usage, "Error: --pick needs a terminal!");
+    };
+
+    let lines: Vec<_> = spans.iter().map(|span| line(content, span)).collect();
+    let mut cursor = 0;
+    let mut drawn = false;
+    let confirmed = loop {
+        let mut screen = String::new();
+        if drawn {
+            // Back to the first line of the list.
+            screen += &format!("\x1b[{}A\r\x1b[J", lines.len() + 1);
+        }
+        screen += "Pick the versions to bump (up/down or k/j, space to toggle, a for all, enter to bump, q to quit):\n";
+        for (idx, (number, before, version, after)) in lines.iter().enumerate() {
+            screen += &format!(
+                "{} [{}] {idx}, line {number}: {before}{}{after}\n",
+                if idx == cursor { '>' } else { ' ' },
+                if selected[idx] { 'x' } else { ' ' },
+                color::version(version)
+            );
+        }
+        let _ = terminal.tty.write_all(screen.as_bytes());
+        drawn = true;
+
+        match terminal.key() {
+            Some(b'k') => cursor = cursor.saturating_sub(1),
+            Some(b'j') => cursor = (cursor + 1).min(lines.len().saturating_sub(1)),
+            Some(b'\x1b') => match (terminal.key(), terminal.key()) {
+                (Some(b'['), Some(b'A')) => cursor = cursor.saturating_sub(1),
+                (Some(b'['), Some(b'B')) => {
+                    cursor = (cursor + 1).min(lines.len().saturating_sub(1))
+                }
+                _ => {}
+            },
+            Some(b' ') if !selected.is_empty() => selected[cursor] = !selected[cursor],
+            Some(b'a') => {
+                let all = selected.iter().all(|s| *s);
+                selected.fill(!all);
+            }
+            Some(b'\n' | b'\r') => break true,
+            Some(b'q') | None => break false,
+            _ => {}
+        }
+    };
+    drop(terminal);
+
+    if !confirmed {
+        return content.to_string();
+    }
+    let changes = args.selector.changes(content, result);
+    interactive::apply(content, &changes, |change| {
+        selected[change.position as usize]
+    })
+}
+
+/// Returns the number of the line containing `span` and the part of it shown around the
+/// version.
+fn line<'a>(content: &'a str, span: &Range<usize>) -> (usize, String, &'a str, String) {
+    let start = content[..span.start].rfind('\n').map_or(0, |idx| idx + 1);
+    let end = content[span.end..]
+        .find('\n')
+        .map_or(content.len(), |idx| span.end + idx);
+    let number = content[..start].matches('\n').count() + 1;
+
+    let before: Vec<_> = content[start..span.start].trim_start().chars().collect();
+    let before = if before.len() > CONTEXT {
+        format!("…{}", String::from_iter(&before[before.len() - CONTEXT..]))
+    } else {
+        String::from_iter(before)
+    };
+    let after: Vec<_> = content[span.end..end].trim_end().chars().collect();
+    let after = if after.len() > CONTEXT {
+        format!("{}…", String::from_iter(&after[..CONTEXT]))
+    } else {
+        String::from_iter(after)
+    };
+    (number, before, &content[span.clone()], after)
+}
@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use verinc::{forge::PullRequest, git, template::render, Change};
+
+use crate::{dir_of, error, relative, report, Args, Failure};
+
+/// Returns the `templates` with the versions of `changes` filled in.
+fn render_each<const N: usize>(
+    templates: [&str; N],
+    file: &str,
+    changes: &[Change],
+) -> [String; N] {
+    let Some(first) = changes.first() else {
+        error(Failure::Guard, "Error: No version was changed!");
+    };
+
+    let report = report(file, changes);
+    let vars = [
+        ("old", first.old.as_str()),
+        ("new", first.new.as_str()),
+        ("file", file),
+        ("report", report.as_str()),
+    ];
+    templates.map(|template| render(template, &vars))
+}
+
+/// Returns the branch, the title and the body of the pull request.
+fn render_all(args: &Args, file: &str, changes: &[Change]) -> (String, String, String) {
+    let [branch, title, body] = render_each(
+        [&args.pr_branch, &args.pr_title, &args.pr_body],
+        file,
+        changes,
+    );
+    (branch, title, body)
+}
+
+/// Returns `written` as absolute paths, git runs in the directory of the file.
+fn absolute(written: &[PathBuf]) -> Vec<PathBuf> {
+    written
+        .iter()
+        .map(|path| verinc::path::canonicalize(path).unwrap_or_else(|_| path.clone()))
+        .collect()
+}
+
+/// Prints what [`commit`] would do.
+pub(crate) fn dry_run_commit(args: &Args, file: &str, changes: &[Change], written: &[PathBuf]) {
+    let [message] = render_each([&args.commit_message], file, changes);
+    info!("Would commit with message '{message}':");
+    for path in written {
+        info!("  {}", relative(path).display());
+    }
+}
+
+/// Commits `written` to the current branch with the rendered --commit-message.
+pub(crate) fn commit(args: &Args, file: &str, changes: &[Change], written: &[PathBuf]) {
+    let [message] = render_each([&args.commit_message], file, changes);
+    let files = absolute(written);
+    let files: Vec<_> = files.iter().map(PathBuf::as_path).collect();
+
+    match git::commit(dir_of(Path::new(file)), &files, &message) {
+        Ok(()) => status!("Committed '{message}'"),
+        Err(err) => error(Failure::Other, &format!("Error: {err}!")),
+    }
+}
+
+/// Prints what [`open`] would do.
+pub(crate) fn dry_run(args: &Args, file: &str, changes: &[Change], written: &[PathBuf]) {
+    let (branch, title, body) = render_all(args, file, changes);
+    let dir = dir_of(Path::new(file));
+    let base = git::current_branch(dir).unwrap_or_else(|_| "<current branch>".to_string());
+    let remote = git::remote_url(dir, "origin").unwrap_or_else(|_| "origin".to_string());
+
+    info!("Would create branch '{branch}' and commit to it with message '{title}':");
+    for path in written {
+        info!("  {}", relative(path).display());
+    }
+    info!("Would push '{branch}' to {remote}");
+    info!("Would open a pull request of '{branch}' into '{base}' titled '{title}':");
+    for line in body.lines() {
+        info!("  {line}");
+    }
+}
+
+/// Commits `written` to a new branch, pushes it and opens a pull request with a report of
+/// `changes`.
+pub(crate) fn open(args: &Args, file: &str, changes: &[Change], written: &[PathBuf]) {
+    let (branch, title, body) = render_all(args, file, changes);
+
+    let dir = dir_of(Path::new(file));
+    let files = absolute(written);
+    let files: Vec<_> = files.iter().map(PathBuf::as_path).collect();
+
+    let result = git::current_branch(dir).and_then(|base| {
+        git::create_branch(dir, &branch)?;
+        git::commit(dir, &files, &title)?;
+        git::push(dir, "origin", &branch)?;
+        git::checkout(dir, &base)?;
+
+        let pr = PullRequest {
+            title: &title,
+            body: &body,
+            head: &branch,
+            base: &base,
+        };
+        verinc::forge::open(&git::remote_url(dir, "origin")?, &pr)
+    });
+
+    match result {
+        Ok(url) => info!("{url}"),
+        Err(err) => error(Failure::Network, &format!("Error: {err}!")),
+    }
+}
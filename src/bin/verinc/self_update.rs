@@ -0,0 +1,120 @@
+use std::{env, fs::remove_file, path::Path, process, sync::atomic::Ordering};
+
+use verinc::{
+    self_update::{self, Release},
+    signature::{self, Verifier},
+};
+
+use crate::{dir_of, error, value, Failure, JSON_ERRORS};
+
+/// Runs `verinc self-update`. Returns `None` if the usage should be printed instead.
+pub(crate) fn run(mut iter: impl Iterator<Item = String>) -> Option<()> {
+    let mut check = false;
+    let mut dry_run = false;
+    let mut verifier = None;
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-n" | "--dry-run" => dry_run = true,
+            "--verify-with" => verifier = Some(value(&mut iter, "verifier")),
+            "--proxy" => verinc::set_proxy(Some(&value(&mut iter, "proxy"))),
+            "--token-file" => {
+                verinc::token::set_token_file(Some(Path::new(&value(&mut iter, "token file"))))
+            }
+            "--json-errors" => JSON_ERRORS.store(true, Ordering::Relaxed),
+            _ => return None,
+        }
+    }
+
+    let verifier = verifier.map(|spec| match Verifier::parse(&spec) {
+        Ok(verifier) => verifier,
+        Err(err) => error(Failure::Usage, &format!("Error: {err}!")),
+    });
+
+    let release = match self_update::latest() {
+        Ok(release) => release,
+        Err(err) => error(Failure::Network, &format!("Error: {err}!")),
+    };
+    let current = env!("CARGO_PKG_VERSION");
+    if !self_update::is_newer(&release.version, current) {
+        println!("verinc {current} is up to date");
+        return Some(());
+    }
+    if check {
+        println!(
+            "verinc {} is available (installed {current})",
+            release.version
+        );
+        return Some(());
+    }
+
+    let exe = match env::current_exe().and_then(|exe| verinc::path::canonicalize(&exe)) {
+        Ok(exe) => exe,
+        Err(err) => error(
+            Failure::Io,
+            &format!("Error: Cannot find the running executable: {err}!"),
+        ),
+    };
+    if dry_run {
+        println!(
+            "Would download {} and check it against {}",
+            release.url, release.checksum_url
+        );
+        if let Some(verifier) = &verifier {
+            println!(
+                "Would check its signature {}{}",
+                release.url,
+                verifier.extension()
+            );
+        }
+        println!(
+            "Would replace {} with verinc {}",
+            exe.display(),
+            release.version
+        );
+        return Some(());
+    }
+
+    install(&release, &exe, verifier.as_ref());
+    println!("Updated verinc {current} to {}", release.version);
+    Some(())
+}
+
+/// Downloads the binary next to `exe`, checks it and moves it over `exe`.
+fn install(release: &Release, exe: &Path, verifier: Option<&Verifier>) {
+    let expected = match self_update::checksum(release) {
+        Ok(checksum) => checksum,
+        Err(err) => error(Failure::Network, &format!("Error: {err}!")),
+    };
+
+    // In the same directory, so that the rename doesn't cross file systems.
+    let temp = dir_of(exe).join(format!(".verinc-update-{}", process::id()));
+    let fail = |failure, msg: &str| -> ! {
+        let _ = remove_file(&temp);
+        error(failure, msg)
+    };
+
+    match self_update::download(&release.url, &temp) {
+        Ok(checksum) if checksum == expected => {}
+        Ok(checksum) => fail(
+            Failure::Guard,
+            &format!(
+                "Error: The checksum of {} is {checksum} but {expected} was expected!",
+                release.url
+            ),
+        ),
+        Err(err) => fail(Failure::Network, &format!("Error: {err}!")),
+    }
+
+    if let Some(verifier) = verifier {
+        let signature_url = format!("{}{}", release.url, verifier.extension());
+        if let Err(err) = signature::verify_file(&temp, &signature_url, verifier) {
+            fail(Failure::Guard, &format!("Error: {err}!"));
+        }
+    }
+
+    if let Err(err) = self_update::replace(&temp, exe) {
+        fail(Failure::Io, &format!("Error: {err}!"));
+    }
+}
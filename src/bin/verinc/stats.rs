@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+/// Metrics of a run, printed with --stats.
+pub(crate) struct Stats {
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+    pub files: u64,
+    pub bytes: u64,
+    pub matches: u64,
+    pub replacements: u64,
+}
+
+impl Stats {
+    pub(crate) fn new() -> Self {
+        Stats {
+            last: Instant::now(),
+            phases: Vec::new(),
+            files: 0,
+            bytes: 0,
+            matches: 0,
+            replacements: 0,
+        }
+    }
+
+    /// Ends the phase `name` which started when the previous one ended.
+    pub(crate) fn phase(&mut self, name: &'static str) {
+        let now = Instant::now();
+        self.phases.push((name, now - self.last));
+        self.last = now;
+    }
+
+    /// Counts a file which was read.
+    pub(crate) fn scanned(&mut self, content: &str) {
+        self.files += 1;
+        self.bytes += content.len() as u64;
+    }
+
+    /// Prints the metrics to stderr so that they don't mix with the output.
+    pub(crate) fn print(&self) {
+        let (requests, received) = verinc::network_stats();
+        eprintln!("Files scanned:     {} ({} bytes)", self.files, self.bytes);
+        eprintln!("Versions found:    {}", self.matches);
+        eprintln!("Replacements:      {}", self.replacements);
+        eprintln!("Network requests:  {requests} ({received} bytes received)");
+        for (name, elapsed) in &self.phases {
+            eprintln!("{:<19}{:.1} ms", format!("{name}:"), ms(*elapsed));
+        }
+    }
+
+    pub(crate) fn json(&self) -> String {
+        let (requests, received) = verinc::network_stats();
+        let phases: Vec<_> = self
+            .phases
+            .iter()
+            .map(|(name, elapsed)| format!("\"{name}\": {:.3}", ms(*elapsed)))
+            .collect();
+        format!(
+            "{{\"files\": {}, \"bytes\": {}, \"matches\": {}, \"replacements\": {}, \"requests\": {requests}, \"received\": {received}, \"phases_ms\": {{{}}}}}",
+            self.files,
+            self.bytes,
+            self.matches,
+            self.replacements,
+            phases.join(", ")
+        )
+    }
+}
+
+fn ms(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,73 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+use verinc::undo::{self, Log};
+
+use crate::{error, file, Failure, JSON_ERRORS};
+
+/// Runs `verinc undo`. Returns `None` if the usage should be printed instead.
+pub(crate) fn run(iter: impl Iterator<Item = String>) -> Option<()> {
+    let mut dry_run = false;
+    for arg in iter {
+        match arg.as_str() {
+            "-n" | "--dry-run" => dry_run = true,
+            "--json-errors" => JSON_ERRORS.store(true, Ordering::Relaxed),
+            _ => return None,
+        }
+    }
+
+    let mut log = Log::open(Path::new(""))
+        .unwrap_or_else(|err| error(Failure::Parse, &format!("Error: The undo log {err}!")));
+    let edits = log.pop();
+    if edits.is_empty() {
+        error(
+            Failure::NoMatch,
+            &format!("Error: There is nothing to undo in '{}'!", undo::PATH),
+        );
+    }
+
+    let mut files: Vec<&PathBuf> = edits.iter().map(|edit| &edit.file).collect();
+    files.dedup();
+    let updates: Vec<_> = files
+        .into_iter()
+        .map(|path| {
+            let Ok(content) = fs::read_to_string(path) else {
+                error(
+                    Failure::Io,
+                    &format!("Error: Cannot open file '{}'!", path.display()),
+                );
+            };
+            let edits: Vec<_> = edits.iter().filter(|edit| edit.file == *path).collect();
+            match undo::revert(&content, &edits) {
+                Ok(reverted) => (path.clone(), reverted),
+                Err(err) => error(Failure::Guard, &format!("Error: {err}, not undoing it!")),
+            }
+        })
+        .collect();
+
+    for (path, _) in &updates {
+        if dry_run {
+            status!("Would revert {}", path.display());
+        } else {
+            status!("Reverting {}", path.display());
+        }
+    }
+    if dry_run {
+        return Some(());
+    }
+    let options = file::Options {
+        no_undo: true,
+        ..Default::default()
+    };
+    file::write_all(Path::new(""), &updates, options);
+    if let Err(err) = log.save() {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot save the undo log, {err}!"),
+        );
+    }
+    Some(())
+}
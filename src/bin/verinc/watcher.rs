@@ -0,0 +1,48 @@
+use std::{
+    fs, thread,
+    time::{Duration, SystemTime},
+};
+
+/// How often the files are checked for changes.
+const INTERVAL: Duration = Duration::from_millis(500);
+
+/// What a change of a file is told by, `None` if it cannot be read.
+type Snapshot = Option<(SystemTime, u64)>;
+
+fn snapshot(file: &str) -> Snapshot {
+    let metadata = fs::metadata(file).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Calls `run` with each of the `files` which changes until the process is interrupted.
+/// A file is run once it stops changing, changes made by `run` itself are not noticed.
+pub(crate) fn watch(files: &[String], mut run: impl FnMut(&str)) -> ! {
+    status!(
+        "Watching {} files for changes, press Ctrl-C to stop",
+        files.len()
+    );
+    let mut seen: Vec<_> = files.iter().map(|file| snapshot(file)).collect();
+    loop {
+        thread::sleep(INTERVAL);
+        for (file, seen) in files.iter().zip(&mut seen) {
+            let mut current = snapshot(file);
+            if current == *seen {
+                continue;
+            }
+            // Editors may write a file in several steps.
+            loop {
+                thread::sleep(INTERVAL);
+                let next = snapshot(file);
+                if next == current {
+                    break;
+                }
+                current = next;
+            }
+            if current.is_some() {
+                status!("{file} changed");
+                run(file);
+            }
+            *seen = snapshot(file);
+        }
+    }
+}
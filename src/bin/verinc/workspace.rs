@@ -0,0 +1,324 @@
+use std::{
+    fs::read_to_string,
+    io::{stdout, IsTerminal},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use verinc::{cargo::Manifest, npm::Package, Change};
+
+use crate::{dir_of, error, relative, Failure};
+
+fn parse_manifest<'a>(path: &Path, content: &'a str) -> Manifest<'a> {
+    match Manifest::parse(content) {
+        Ok(manifest) => manifest,
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot parse '{}': {}!", path.display(), err),
+        ),
+    }
+}
+
+fn parse_package<'a>(path: &Path, content: &'a str) -> Package<'a> {
+    match Package::parse(content) {
+        Ok(package) => package,
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot parse '{}': {}!", path.display(), err),
+        ),
+    }
+}
+
+/// Returns the files of the workspace of the bumped crate or package with the references
+/// to it updated. The bumped file itself is returned under `file` if it refers to itself.
+pub(crate) fn dependents(file: &str, old: &str, new: &str) -> Vec<(PathBuf, String)> {
+    let path = Path::new(file);
+    let npm = path.file_name().is_some_and(|name| name == "package.json");
+
+    let (name, old_version, version) = if npm {
+        let old = parse_package(path, old);
+        let new = parse_package(path, new);
+        (
+            new.name().map(str::to_string),
+            old.version().map(str::to_string),
+            new.version().map(str::to_string),
+        )
+    } else {
+        let old = parse_manifest(path, old);
+        let new = parse_manifest(path, new);
+        (
+            new.name().map(str::to_string),
+            old.version().map(str::to_string),
+            new.version().map(str::to_string),
+        )
+    };
+
+    let (Some(name), Some(version)) = (name, version) else {
+        error(Failure::Parse, "Error: Missing package name or version!");
+    };
+    if old_version.as_ref() == Some(&version) {
+        return Vec::new();
+    }
+
+    let root = if npm {
+        verinc::npm::find_workspace_root(path)
+    } else {
+        verinc::cargo::find_workspace_root(path)
+    };
+    let root = match root {
+        Ok(Some(root)) => root,
+        Ok(None) => error(Failure::NoMatch, "Error: Cannot find the workspace!"),
+        Err(err) => error(
+            Failure::Io,
+            &format!("Error: Cannot find the workspace: {err}!"),
+        ),
+    };
+
+    let manifests = if npm {
+        verinc::npm::workspace_packages(&root)
+    } else {
+        verinc::cargo::workspace_manifests(&root)
+    };
+    let manifests = match manifests {
+        Ok(manifests) => manifests,
+        Err(err) => error(
+            Failure::Io,
+            &format!("Error: Cannot read the workspace: {err}!"),
+        ),
+    };
+    let bumped = verinc::path::canonicalize(path).ok();
+
+    let results = verinc::parallel::map(&manifests, |manifest| {
+        let content = if bumped.as_ref() == Some(manifest) {
+            new.to_string()
+        } else {
+            match read_to_string(manifest) {
+                Ok(content) => content,
+                Err(_) => error(
+                    Failure::Io,
+                    &format!("Error: Cannot open file '{}'!", manifest.display()),
+                ),
+            }
+        };
+
+        let result = if npm {
+            parse_package(manifest, &content).update_dependency(&name, &version)
+        } else {
+            parse_manifest(manifest, &content).update_dependency(&name, &version)
+        };
+        (result != content).then_some(result)
+    });
+
+    let mut updates = Vec::new();
+    for (manifest, result) in manifests.into_iter().zip(results) {
+        if let Some(result) = result {
+            if stdout().is_terminal() {
+                info!("{}: {name} -> {version}", relative(&manifest).display());
+            }
+
+            if bumped.as_ref() == Some(&manifest) {
+                updates.push((PathBuf::from(file), result));
+            } else {
+                updates.push((manifest, result));
+            }
+        }
+    }
+
+    updates
+}
+
+/// Returns the old and new version of the bump, or `None` if no version changed. All of the
+/// bumped versions have to agree for `what` to be updated.
+fn bumped(changes: &[Change], what: &str) -> Option<(String, String)> {
+    let first = changes.first()?;
+    if changes
+        .iter()
+        .any(|c| c.old != first.old || c.new != first.new)
+    {
+        error(
+            Failure::Guard,
+            &format!("Error: The bumped versions differ, cannot update {what}!"),
+        );
+    }
+    Some((first.old.clone(), first.new.clone()))
+}
+
+/// Returns the new content of the release-please manifest with the entry of the package
+/// containing `path` updated, or `None` if no version changed.
+pub(crate) fn release_please(
+    manifest_path: &str,
+    path: &str,
+    changes: &[Change],
+) -> Option<String> {
+    let (from, to) = bumped(changes, "the manifest")?;
+    let (from, to) = (from.as_str(), to.as_str());
+
+    let Ok(content) = read_to_string(manifest_path) else {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot open file '{}'!", manifest_path),
+        );
+    };
+    let manifest = match verinc::release_please::Manifest::parse(&content) {
+        Ok(manifest) => manifest,
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot parse '{}': {}!", manifest_path, err),
+        ),
+    };
+
+    let dir = dir_of(Path::new(manifest_path));
+    let (Ok(dir), Ok(file)) = (
+        verinc::path::canonicalize(dir),
+        verinc::path::canonicalize(Path::new(path)),
+    ) else {
+        error(Failure::Io, &format!("Error: Cannot resolve '{}'!", path));
+    };
+    let Ok(relative) = file.strip_prefix(&dir) else {
+        error(
+            Failure::NoMatch,
+            &format!(
+                "Error: '{}' is not inside the directory of the manifest!",
+                path
+            ),
+        );
+    };
+
+    let Some(package) = manifest.package_for(relative) else {
+        error(
+            Failure::NoMatch,
+            &format!("Error: No manifest entry for '{}'!", path),
+        );
+    };
+    match manifest.version(package) {
+        Some(version) if version == from => {}
+        Some(version) => error(
+            Failure::Guard,
+            &format!(
+            "Error: The manifest has version {version} for '{package}' but the file has {from}!"
+        ),
+        ),
+        None => error(
+            Failure::Parse,
+            &format!("Error: Invalid manifest entry for '{package}'!"),
+        ),
+    }
+
+    if stdout().is_terminal() {
+        info!("{manifest_path}: {package} -> {to}");
+    }
+
+    manifest.set(package, to)
+}
+
+/// Returns the new content of the SBOM at `sbom_path` with the version of its main
+/// component updated, or `None` if no version changed.
+pub(crate) fn sbom(sbom_path: &str, changes: &[Change]) -> Option<String> {
+    let (from, to) = bumped(changes, "the SBOM")?;
+
+    let Ok(content) = read_to_string(sbom_path) else {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot open file '{}'!", sbom_path),
+        );
+    };
+    let sbom = match verinc::sbom::Sbom::parse(&content) {
+        Ok(sbom) => sbom,
+        Err(err) => error(
+            Failure::Parse,
+            &format!("Error: Cannot parse '{}': {}!", sbom_path, err),
+        ),
+    };
+
+    match sbom.version() {
+        Some(version) if version == from => {}
+        Some(version) => error(
+            Failure::Guard,
+            &format!("Error: '{sbom_path}' has version {version} but the file has {from}!"),
+        ),
+        None => error(
+            Failure::NoMatch,
+            &format!("Error: Cannot find the component version in '{sbom_path}'!"),
+        ),
+    }
+
+    if stdout().is_terminal() {
+        info!("{sbom_path}: {from} -> {to}");
+    }
+
+    sbom.set(&to)
+}
+
+/// Returns the new content of the `CHANGELOG.md` at `path` with a section for the new
+/// version, or `None` if no version changed.
+pub(crate) fn changelog(path: &str, changes: &[Change], move_unreleased: bool) -> Option<String> {
+    let (_, to) = bumped(changes, "the changelog")?;
+
+    let Ok(content) = read_to_string(path) else {
+        error(Failure::Io, &format!("Error: Cannot open file '{path}'!"));
+    };
+    match verinc::changelog::add_release(&content, &to, crate::now(), move_unreleased) {
+        Ok(content) => Some(content),
+        Err(err) => error(Failure::Guard, &format!("Error: '{path}': {err}!")),
+    }
+}
+
+/// Returns the path and the new content of the .SRCINFO next to the PKGBUILD at `path`,
+/// `None` if it doesn't change.
+pub(crate) fn srcinfo(path: &str, pkgbuild: &str) -> Option<(PathBuf, String)> {
+    let srcinfo_path = dir_of(Path::new(path)).join(".SRCINFO");
+    let Ok(content) = read_to_string(&srcinfo_path) else {
+        error(
+            Failure::Io,
+            &format!("Error: Cannot open file '{}'!", srcinfo_path.display()),
+        );
+    };
+    match verinc::pkgbuild::update_srcinfo(&content, pkgbuild) {
+        Ok(updated) => (updated != content).then_some((srcinfo_path, updated)),
+        Err(err) => error(
+            Failure::Parse,
+            &format!(
+                "Error: Cannot update '{}': {err}, regenerate it with makepkg --printsrcinfo!",
+                srcinfo_path.display()
+            ),
+        ),
+    }
+}
+
+/// Returns the name of the crate of the manifest at `path`.
+pub(crate) fn crate_name(path: &str, content: &str) -> String {
+    match parse_manifest(Path::new(path), content).name() {
+        Some(name) => name.to_string(),
+        None => error(Failure::Parse, "Error: Missing [package] name!"),
+    }
+}
+
+/// Runs `cargo update -p <crate>` and returns the path of the lock file.
+pub(crate) fn cargo_update(path: &str, content: &str) -> PathBuf {
+    let name = crate_name(path, content);
+    let path = Path::new(path);
+
+    let dir = dir_of(path);
+
+    match Command::new("cargo")
+        .args(["update", "-p", &name])
+        .current_dir(dir)
+        .status()
+    {
+        Ok(status) if status.success() => {}
+        _ => error(
+            Failure::Other,
+            &format!("Error: \"cargo update -p {name}\" failed!"),
+        ),
+    }
+
+    lock_file(path)
+}
+
+/// Returns the path of the lock file of the crate at `path`.
+pub(crate) fn lock_file(path: &Path) -> PathBuf {
+    match verinc::cargo::find_workspace_root(path) {
+        Ok(Some(root)) => root.with_file_name("Cargo.lock"),
+        _ => dir_of(path).join("Cargo.lock"),
+    }
+}
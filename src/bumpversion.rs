@@ -0,0 +1,253 @@
+//! Compatibility with the configuration of bumpversion, bump2version and bump-my-version,
+//! i.e. `.bumpversion.cfg`, the `[bumpversion]` sections of `setup.cfg` and
+//! `[tool.bumpversion]` of `pyproject.toml`.
+//!
+//! Only the `current_version`, the files (or globs) and their `search` and `replace`
+//! templates are used. `{current_version}` and `{new_version}` are replaced in the
+//! templates.
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    glob::expand,
+    ini,
+    template::render,
+    toml::{self, Value},
+};
+
+/// The names of the files which may contain the configuration, in the order they are
+/// looked for.
+pub const FILE_NAMES: [&str; 3] = [".bumpversion.cfg", "setup.cfg", "pyproject.toml"];
+
+/// A file updated on every bump.
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    /// A path or a glob pattern relative to the configuration.
+    pub path: String,
+    pub glob: bool,
+    pub search: String,
+    pub replace: String,
+}
+
+impl Rule {
+    /// Returns the files matched by the rule.
+    pub fn paths(&self, dir: &Path) -> Vec<PathBuf> {
+        if self.glob {
+            expand(dir, &self.path).unwrap_or_default()
+        } else {
+            vec![dir.join(&self.path)]
+        }
+    }
+
+    /// Replaces the search template with the replace template in `content`. Returns `None`
+    /// if the search template is not found.
+    pub fn apply(&self, content: &str, current: &str, new: &str) -> Option<String> {
+        let vars = [("current_version", current), ("new_version", new)];
+        let search = render(&self.search, &vars);
+        content
+            .contains(&search)
+            .then(|| content.replace(&search, &render(&self.replace, &vars)))
+    }
+}
+
+#[derive(Debug)]
+pub struct Config<'a> {
+    src: &'a str,
+    pub current_version: String,
+    /// Span of `current_version` in the configuration, excluding quotes.
+    span: Range<usize>,
+    pub rules: Vec<Rule>,
+}
+
+impl<'a> Config<'a> {
+    /// Parses the configuration, `name` is the file name which determines the format.
+    /// Returns `Ok(None)` if the file does not contain a bumpversion configuration.
+    pub fn parse(name: &str, src: &'a str) -> Result<Option<Self>, String> {
+        if name.ends_with(".toml") {
+            Self::parse_pyproject(src)
+        } else {
+            Self::parse_cfg(src)
+        }
+    }
+
+    fn parse_cfg(src: &'a str) -> Result<Option<Self>, String> {
+        let entries = ini::parse(src);
+        let Some(current) = entries
+            .iter()
+            .find(|e| e.section == "bumpversion" && e.key == "current_version")
+        else {
+            return Ok(None);
+        };
+
+        let get = |section: &str, key: &str| {
+            entries
+                .iter()
+                .find(|e| e.section == section && e.key == key)
+                .map(|e| e.value.clone())
+        };
+        let search = get("bumpversion", "search").unwrap_or("{current_version}".to_string());
+        let replace = get("bumpversion", "replace").unwrap_or("{new_version}".to_string());
+
+        let mut rules = Vec::new();
+        // Sections without any keys don't show up among the entries.
+        let sections = src.lines().filter_map(|line| {
+            let line = line.trim();
+            Some(line.strip_prefix('[')?.strip_suffix(']')?.trim())
+        });
+
+        for section in sections {
+            let (glob, path) = match section.strip_prefix("bumpversion:file:") {
+                Some(path) => (false, path),
+                None => match section.strip_prefix("bumpversion:glob:") {
+                    Some(path) => (true, path),
+                    None => continue,
+                },
+            };
+            rules.push(Rule {
+                path: path.trim().to_string(),
+                glob,
+                search: get(section, "search").unwrap_or(search.clone()),
+                replace: get(section, "replace").unwrap_or(replace.clone()),
+            });
+        }
+
+        Ok(Some(Config {
+            src,
+            current_version: current.value.clone(),
+            span: current.span.clone(),
+            rules,
+        }))
+    }
+
+    fn parse_pyproject(src: &'a str) -> Result<Option<Self>, String> {
+        let doc = toml::parse(src)?;
+        let table = ["tool", "bumpversion"];
+        let Some(current) = doc.get(&table, "current_version") else {
+            return Ok(None);
+        };
+        let Some(current_version) = current.value.as_str() else {
+            return Err("current_version is not a string".to_string());
+        };
+
+        let string = |value: Option<&toml::Spanned<Value>>, default: &str| {
+            value
+                .and_then(|v| v.value.as_str())
+                .unwrap_or(default)
+                .to_string()
+        };
+        let search = string(doc.get(&table, "search"), "{current_version}");
+        let replace = string(doc.get(&table, "replace"), "{new_version}");
+
+        let mut rules = Vec::new();
+        for (idx, header) in doc.headers.iter().enumerate() {
+            if header.path != ["tool", "bumpversion", "files"] {
+                continue;
+            }
+            let get = |key: &str| {
+                doc.entries
+                    .iter()
+                    .find(|e| e.header == Some(idx) && e.key == [key])
+                    .map(|e| &e.value)
+            };
+
+            let (glob, path) = match (get("filename"), get("glob")) {
+                (Some(path), _) => (false, path),
+                (None, Some(path)) => (true, path),
+                (None, None) => return Err("file without filename or glob".to_string()),
+            };
+            rules.push(Rule {
+                path: string(Some(path), ""),
+                glob,
+                search: string(get("search"), &search),
+                replace: string(get("replace"), &replace),
+            });
+        }
+
+        Ok(Some(Config {
+            src,
+            current_version: current_version.to_string(),
+            span: current.span.start + 1..current.span.end - 1,
+            rules,
+        }))
+    }
+
+    /// Returns the configuration with `current_version` set to `new`.
+    pub fn set_current_version(&self, new: &str) -> String {
+        format!(
+            "{}{}{}",
+            &self.src[..self.span.start],
+            new,
+            &self.src[self.span.end..]
+        )
+    }
+}
+
+/// Looks for a bumpversion configuration in `dir` and returns its path.
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    FILE_NAMES.iter().map(|name| dir.join(name)).find(|path| {
+        std::fs::read_to_string(path).is_ok_and(|src| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            matches!(Config::parse(&name, &src), Ok(Some(_)))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cfg() {
+        let src = "[bumpversion]\ncurrent_version = 1.2.3\ncommit = True\n\n[bumpversion:file:setup.py]\nsearch = version=\"{current_version}\"\nreplace = version=\"{new_version}\"\n\n[bumpversion:file:README.md]\n\n[bumpversion:glob:docs/*.rst]\n";
+        let config = Config::parse(".bumpversion.cfg", src).unwrap().unwrap();
+        assert_eq!(config.current_version, "1.2.3");
+        assert_eq!(config.rules.len(), 3);
+        assert_eq!(config.rules[1].path, "README.md");
+        assert_eq!(config.rules[1].search, "{current_version}");
+        assert!(config.rules[2].glob);
+        assert_eq!(
+            config.rules[0].apply("version=\"1.2.3\" 1.2.3", "1.2.3", "1.3.0"),
+            Some("version=\"1.3.0\" 1.2.3".to_string())
+        );
+        assert_eq!(config.rules[0].apply("1.2.3", "1.2.3", "1.3.0"), None);
+        assert_eq!(
+            config.set_current_version("1.3.0"),
+            src.replacen("1.2.3", "1.3.0", 1)
+        );
+
+        assert!(Config::parse("setup.cfg", "[metadata]\nname = foo\n")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn pyproject() {
+        let src = "[tool.bumpversion]\ncurrent_version = \"0.1.0\"\nreplace = \"v{new_version}\"\n\n[[tool.bumpversion.files]]\nfilename = \"src/foo/__init__.py\"\nsearch = '__version__ = \"{current_version}\"'\nreplace = '__version__ = \"{new_version}\"'\n\n[[tool.bumpversion.files]]\nglob = \"docs/*.md\"\n";
+        let config = Config::parse("pyproject.toml", src).unwrap().unwrap();
+        assert_eq!(config.current_version, "0.1.0");
+        assert_eq!(
+            config.rules,
+            [
+                Rule {
+                    path: "src/foo/__init__.py".to_string(),
+                    glob: false,
+                    search: "__version__ = \"{current_version}\"".to_string(),
+                    replace: "__version__ = \"{new_version}\"".to_string(),
+                },
+                Rule {
+                    path: "docs/*.md".to_string(),
+                    glob: true,
+                    search: "{current_version}".to_string(),
+                    replace: "v{new_version}".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            config.set_current_version("0.2.0"),
+            src.replace("\"0.1.0\"", "\"0.2.0\"")
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! An on-disk cache of upstream lookups, so that repeated runs don't run into the rate
+//! limits of the registries.
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use sha2::{Digest, Sha256};
+
+pub struct Cache {
+    pub dir: PathBuf,
+    /// How long the entries are used for. Older entries are only used when offline.
+    pub ttl: Duration,
+}
+
+impl Cache {
+    /// Returns the cache in `$XDG_CACHE_HOME/verinc` (or `~/.cache/verinc`) which keeps the
+    /// entries for an hour.
+    pub fn new() -> Option<Cache> {
+        let cache = env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(Cache {
+            dir: cache.join("verinc"),
+            ttl: Duration::from_secs(60 * 60),
+        })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let hash = Sha256::digest(key.as_bytes());
+        let name: String = hash[..16].iter().map(|b| format!("{b:02x}")).collect();
+        self.dir.join(name)
+    }
+
+    /// Returns the entry for `key` if it is younger than the TTL (or of any age if
+    /// `stale`).
+    pub fn get(&self, key: &str, stale: bool) -> Option<String> {
+        let path = self.path(key);
+        let age = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        if !stale && age >= self.ttl {
+            return None;
+        }
+
+        // The key is stored along with the value to rule out collisions.
+        let entry = fs::read_to_string(path).ok()?;
+        let (stored, value) = entry.split_once('\n')?;
+        (stored == key).then(|| value.to_string())
+    }
+
+    /// Stores `value` under `key`. Failures are ignored, the cache is just an optimization.
+    pub fn put(&self, key: &str, value: &str) {
+        if fs::create_dir_all(&self.dir).is_ok() {
+            let _ = fs::write(self.path(key), format!("{key}\n{value}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let dir = env::temp_dir().join(format!("verinc-cache-{}", std::process::id()));
+        let mut cache = Cache {
+            dir: dir.clone(),
+            ttl: Duration::from_secs(60),
+        };
+
+        assert_eq!(cache.get("docker:nginx", false), None);
+        cache.put("docker:nginx", "1.27.0");
+        assert_eq!(cache.get("docker:nginx", false).as_deref(), Some("1.27.0"));
+
+        cache.ttl = Duration::ZERO;
+        assert_eq!(cache.get("docker:nginx", false), None);
+        assert!(cache.get("docker:nginx", true).is_some());
+        assert_eq!(cache.get("docker:redis", true), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+//! Helpers for Cargo manifests and workspaces.
+
+use std::{
+    fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    glob::expand,
+    toml::{self, Document, Value},
+};
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// A parsed Cargo.toml.
+pub struct Manifest<'a> {
+    src: &'a str,
+    doc: Document,
+}
+
+impl<'a> Manifest<'a> {
+    pub fn parse(src: &'a str) -> Result<Self, String> {
+        Ok(Manifest {
+            src,
+            doc: toml::parse(src)?,
+        })
+    }
+
+    /// Returns `[package].name`.
+    pub fn name(&self) -> Option<&str> {
+        self.doc.get(&["package"], "name")?.value.as_str()
+    }
+
+    /// Returns `[package].version`.
+    pub fn version(&self) -> Option<&str> {
+        self.doc.get(&["package"], "version")?.value.as_str()
+    }
+
+    /// Returns the location of `[package].version`, without the quotes.
+    pub fn version_span(&self) -> Option<Range<usize>> {
+        let version = self.doc.get(&["package"], "version")?;
+        version.value.as_str()?;
+        Some(version.span.start + 1..version.span.end - 1)
+    }
+
+    /// Returns true if the manifest contains a `[workspace]` table.
+    pub fn is_workspace(&self) -> bool {
+        self.doc
+            .headers
+            .iter()
+            .any(|h| h.path.first().is_some_and(|p| p == "workspace"))
+    }
+
+    fn strings(&self, table: &[&str], key: &str) -> Vec<&str> {
+        match self.doc.get(table, key).map(|v| &v.value) {
+            Some(Value::Array(items)) => items.iter().filter_map(|i| i.value.as_str()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Rewrites all requirements on the crate `name` in the dependency tables to `version`.
+    /// The requirement operator (`^`, `~`, `=`) is kept, requirements with multiple
+    /// comparators or wildcards are left alone.
+    pub fn update_dependency(&self, name: &str, version: &str) -> String {
+        let mut edits = Vec::new();
+
+        for entry in &self.doc.entries {
+            let table = self.doc.table(entry);
+            let key: Vec<&str> = entry.key.iter().map(String::as_str).collect();
+
+            let Some(base) = dependency_table(table) else {
+                continue;
+            };
+
+            match (&table[base..], key.as_slice()) {
+                // foo = "1.0" or foo = { version = "1.0" }
+                ([], [dep]) => match &entry.value.value {
+                    Value::String(_) if *dep == name => edits.push(entry.value.span.clone()),
+                    Value::Table(_) => {
+                        let value = &entry.value.value;
+                        let package = value.get("package").and_then(|p| p.value.as_str());
+                        if package.unwrap_or(*dep) == name {
+                            if let Some(req) = value.get("version") {
+                                edits.push(req.span.clone());
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                // foo.version = "1.0"
+                ([], [dep, "version"]) if *dep == name => edits.push(entry.value.span.clone()),
+                // [dependencies.foo] version = "1.0"
+                ([dep], ["version"]) => {
+                    let path: Vec<&str> = table.iter().map(String::as_str).collect();
+                    let package = self.doc.get(&path, "package");
+                    if package.and_then(|p| p.value.as_str()).unwrap_or(dep) == name {
+                        edits.push(entry.value.span.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.apply(edits, version)
+    }
+
+    fn apply(&self, mut edits: Vec<Range<usize>>, version: &str) -> String {
+        edits.sort_by_key(|span| span.start);
+
+        let mut out = String::with_capacity(self.src.len());
+        let mut last = 0;
+        for span in edits {
+            let quoted = &self.src[span.clone()];
+            let Some(req) = rewrite_requirement(&quoted[1..quoted.len() - 1], version) else {
+                continue;
+            };
+            let quote = &quoted[..1];
+            out.push_str(&self.src[last..span.start]);
+            out.push_str(&format!("{quote}{req}{quote}"));
+            last = span.end;
+        }
+        out.push_str(&self.src[last..]);
+        out
+    }
+}
+
+/// Returns the length of the dependency table prefix of `table`, if it is one.
+fn dependency_table(table: &[String]) -> Option<usize> {
+    let is_deps = |name: &String| DEPENDENCY_TABLES.contains(&name.as_str());
+
+    match table {
+        [first, ..] if is_deps(first) => Some(1),
+        [ws, deps, ..] if ws == "workspace" && deps == "dependencies" => Some(2),
+        [target, _, deps, ..] if target == "target" && is_deps(deps) => Some(3),
+        _ => None,
+    }
+}
+
+fn rewrite_requirement(req: &str, version: &str) -> Option<String> {
+    let req = req.trim();
+    let op = req
+        .find(|c: char| c.is_ascii_digit())
+        .map(|idx| &req[..idx])?;
+
+    if req.contains([',', '*']) || !matches!(op.trim(), "" | "^" | "~" | "=") {
+        return None;
+    }
+
+    Some(format!("{op}{version}"))
+}
+
+/// Walks up from the directory of `manifest` looking for the manifest that declares the
+/// `[workspace]`. Returns the path of that manifest.
+pub fn find_workspace_root(manifest: &Path) -> io::Result<Option<PathBuf>> {
+    let dir = manifest.parent().unwrap_or(Path::new(""));
+    let dir = if dir.as_os_str().is_empty() {
+        crate::path::canonicalize(Path::new("."))?
+    } else {
+        crate::path::canonicalize(dir)?
+    };
+
+    for ancestor in dir.ancestors() {
+        let candidate = ancestor.join("Cargo.toml");
+        if !candidate.is_file() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&candidate)?;
+        if Manifest::parse(&content).is_ok_and(|m| m.is_workspace()) {
+            return Ok(Some(candidate));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the paths of the workspace root manifest and all member manifests (sorted).
+pub fn workspace_manifests(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let content = fs::read_to_string(root)?;
+    let manifest =
+        Manifest::parse(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let dir = root.parent().unwrap_or(Path::new("."));
+
+    let mut excluded = Vec::new();
+    for pattern in manifest.strings(&["workspace"], "exclude") {
+        excluded.extend(expand(dir, pattern)?);
+    }
+
+    let mut manifests = vec![root.to_path_buf()];
+    for pattern in manifest.strings(&["workspace"], "members") {
+        for member in expand(dir, pattern)? {
+            let path = member.join("Cargo.toml");
+            if !excluded.contains(&member) && path.is_file() && !manifests.contains(&path) {
+                manifests.push(path);
+            }
+        }
+    }
+
+    // The root comes first, the members are sorted so that the order doesn't depend on
+    // the order of the patterns or of the directory entries.
+    manifests[1..].sort();
+    Ok(manifests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package() {
+        let manifest = Manifest::parse("[package]\nname = \"foo\"\nversion = \"0.1.0\"\n").unwrap();
+        assert_eq!(manifest.name(), Some("foo"));
+        assert_eq!(manifest.version(), Some("0.1.0"));
+        assert!(!manifest.is_workspace());
+    }
+
+    #[test]
+    fn update_dependency() {
+        let src = r#"[package]
+name = "bar"
+version = "0.1.0"
+
+[dependencies]
+foo = "0.1.0"
+foobar = "0.1.0"
+inline = { version = "^0.1", path = "../foo", package = "foo" }
+other = { version = "0.1.0", path = "../other" }
+range = { version = ">=0.1, <0.3", package = "foo" }
+
+[dev-dependencies.foo]
+version = "~0.1.0"
+path = "../foo"
+
+[target.'cfg(unix)'.build-dependencies]
+foo.version = "=0.1.0"
+
+[workspace.dependencies]
+foo = { path = "foo", version = "0.1.0" }
+"#;
+        let expected = r#"[package]
+name = "bar"
+version = "0.1.0"
+
+[dependencies]
+foo = "0.2.0"
+foobar = "0.1.0"
+inline = { version = "^0.2.0", path = "../foo", package = "foo" }
+other = { version = "0.1.0", path = "../other" }
+range = { version = ">=0.1, <0.3", package = "foo" }
+
+[dev-dependencies.foo]
+version = "~0.2.0"
+path = "../foo"
+
+[target.'cfg(unix)'.build-dependencies]
+foo.version = "=0.2.0"
+
+[workspace.dependencies]
+foo = { path = "foo", version = "0.2.0" }
+"#;
+        let manifest = Manifest::parse(src).unwrap();
+        assert_eq!(manifest.update_dependency("foo", "0.2.0"), expected);
+    }
+}
@@ -0,0 +1,136 @@
+//! Adding the section of a release to a `CHANGELOG.md` in the style of
+//! [Keep a Changelog](https://keepachangelog.com).
+//!
+//! The section is added above the latest release, below the `Unreleased` one if there is
+//! one. Its heading uses brackets if the others do (`## [1.2.0] - 2026-10-14`).
+
+use crate::date;
+
+/// A `## ` heading of the changelog.
+struct Heading<'a> {
+    /// Offset of the line.
+    start: usize,
+    /// Offset of the line after it.
+    end: usize,
+    title: &'a str,
+}
+
+fn headings(content: &str) -> Vec<Heading<'_>> {
+    let mut headings = Vec::new();
+    let mut offset = 0;
+    let mut fenced = false;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        if line.starts_with("```") {
+            fenced = !fenced;
+        }
+        if let Some(title) = line.strip_prefix("## ").filter(|_| !fenced) {
+            headings.push(Heading {
+                start,
+                end: offset,
+                title: title.trim(),
+            });
+        }
+    }
+    headings
+}
+
+/// Returns the version of the heading `title`, `Unreleased` for that one.
+fn version(title: &str) -> &str {
+    let name = title
+        .split([' ', '\t'])
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    name.strip_prefix('v').unwrap_or(name)
+}
+
+/// Returns `content` with a section for `new` released at `time` (Unix seconds). With
+/// `move_unreleased`, what is under `Unreleased` moves to the new section. Fails if there
+/// already is one for `new`.
+pub fn add_release(
+    content: &str,
+    new: &str,
+    time: u64,
+    move_unreleased: bool,
+) -> Result<String, String> {
+    let headings = headings(content);
+    if headings.iter().any(|heading| version(heading.title) == new) {
+        return Err(format!("there already is a section for {new}"));
+    }
+
+    let unreleased = headings
+        .iter()
+        .position(|heading| version(heading.title).eq_ignore_ascii_case("unreleased"));
+    let brackets = headings.first().is_some_and(|h| h.title.starts_with('['));
+    let title = if brackets {
+        format!("[{new}]")
+    } else {
+        new.to_string()
+    };
+    let heading = format!("## {title} - {}\n", date::iso(time));
+
+    let (offset, section) = match unreleased {
+        Some(idx) if move_unreleased => (headings[idx].end, format!("\n{heading}")),
+        Some(idx) => match headings.get(idx + 1) {
+            Some(next) => (next.start, format!("{heading}\n")),
+            None => (content.len(), format!("\n{heading}")),
+        },
+        None => match headings.first() {
+            Some(first) => (first.start, format!("{heading}\n")),
+            None => (content.len(), format!("\n{heading}")),
+        },
+    };
+
+    let mut out = String::with_capacity(content.len() + section.len() + 1);
+    out.push_str(&content[..offset]);
+    if offset == content.len() && !content.is_empty() && !content.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&section);
+    out.push_str(&content[offset..]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIME: u64 = 1_791_966_600;
+
+    #[test]
+    fn releases() {
+        let changelog = "# Changelog
+
+## [Unreleased]
+### Added
+- Foo
+
+## [1.0.0] - 2026-01-01
+- Initial release
+";
+        assert_eq!(
+            add_release(changelog, "1.1.0", TIME, true).unwrap(),
+            changelog.replace(
+                "## [Unreleased]\n",
+                "## [Unreleased]\n\n## [1.1.0] - 2026-10-14\n"
+            )
+        );
+        assert_eq!(
+            add_release(changelog, "1.1.0", TIME, false).unwrap(),
+            changelog.replace("## [1.0.0]", "## [1.1.0] - 2026-10-14\n\n## [1.0.0]")
+        );
+        assert!(add_release(changelog, "1.0.0", TIME, false).is_err());
+
+        assert_eq!(
+            add_release("# Changelog\n\n## v1.0.0\n", "1.0.1", TIME, true).unwrap(),
+            "# Changelog\n\n## 1.0.1 - 2026-10-14\n\n## v1.0.0\n"
+        );
+        assert_eq!(
+            add_release("# Changelog", "1.0.0", TIME, false).unwrap(),
+            "# Changelog\n\n## 1.0.0 - 2026-10-14\n"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! The functions of the library as they were before they returned a [`VerincError`],
+//! for the existing callers. They panic where the new ones fail.
+
+use crate::{MapFn, Position, Version, VersionScheme};
+
+fn unwrap(result: Result<String, crate::VerincError>) -> String {
+    result.unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// See [`crate::inc`].
+pub fn inc(hay: &str, position: Position, version: Version, step: u64) -> String {
+    unwrap(crate::inc(hay, position, version, step))
+}
+
+/// See [`crate::dec`].
+pub fn dec(hay: &str, position: Position, version: Version) -> String {
+    unwrap(crate::dec(hay, position, version))
+}
+
+/// See [`crate::inc_with`].
+pub fn inc_with(
+    hay: &str,
+    position: Position,
+    version: Version,
+    scheme: &dyn VersionScheme,
+) -> String {
+    unwrap(crate::inc_with(hay, position, version, scheme))
+}
+
+/// See [`crate::inc_map`].
+pub fn inc_map(hay: &str, position: Position, f: MapFn) -> String {
+    unwrap(crate::inc_map(hay, position, f))
+}
+
+/// See [`crate::set`].
+pub fn set(hay: &str, position: Position, new: &str) -> String {
+    unwrap(crate::set(hay, position, new))
+}
+
+/// See [`crate::set_component`].
+pub fn set_component(hay: &str, position: Position, version: Version, value: u64) -> String {
+    unwrap(crate::set_component(hay, position, version, value))
+}
+
+/// See [`crate::finalize`].
+pub fn finalize(hay: &str, position: Position) -> String {
+    unwrap(crate::finalize(hay, position))
+}
+
+/// See [`crate::next`], `None` if `current` cannot be bumped.
+pub fn next(current: &str, version: Version) -> Option<String> {
+    crate::next(current, version).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrappers() {
+        assert_eq!(inc("v1.2.3", Position::Nth(0), Version::Minor, 1), "v1.3.0");
+        assert_eq!(set("1.2.3", Position::All, "2.0.0"), "2.0.0");
+        assert_eq!(next("1.2.3", Version::Major), Some("2.0.0".to_string()));
+        assert_eq!(next("1.2", Version::Major), None);
+    }
+}
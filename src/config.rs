@@ -0,0 +1,357 @@
+//! The `.verinc.toml` project configuration.
+//!
+//! It declares all of the places which carry the version of the project:
+//!
+//! ```toml
+//! bump = "minor"
+//!
+//! [[file]]
+//! path = "Cargo.toml"
+//! format = "cargo"
+//!
+//! [[file]]
+//! path = "README.md"
+//! position = "all"
+//! match = "^cargo add"
+//! ```
+//!
+//! `path` is relative to the directory of the configuration. `position` is the index of
+//! the version (see `--list`) or `"all"` and defaults to 0. With `context`, only versions
+//! on lines containing it are considered and `position` counts just those, `match` does
+//! the same with a regex. A `format` (see `--format`) finds the version itself, `key` is
+//! its path, key or image. `bump` is the component which is incremented by default.
+//!
+//! A `[hooks]` table may have `pre` and `post` commands (a string or an array of them)
+//! which run before and after the bump in `dir`, relative to the directory of the
+//! configuration (which is the default), see [`crate::hook`].
+//!
+//! A `[tokens]` table may refer to API tokens, see [`crate::token`].
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    format::Format,
+    toml::{self, Value},
+    Position, Regex, Selector, Version,
+};
+
+pub const FILE_NAME: &str = ".verinc.toml";
+
+/// A location of the project version.
+#[derive(Debug, Clone)]
+pub struct ManagedFile {
+    pub path: String,
+    pub position: Position,
+    pub context: Option<String>,
+    /// Only the versions on the lines matching it are considered.
+    pub line_match: Option<Regex>,
+    pub format: Option<Format>,
+}
+
+impl ManagedFile {
+    /// Calls `f` for every version at this location and replaces it with the result.
+    fn map(&self, content: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+        if let Some(format) = &self.format {
+            let mut new = None;
+            let bumped = format.bump(content, |version| {
+                new = f(version);
+                new.clone().unwrap_or_else(|| version.to_string())
+            });
+            return bumped.unwrap_or_else(|_| content.to_string());
+        }
+
+        let mut out = String::with_capacity(content.len());
+        let mut last = 0;
+
+        let candidates: Vec<_> = Selector::default()
+            .find(content)
+            .into_iter()
+            .filter(|found| {
+                let line_start = content[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+                let line_end = content[found.end()..]
+                    .find('\n')
+                    .map_or(content.len(), |i| found.end() + i);
+                let line = &content[line_start..line_end];
+                self.context
+                    .as_ref()
+                    .is_none_or(|c| line.contains(c.as_str()))
+                    && self.line_match.as_ref().is_none_or(|r| r.is_match(line))
+            })
+            .collect();
+        let position = self.position.resolve(candidates.len() as u32);
+
+        for (idx, found) in candidates.into_iter().enumerate() {
+            let selected = position.contains(idx as u32);
+            if let Some(new) = selected.then(|| f(found.as_str())).flatten() {
+                out.push_str(&content[last..found.start()]);
+                out.push_str(&new);
+                last = found.end();
+            }
+        }
+        out.push_str(&content[last..]);
+        out
+    }
+
+    /// Returns the versions at this location in `content`.
+    pub fn versions(&self, content: &str) -> Vec<String> {
+        let mut versions = Vec::new();
+        self.map(content, |version| {
+            versions.push(version.to_string());
+            None
+        });
+        versions
+    }
+
+    /// Replaces the versions at this location in `content` with `new`.
+    pub fn set(&self, content: &str, new: &str) -> String {
+        self.map(content, |_| Some(new.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub files: Vec<ManagedFile>,
+    /// The component `verinc bump` increments if none is given.
+    pub bump: Option<Version>,
+    /// The `[tokens]` table as `(service, reference)`.
+    pub tokens: Vec<(String, String)>,
+    /// The commands of `hooks.pre`.
+    pub pre_hooks: Vec<String>,
+    /// The commands of `hooks.post`.
+    pub post_hooks: Vec<String>,
+    /// The directory of `hooks.dir`, relative to the configuration.
+    pub hooks_dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let doc = toml::parse(src)?;
+        let mut files = Vec::new();
+
+        for (idx, header) in doc.headers.iter().enumerate() {
+            if header.path != ["file"] {
+                continue;
+            }
+            let get = |key: &str| {
+                doc.entries
+                    .iter()
+                    .find(|e| e.header == Some(idx) && e.key == [key])
+                    .map(|e| &e.value.value)
+            };
+
+            let Some(path) = get("path").and_then(Value::as_str) else {
+                return Err(format!("file #{}: missing path", files.len() + 1));
+            };
+            let position = match get("position") {
+                None => Position::Nth(0),
+                Some(Value::String(position)) => match Position::parse(position) {
+                    Some(position) => position,
+                    None => return Err(format!("{path}: invalid position")),
+                },
+                Some(value) => match value.as_integer().map(u32::try_from) {
+                    Some(Ok(n)) => Position::Nth(n),
+                    _ => return Err(format!("{path}: invalid position")),
+                },
+            };
+            let context = match get("context") {
+                None => None,
+                Some(Value::String(context)) => Some(context.clone()),
+                Some(_) => return Err(format!("{path}: invalid context")),
+            };
+            let line_match = match get("match") {
+                None => None,
+                Some(Value::String(pattern)) => match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(_) => return Err(format!("{path}: invalid match")),
+                },
+                Some(_) => return Err(format!("{path}: invalid match")),
+            };
+            let format = match get("format") {
+                None => None,
+                Some(Value::String(name)) => match Format::parse(name) {
+                    // The changelog entry needs an author and a message.
+                    Some(Format::DebChangelog(_)) | None => {
+                        return Err(format!("{path}: invalid format"))
+                    }
+                    Some(format) => Some(format),
+                },
+                Some(_) => return Err(format!("{path}: invalid format")),
+            };
+            let format = match (format, get("key")) {
+                (format, None) => format,
+                (Some(format), Some(Value::String(key))) => match format {
+                    Format::Yaml(_) => Some(Format::Yaml(key.clone())),
+                    Format::Xml(_) => Some(Format::Xml(key.clone())),
+                    Format::Ini(_) => Some(Format::Ini(key.clone())),
+                    Format::DockerLabel(_) => Some(Format::DockerLabel(key.clone())),
+                    Format::DockerFrom(_) => Some(Format::DockerFrom(Some(key.clone()))),
+                    _ => return Err(format!("{path}: the format has no key")),
+                },
+                _ => return Err(format!("{path}: invalid key")),
+            };
+            let filtered = ["position", "context", "match"]
+                .iter()
+                .any(|key| get(key).is_some());
+            if format.is_some() && filtered {
+                return Err(format!(
+                    "{path}: format cannot be combined with position, context or match"
+                ));
+            }
+
+            files.push(ManagedFile {
+                path: path.to_string(),
+                position,
+                context,
+                line_match,
+                format,
+            });
+        }
+
+        let mut tokens = Vec::new();
+        for entry in &doc.entries {
+            if doc.table(entry) != ["tokens"] || entry.key.len() != 1 {
+                continue;
+            }
+            let Some(reference) = entry.value.value.as_str() else {
+                return Err(format!("tokens.{}: invalid reference", entry.key[0]));
+            };
+            tokens.push((entry.key[0].clone(), reference.to_string()));
+        }
+
+        let bump = match doc
+            .entries
+            .iter()
+            .find(|e| e.header.is_none() && e.key == ["bump"])
+        {
+            None => None,
+            Some(entry) => match entry.value.value.as_str().and_then(component) {
+                Some(version) => Some(version),
+                None => return Err("invalid bump".to_string()),
+            },
+        };
+
+        let mut hooks = [Vec::new(), Vec::new()];
+        for (name, commands) in ["pre", "post"].iter().zip(&mut hooks) {
+            let invalid = || format!("hooks.{name}: invalid command");
+            match doc.get(&["hooks"], name).map(|value| &value.value) {
+                None => {}
+                Some(Value::String(command)) => commands.push(command.clone()),
+                Some(Value::Array(values)) => {
+                    for value in values {
+                        commands.push(value.value.as_str().ok_or_else(invalid)?.to_string());
+                    }
+                }
+                Some(_) => return Err(invalid()),
+            }
+        }
+        let [pre_hooks, post_hooks] = hooks;
+        let hooks_dir = match doc.get(&["hooks"], "dir").map(|value| &value.value) {
+            None => None,
+            Some(Value::String(dir)) => Some(PathBuf::from(dir)),
+            Some(_) => return Err("hooks.dir: invalid directory".to_string()),
+        };
+
+        Ok(Config {
+            files,
+            bump,
+            tokens,
+            pre_hooks,
+            post_hooks,
+            hooks_dir,
+        })
+    }
+}
+
+/// Returns the component called `name` (`major`, `minor` or `patch`).
+pub fn component(name: &str) -> Option<Version> {
+    match name {
+        "major" => Some(Version::Major),
+        "minor" => Some(Version::Minor),
+        "patch" => Some(Version::Patch),
+        _ => None,
+    }
+}
+
+/// Walks up from `dir` looking for `.verinc.toml`.
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files() {
+        let config = Config::parse(
+            "[[file]]\npath = \"Cargo.toml\"\ncontext = \"version =\"\n\n[[file]]\npath = \"README.md\"\nposition = \"all\"\n\n[[file]]\npath = \"x\"\nposition = 2\n\n[tokens]\ngithub = \"env:RELEASE_TOKEN\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.files.len(), 3);
+        assert!(matches!(config.files[1].position, Position::All));
+        assert!(matches!(config.files[2].position, Position::Nth(2)));
+        assert_eq!(
+            config.tokens,
+            [("github".to_string(), "env:RELEASE_TOKEN".to_string())]
+        );
+
+        assert!(Config::parse("[[file]]\nposition = 1\n").is_err());
+        assert!(Config::parse("[[file]]\npath = \"x\"\nposition = -1\n").is_err());
+    }
+
+    #[test]
+    fn context() {
+        let file = ManagedFile {
+            path: "Cargo.toml".to_string(),
+            position: Position::Nth(0),
+            context: Some("version =".to_string()),
+            line_match: None,
+            format: None,
+        };
+        let src = "[dependencies]\nfoo = \"1.2.3\"\n\n[package]\nversion = \"0.1.0\"\n";
+        assert_eq!(file.versions(src), ["0.1.0"]);
+        assert_eq!(file.set(src, "0.2.0"), src.replace("0.1.0", "0.2.0"));
+    }
+
+    #[test]
+    fn formats_and_patterns() {
+        let config = Config::parse(
+            "bump = \"minor\"\n\n[[file]]\npath = \"Cargo.toml\"\nformat = \"cargo\"\n\n[[file]]\npath = \"README.md\"\nmatch = \"^cargo add\"\nposition = \"all\"\n",
+        )
+        .unwrap();
+        assert!(matches!(config.bump, Some(Version::Minor)));
+
+        let src = "[dependencies]\nfoo = \"1.2.3\"\n\n[package]\nversion = \"0.1.0\"\n";
+        assert_eq!(config.files[0].versions(src), ["0.1.0"]);
+        assert_eq!(
+            config.files[0].set(src, "0.2.0"),
+            src.replace("0.1.0", "0.2.0")
+        );
+
+        let readme = "Version 0.1.0\ncargo add foo@0.1.0\n";
+        assert_eq!(config.files[1].versions(readme), ["0.1.0"]);
+        assert_eq!(
+            config.files[1].set(readme, "0.2.0"),
+            "Version 0.1.0\ncargo add foo@0.2.0\n"
+        );
+
+        assert!(Config::parse("bump = \"huge\"\n").is_err());
+
+        let config =
+            Config::parse("[hooks]\npre = \"cargo fmt --check\"\npost = [\"a\", \"b\"]\n").unwrap();
+        assert_eq!(config.pre_hooks, ["cargo fmt --check"]);
+        assert_eq!(config.post_hooks, ["a", "b"]);
+        assert_eq!(config.hooks_dir, None);
+        assert!(Config::parse("[hooks]\npre = 1\n").is_err());
+        let config = Config::parse("[hooks]\ndir = \"web\"\npre = \"npm test\"\n").unwrap();
+        assert_eq!(config.hooks_dir, Some(PathBuf::from("web")));
+        assert!(Config::parse("[hooks]\ndir = 1\n").is_err());
+        assert!(Config::parse("[[file]]\npath = \"x\"\nformat = \"nope\"\n").is_err());
+        assert!(
+            Config::parse("[[file]]\npath = \"x\"\nformat = \"cargo\"\nkey = \"a\"\n").is_err()
+        );
+        assert!(Config::parse("[[file]]\npath = \"x\"\nformat = \"npm\"\nposition = 1\n").is_err());
+    }
+}
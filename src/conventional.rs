@@ -0,0 +1,62 @@
+//! Deriving the bump from [Conventional Commits](https://www.conventionalcommits.org).
+
+use crate::{level, Version};
+
+/// Returns the component `message` calls for: major for breaking changes (`feat!:` or a
+/// `BREAKING CHANGE:` footer), minor for `feat` and patch for `fix`. Other types and
+/// messages which don't follow the convention call for none.
+pub fn component(message: &str) -> Option<Version> {
+    let subject = message.lines().next()?;
+    let (head, _) = subject.split_once(':')?;
+    let kind = head.strip_suffix('!').unwrap_or(head);
+    let kind = kind.split_once('(').map_or(kind, |(kind, _)| kind);
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    let footer = message
+        .lines()
+        .skip(1)
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+    if head.ends_with('!') || footer {
+        return Some(Version::Major);
+    }
+    match kind.to_ascii_lowercase().as_str() {
+        "feat" => Some(Version::Minor),
+        "fix" => Some(Version::Patch),
+        _ => None,
+    }
+}
+
+/// Returns the highest component any of `messages` calls for.
+pub fn highest<'a>(messages: impl IntoIterator<Item = &'a str>) -> Option<Version> {
+    messages.into_iter().filter_map(component).min_by_key(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components() {
+        assert!(matches!(component("feat: add --foo"), Some(Version::Minor)));
+        assert!(matches!(component("fix(parser): x"), Some(Version::Patch)));
+        assert!(matches!(
+            component("refactor!: drop x"),
+            Some(Version::Major)
+        ));
+        assert!(matches!(
+            component("fix: x\n\nBREAKING CHANGE: y"),
+            Some(Version::Major)
+        ));
+        assert!(component("docs: typo").is_none());
+        assert!(component("Merge branch 'main'").is_none());
+        assert!(component("Fix the thing: really").is_none());
+
+        assert!(matches!(
+            highest(["fix: a", "docs: b", "feat: c"]),
+            Some(Version::Minor)
+        ));
+        assert!(highest(["chore: a"]).is_none());
+    }
+}
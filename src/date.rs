@@ -0,0 +1,83 @@
+//! Formatting of the (UTC) dates written into changelogs.
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// A day and time in UTC.
+struct Time {
+    year: i64,
+    month: usize,
+    day: u32,
+    weekday: usize,
+    seconds: u64,
+}
+
+fn time(unix: u64) -> Time {
+    let days = (unix / 86400) as i64;
+    // The days since 1970-01-01 to the civil date, see Howard Hinnant's days_from_civil.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as usize;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    Time {
+        year,
+        month,
+        day,
+        weekday: (days % 7) as usize,
+        seconds: unix % 86400,
+    }
+}
+
+/// Returns `unix` as in RPM changelogs, `Wed Oct 14 2026`.
+pub(crate) fn rpm(unix: u64) -> String {
+    let t = time(unix);
+    format!(
+        "{} {} {:02} {}",
+        WEEKDAYS[t.weekday],
+        MONTHS[t.month - 1],
+        t.day,
+        t.year
+    )
+}
+
+/// Returns the day of `unix` as in ISO 8601, `2026-10-14`.
+pub(crate) fn iso(unix: u64) -> String {
+    let t = time(unix);
+    format!("{}-{:02}-{:02}", t.year, t.month, t.day)
+}
+
+/// Returns `unix` as in RFC 2822, `Wed, 14 Oct 2026 08:30:00 +0000`.
+pub(crate) fn rfc2822(unix: u64) -> String {
+    let t = time(unix);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[t.weekday],
+        t.day,
+        MONTHS[t.month - 1],
+        t.year,
+        t.seconds / 3600,
+        t.seconds / 60 % 60,
+        t.seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        assert_eq!(rpm(0), "Thu Jan 01 1970");
+        assert_eq!(rpm(1_709_164_800), "Thu Feb 29 2024");
+        assert_eq!(rfc2822(1_791_966_600), "Wed, 14 Oct 2026 08:30:00 +0000");
+        assert_eq!(iso(1_709_164_800), "2024-02-29");
+    }
+}
@@ -0,0 +1,50 @@
+//! Reading and extending `debian/changelog` files.
+
+use std::ops::Range;
+
+/// Returns the location of the upstream part of the version of the first stanza (with
+/// the epoch but without the Debian revision), the `1:2.3.4` of `foo (1:2.3.4-1)`.
+pub(crate) fn version_span(content: &str) -> Option<Range<usize>> {
+    let start = content.find(|c: char| !c.is_whitespace())?;
+    let line = content[start..].lines().next()?;
+    let open = line.find(" (")? + 2;
+    let close = open + line[open..].find(')')?;
+    let end = line[open..close].rfind('-').map_or(close, |idx| open + idx);
+    Some(start + open..start + end)
+}
+
+/// Returns `content` with a stanza for the upstream version `new` prepended. The package
+/// name is taken from the first stanza and the Debian revision (if any) starts at 1.
+pub(crate) fn prepend(content: &str, new: &str, message: &str, author: &str, date: &str) -> String {
+    let span = version_span(content).unwrap_or(0..0);
+    let start = content.find(|c: char| !c.is_whitespace()).unwrap_or(0);
+    let name = content[start..span.start].trim_end_matches(" (");
+    let revision = if content[span.end..].starts_with('-') {
+        "-1"
+    } else {
+        ""
+    };
+    format!(
+        "{name} ({new}{revision}) UNRELEASED; urgency=medium\n\n  * {message}\n\n -- {author}  {date}\n\n{}",
+        &content[start..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stanzas() {
+        let changelog = "foo (1:2.3.4-2) unstable; urgency=medium\n\n  * Fix.\n\n -- A <a@a>  Thu, 01 Jan 1970 00:00:00 +0000\n";
+        assert_eq!(&changelog[version_span(changelog).unwrap()], "1:2.3.4");
+        assert_eq!(
+            prepend(changelog, "1:2.4.0", "New upstream release.", "B <b@b>", "now"),
+            format!("foo (1:2.4.0-1) UNRELEASED; urgency=medium\n\n  * New upstream release.\n\n -- B <b@b>  now\n\n{changelog}")
+        );
+
+        let native = "foo (1.0) unstable; urgency=low\n";
+        assert_eq!(&native[version_span(native).unwrap()], "1.0");
+        assert!(prepend(native, "1.1", "x", "B <b@b>", "now").starts_with("foo (1.1) UNRELEASED"));
+    }
+}
@@ -0,0 +1,224 @@
+//! Unified diffs of what a bump changes, as printed by `--diff`.
+
+use std::ops::Range;
+
+/// The lines of context around the changes.
+const CONTEXT: usize = 3;
+
+/// Above this many cells the changed part is not aligned, it's shown as removed and added.
+const MAX_TABLE: usize = 1 << 22;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// An edit along with the number of old and new lines before it.
+#[derive(Debug, Clone, Copy)]
+struct Edit {
+    kind: Kind,
+    old: usize,
+    new: usize,
+}
+
+/// Returns the unified diff turning `old` into `new`, or an empty string if they are the
+/// same.
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let a: Vec<_> = old.split_inclusive('\n').collect();
+    let b: Vec<_> = new.split_inclusive('\n').collect();
+    let edits = edits(&a, &b);
+    let is_change = |edit: &Edit| edit.kind != Kind::Equal;
+    if !edits.iter().any(is_change) {
+        return String::new();
+    }
+
+    let mut out = format!("--- {old_name}\n+++ {new_name}\n");
+    let mut from = 0;
+    while let Some(first) = edits[from..].iter().position(is_change) {
+        let first = from + first;
+        let start = first.saturating_sub(CONTEXT).max(from);
+        // Changes closer than twice the context share a hunk.
+        let mut end = first + 1;
+        while let Some(next) = edits[end..edits.len().min(end + 2 * CONTEXT)]
+            .iter()
+            .position(is_change)
+        {
+            end += next + 1;
+        }
+        let end = edits.len().min(end + CONTEXT);
+
+        let hunk = &edits[start..end];
+        let old_len = hunk.iter().filter(|e| e.kind != Kind::Insert).count();
+        let new_len = hunk.iter().filter(|e| e.kind != Kind::Delete).count();
+        // An empty range starts at the line before it.
+        let line = |before: usize, len: usize| before + usize::from(len > 0);
+        out += &format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n",
+            line(hunk[0].old, old_len),
+            line(hunk[0].new, new_len)
+        );
+        for edit in hunk {
+            let (prefix, text) = match edit.kind {
+                Kind::Equal => (' ', a[edit.old]),
+                Kind::Delete => ('-', a[edit.old]),
+                Kind::Insert => ('+', b[edit.new]),
+            };
+            out.push(prefix);
+            out += text;
+            if !text.ends_with('\n') {
+                out += "\n\\ No newline at end of file\n";
+            }
+        }
+        from = end;
+    }
+    out
+}
+
+/// Returns the byte ranges of the runs of changed lines in `old` and in `new`.
+pub fn hunks(old: &str, new: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    let a: Vec<_> = old.split_inclusive('\n').collect();
+    let b: Vec<_> = new.split_inclusive('\n').collect();
+    let (mut old_at, mut new_at) = (0, 0);
+    let mut hunks: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+    let mut in_hunk = false;
+    for edit in edits(&a, &b) {
+        let (old_start, new_start) = (old_at, new_at);
+        match edit.kind {
+            Kind::Equal => {
+                old_at += a[edit.old].len();
+                new_at += b[edit.new].len();
+                in_hunk = false;
+                continue;
+            }
+            Kind::Delete => old_at += a[edit.old].len(),
+            Kind::Insert => new_at += b[edit.new].len(),
+        }
+        match hunks.last_mut() {
+            Some((old_range, new_range)) if in_hunk => {
+                old_range.end = old_at;
+                new_range.end = new_at;
+            }
+            _ => hunks.push((old_start..old_at, new_start..new_at)),
+        }
+        in_hunk = true;
+    }
+    hunks
+}
+
+/// Returns the edits turning the lines `a` into `b`.
+fn edits(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    // Bumps touch a few lines, so only the part between the common ends is aligned.
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (ma, mb) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits = Vec::with_capacity(a.len().max(b.len()));
+    let mut push = |kind, old: &mut usize, new: &mut usize| {
+        edits.push(Edit {
+            kind,
+            old: *old,
+            new: *new,
+        });
+        match kind {
+            Kind::Equal => {
+                *old += 1;
+                *new += 1;
+            }
+            Kind::Delete => *old += 1,
+            Kind::Insert => *new += 1,
+        }
+    };
+    let (mut old, mut new) = (0, 0);
+    for _ in 0..prefix {
+        push(Kind::Equal, &mut old, &mut new);
+    }
+
+    if (ma.len() + 1) * (mb.len() + 1) > MAX_TABLE {
+        for _ in ma {
+            push(Kind::Delete, &mut old, &mut new);
+        }
+        for _ in mb {
+            push(Kind::Insert, &mut old, &mut new);
+        }
+    } else {
+        // lcs[i][j] is the length of the longest common subsequence of ma[i..] and mb[j..].
+        let width = mb.len() + 1;
+        let mut lcs = vec![0u32; (ma.len() + 1) * width];
+        for i in (0..ma.len()).rev() {
+            for j in (0..mb.len()).rev() {
+                lcs[i * width + j] = if ma[i] == mb[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < ma.len() || j < mb.len() {
+            if i < ma.len() && j < mb.len() && ma[i] == mb[j] {
+                push(Kind::Equal, &mut old, &mut new);
+                (i, j) = (i + 1, j + 1);
+            } else if j == mb.len()
+                || i < ma.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]
+            {
+                push(Kind::Delete, &mut old, &mut new);
+                i += 1;
+            } else {
+                push(Kind::Insert, &mut old, &mut new);
+                j += 1;
+            }
+        }
+    }
+
+    for _ in 0..suffix {
+        push(Kind::Equal, &mut old, &mut new);
+    }
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hunks() {
+        let old = "pkgver=1.0.0\npkgrel=1\n1\n2\n3\n4\n5\n6\nsource=(foo-1.0.0.tar.gz)";
+        let new = old.replace("1.0.0", "1.0.1");
+        assert_eq!(
+            unified(old, &new, "a/PKGBUILD", "b/PKGBUILD"),
+            "--- a/PKGBUILD
++++ b/PKGBUILD
+@@ -1,4 +1,4 @@
+-pkgver=1.0.0
++pkgver=1.0.1
+ pkgrel=1
+ 1
+ 2
+@@ -6,4 +6,4 @@
+ 4
+ 5
+ 6
+-source=(foo-1.0.0.tar.gz)
+\\ No newline at end of file
++source=(foo-1.0.1.tar.gz)
+\\ No newline at end of file
+"
+        );
+
+        assert_eq!(unified(old, old, "a", "b"), "");
+        assert_eq!(
+            unified("", "1.0.0\n", "a", "b"),
+            "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+1.0.0\n"
+        );
+
+        assert_eq!(super::hunks(old, &new), [(0..13, 0..13), (34..59, 34..59)]);
+        assert_eq!(super::hunks("a\nb\n", "a\nc\nd\n"), [(2..4, 2..6)]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Downloading of release artifacts and computation of their checksums, as needed by
+//! package manifests which pin their sources (Flatpak, PKGBUILD, Homebrew, ...).
+
+use blake2::Blake2b512;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{http, template};
+
+/// Hex encoded checksums of a download.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digests {
+    pub size: u64,
+    pub sha256: String,
+    pub sha512: String,
+    /// BLAKE2b-512, as used by `b2sums` in PKGBUILDs.
+    pub b2: String,
+}
+
+/// Limits applied to a download.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Downloads larger than this many bytes are aborted.
+    pub max_size: u64,
+    /// How many times a failed download is retried.
+    pub retries: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_size: 1 << 30,
+            retries: 2,
+        }
+    }
+}
+
+/// Downloads the artifact at `url_template` with `{version}` replaced by `version` and
+/// returns its checksums.
+pub fn fetch_and_hash(url_template: &str, version: &str) -> Result<Digests, String> {
+    let url = template::render(url_template, &[("version", version)]);
+    fetch(&url, &Limits::default())
+}
+
+/// Downloads `url` within `limits` and returns its checksums.
+pub fn fetch(url: &str, limits: &Limits) -> Result<Digests, String> {
+    http::retry(limits.retries, || {
+        let mut hasher = Hasher::default();
+        let size = http::stream(url, limits.max_size, |chunk| hasher.update(chunk))?;
+        Ok(hasher.finish(size))
+    })
+}
+
+/// Computes all of the digests at once.
+#[derive(Default)]
+struct Hasher {
+    sha256: Sha256,
+    sha512: Sha512,
+    b2: Blake2b512,
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.sha256.update(data);
+        self.sha512.update(data);
+        self.b2.update(data);
+    }
+
+    fn finish(self, size: u64) -> Digests {
+        Digests {
+            size,
+            sha256: hex(&self.sha256.finalize()),
+            sha512: hex(&self.sha512.finalize()),
+            b2: hex(&self.b2.finalize()),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests() {
+        let mut hasher = Hasher::default();
+        hasher.update(b"hello ");
+        hasher.update(b"world");
+        let digests = hasher.finish(11);
+
+        assert_eq!(
+            digests.sha256,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert!(digests
+            .sha512
+            .starts_with("309ecc489c12d6eb4cc40f50c902f2b4d0ed77ee511a7c7a"));
+        assert!(digests
+            .b2
+            .starts_with("021ced8799296ceca557832ab941a50b4a11f83478cf141f"));
+    }
+}
@@ -0,0 +1,151 @@
+//! Finding the image tags of `FROM` and the values of `LABEL` in Dockerfiles.
+
+use std::ops::Range;
+
+/// Returns the spans of the arguments of the `instruction`s (ignoring case) of `src`,
+/// continuation lines included. Quoted arguments keep their quotes.
+fn instructions(src: &str, instruction: &str) -> Vec<Vec<Range<usize>>> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    let mut lines = src.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        let Some(name) = trimmed.split_whitespace().next() else {
+            continue;
+        };
+        if !name.eq_ignore_ascii_case(instruction) {
+            continue;
+        }
+
+        let args_start = start + line.len() - trimmed.len() + name.len();
+        let mut end = offset;
+        let mut last = line;
+        while last.trim_end().ends_with('\\') {
+            let Some(next) = lines.next() else {
+                break;
+            };
+            offset += next.len();
+            end = offset;
+            last = next;
+        }
+        found.push(words(src, args_start..end));
+    }
+    found
+}
+
+/// Splits `src[range]` into words separated by whitespace and line continuations.
+fn words(src: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    for (idx, c) in src[range.clone()].char_indices() {
+        let idx = range.start + idx;
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                start.get_or_insert(idx);
+            }
+            None if c.is_whitespace() || c == '\\' && src[idx + 1..].starts_with(['\n', '\r']) => {
+                if let Some(start) = start.take() {
+                    words.push(start..idx);
+                }
+            }
+            None => {
+                start.get_or_insert(idx);
+            }
+        }
+    }
+    if let Some(start) = start {
+        words.push(start..range.end);
+    }
+    words
+}
+
+/// Returns the location of the tag of the first `FROM` image called `image` (the whole
+/// name like `docker.io/library/nginx` or just its last component like `nginx`), of the
+/// first one with a tag if `None`. Just the version of it, the variant after the first
+/// `-` of `1.25.3-alpine` is left out.
+pub(crate) fn image_tag(src: &str, image: Option<&str>) -> Option<Range<usize>> {
+    instructions(src, "FROM").into_iter().find_map(|args| {
+        let reference = args
+            .into_iter()
+            .find(|arg| !src[arg.clone()].starts_with("--"))?;
+        let text = &src[reference.clone()];
+        let text = text.split('@').next().unwrap_or(text);
+        let name_end = text.rfind('/').map_or(0, |idx| idx + 1);
+        let colon = name_end + text[name_end..].find(':')?;
+        let name = &text[..colon];
+        if image.is_some_and(|image| name != image && !name.ends_with(&format!("/{image}"))) {
+            return None;
+        }
+        let tag = &text[colon + 1..];
+        let start = reference.start + colon + 1;
+        Some(start..start + variant_start(tag))
+    })
+}
+
+/// Returns where the variant of a `tag` like `1.25.3-alpine` or `3.12-slim-bookworm`
+/// starts, its end if it has none. Tags which don't start with a version are kept whole.
+pub(crate) fn variant_start(tag: &str) -> usize {
+    let version = tag.strip_prefix('v').unwrap_or(tag);
+    if !version.starts_with(|c: char| c.is_ascii_digit()) {
+        return tag.len();
+    }
+    tag.find('-').unwrap_or(tag.len())
+}
+
+/// Returns the location of the value of the first `LABEL key=value`, without the quotes.
+pub(crate) fn label(src: &str, key: &str) -> Option<Range<usize>> {
+    instructions(src, "LABEL")
+        .into_iter()
+        .flatten()
+        .find_map(|arg| {
+            let value = src[arg.clone()].strip_prefix(key)?.strip_prefix('=')?;
+            let start = arg.end - value.len();
+            let quoted = value.len() >= 2
+                && (value.starts_with('"') && value.ends_with('"')
+                    || value.starts_with('\'') && value.ends_with('\''));
+            Some(if quoted {
+                start + 1..arg.end - 1
+            } else {
+                start..arg.end
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_and_labels() {
+        let src = "FROM --platform=linux/amd64 localhost:5000/rust:1.80.0 AS build
+from debian:12.5.0@sha256:abcd
+LABEL maintainer=\"someone\" \\
+      version=\"2.0.0\"
+";
+        let text = |span: Option<Range<usize>>| span.map(|span| &src[span]);
+        assert_eq!(text(image_tag(src, None)), Some("1.80.0"));
+        assert_eq!(text(image_tag(src, Some("debian"))), Some("12.5.0"));
+        assert_eq!(
+            text(image_tag(src, Some("localhost:5000/rust"))),
+            Some("1.80.0")
+        );
+        assert_eq!(text(image_tag(src, Some("nginx"))), None);
+        assert_eq!(text(label(src, "version")), Some("2.0.0"));
+        assert_eq!(text(label(src, "release")), None);
+        assert_eq!(image_tag("FROM scratch\n", None), None);
+
+        let variants = "FROM nginx:1.25.3-alpine\nFROM python:3.12.1-slim-bookworm\nFROM node:20.11.0-bookworm\n";
+        let text = |span: Option<Range<usize>>| span.map(|span| &variants[span]);
+        assert_eq!(text(image_tag(variants, Some("nginx"))), Some("1.25.3"));
+        assert_eq!(text(image_tag(variants, Some("python"))), Some("3.12.1"));
+        assert_eq!(text(image_tag(variants, Some("node"))), Some("20.11.0"));
+        assert_eq!(variant_start("latest-alpine"), 13);
+    }
+}
@@ -0,0 +1,44 @@
+//! The errors of the bumps.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerincError {
+    /// The text is longer than [`Limits::max_input`](crate::Limits::max_input).
+    InputTooLarge { len: usize, max: usize },
+    /// The regex does not fit into the [`Limits`](crate::Limits) or cannot be used.
+    Regex(String),
+    /// The text is not a version.
+    InvalidVersion(String),
+    /// The version cannot be bumped that way, like a component which would overflow or a
+    /// pre-release of a version without one.
+    CannotBump(String),
+}
+
+impl fmt::Display for VerincError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerincError::InputTooLarge { len, max } => {
+                write!(f, "input of {len} bytes exceeds the limit of {max}")
+            }
+            VerincError::Regex(err) => f.write_str(err),
+            VerincError::InvalidVersion(version) => write!(f, "'{version}' is not a version"),
+            VerincError::CannotBump(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for VerincError {}
+
+impl From<regex::Error> for VerincError {
+    fn from(err: regex::Error) -> Self {
+        VerincError::Regex(err.to_string())
+    }
+}
+
+/// The rest of the library reports its errors as strings.
+impl From<VerincError> for String {
+    fn from(err: VerincError) -> Self {
+        err.to_string()
+    }
+}
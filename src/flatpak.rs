@@ -0,0 +1,223 @@
+//! Keeping the checksums of Flatpak (Flathub) manifest sources in sync with their URLs.
+//!
+//! After the version inside the `url` of a source was bumped, the artifact is downloaded
+//! and the `sha256` of the same source is replaced. Both JSON and YAML manifests are
+//! supported.
+
+use std::ops::Range;
+
+use crate::{
+    digest::{self, Limits},
+    json::{self, Spanned, Value},
+    parallel,
+};
+
+/// A source with both a `url` and a `sha256`.
+#[derive(Debug, PartialEq)]
+pub struct Source {
+    pub url: String,
+    /// Span of the hash itself, without any quotes.
+    pub sha256: Range<usize>,
+}
+
+/// Returns the sources of the manifest `src`.
+pub fn sources(src: &str) -> Result<Vec<Source>, String> {
+    if src.trim_start().starts_with('{') {
+        let doc = json::parse(src)?;
+        let mut sources = Vec::new();
+        json_sources(&doc, &mut sources);
+        Ok(sources)
+    } else {
+        Ok(yaml_sources(src))
+    }
+}
+
+fn json_sources(value: &Spanned<Value>, sources: &mut Vec<Source>) {
+    match &value.value {
+        Value::Object(entries) => {
+            let url = value.value.get("url").and_then(|u| u.value.as_str());
+            let sha256 = value
+                .value
+                .get("sha256")
+                .filter(|s| s.value.as_str().is_some());
+            if let (Some(url), Some(sha256)) = (url, sha256) {
+                let span = sha256.span.start + 1..sha256.span.end - 1;
+                sources.push(Source {
+                    url: url.to_string(),
+                    sha256: span,
+                });
+            }
+            for (_, value) in entries {
+                json_sources(value, sources);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                json_sources(item, sources);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Splits a YAML line into the column of its key, the key and the (unquoted) value span.
+fn yaml_entry(line: &str) -> Option<(usize, &str, Range<usize>)> {
+    let indent = line.len() - line.trim_start().len();
+    let mut rest = &line[indent..];
+    let mut column = indent;
+    if let Some(item) = rest.strip_prefix("- ") {
+        column += 2 + item.len() - item.trim_start().len();
+        rest = item.trim_start();
+    }
+
+    let (key, value) = rest.split_once(':')?;
+    let start = column + key.len() + 1;
+    let value = value.split(" #").next().unwrap_or(value);
+    let trimmed = value.trim();
+    let offset = start + value.len() - value.trim_start().len();
+    let quoted = trimmed.len() >= 2
+        && (trimmed.starts_with('"') && trimmed.ends_with('"')
+            || trimmed.starts_with('\'') && trimmed.ends_with('\''));
+    let span = if quoted {
+        offset + 1..offset + trimmed.len() - 1
+    } else {
+        offset..offset + trimmed.len()
+    };
+
+    Some((column, key.trim(), span))
+}
+
+/// A source of a YAML manifest which is still being read.
+struct Pending {
+    column: usize,
+    url: Option<String>,
+    sha256: Option<Range<usize>>,
+}
+
+fn yaml_sources(src: &str) -> Vec<Source> {
+    let mut sources = Vec::new();
+    let mut current: Option<Pending> = None;
+    let mut offset = 0;
+
+    let mut finish = |current: &mut Option<Pending>| {
+        if let Some(Pending {
+            url: Some(url),
+            sha256: Some(sha256),
+            ..
+        }) = current.take()
+        {
+            sources.push(Source { url, sha256 });
+        }
+    };
+
+    for line in src.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let Some((column, key, span)) = yaml_entry(line) else {
+            continue;
+        };
+        let item = line.trim_start().starts_with("- ");
+
+        if let Some(pending) = &current {
+            if column < pending.column || (column == pending.column && item) {
+                finish(&mut current);
+            }
+        }
+
+        if key == "url" || key == "sha256" {
+            let pending = current.get_or_insert(Pending {
+                column,
+                url: None,
+                sha256: None,
+            });
+            if pending.column != column {
+                continue;
+            }
+            let span = start + span.start..start + span.end;
+            if key == "url" {
+                pending.url = Some(src[span].to_string());
+            } else {
+                pending.sha256 = Some(span);
+            }
+        }
+    }
+    finish(&mut current);
+
+    sources
+}
+
+/// Computes the sha256 of the artifact at `url`.
+pub fn sha256(url: &str) -> Result<String, String> {
+    digest::fetch(url, &Limits::default()).map(|digests| digests.sha256)
+}
+
+/// Replaces the `sha256` of every source in `new` whose `url` is not present in `old`
+/// with the checksum returned by `hash`, which is called in parallel (see
+/// [`crate::parallel`]). Nothing is returned unless all of them succeed.
+pub fn update(
+    old: &str,
+    new: &str,
+    hash: impl Fn(&str) -> Result<String, String> + Sync,
+) -> Result<String, String> {
+    let known: Vec<_> = sources(old)?.into_iter().map(|s| s.url).collect();
+    let changed: Vec<_> = sources(new)?
+        .into_iter()
+        .filter(|source| !known.contains(&source.url))
+        .collect();
+    let hashes = parallel::map(&changed, |source| hash(&source.url));
+
+    let mut out = String::with_capacity(new.len());
+    let mut last = 0;
+    for (source, sha256) in changed.iter().zip(hashes) {
+        let sha256 = sha256.map_err(|err| format!("{}: {err}", source.url))?;
+        out.push_str(&new[last..source.sha256.start]);
+        out.push_str(&sha256);
+        last = source.sha256.end;
+    }
+    out.push_str(&new[last..]);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml() {
+        let src = "modules:\n  - name: foo\n    sources:\n      - type: archive\n        url: https://example.com/foo-1.0.0.tar.gz\n        sha256: 'aaaa' # old\n      - type: git\n        url: https://example.com/bar.git\n  - name: bar\n    sources:\n      - url: \"https://example.com/bar-2.0.0.tar.gz\"\n        sha256: bbbb\n";
+        let sources = sources(src).unwrap();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].url, "https://example.com/foo-1.0.0.tar.gz");
+        assert_eq!(&src[sources[0].sha256.clone()], "aaaa");
+        assert_eq!(sources[1].url, "https://example.com/bar-2.0.0.tar.gz");
+        assert_eq!(&src[sources[1].sha256.clone()], "bbbb");
+    }
+
+    #[test]
+    fn json() {
+        let src = r#"{"modules": [{"sources": [{"url": "https://example.com/foo-1.0.0.tar.gz", "sha256": "aaaa"}]}]}"#;
+        let sources = sources(src).unwrap();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(&src[sources[0].sha256.clone()], "aaaa");
+    }
+
+    #[test]
+    fn updates_changed_urls() {
+        let old = "sources:\n  - url: https://example.com/foo-1.0.0.tar.gz\n    sha256: aaaa\n  - url: https://example.com/bar-1.0.0.tar.gz\n    sha256: bbbb\n";
+        let new = old.replacen("foo-1.0.0", "foo-1.1.0", 1);
+
+        let updated = update(old, &new, |url| Ok(format!("hash of {url}"))).unwrap();
+        assert_eq!(
+            updated,
+            new.replace("aaaa", "hash of https://example.com/foo-1.1.0.tar.gz")
+        );
+
+        assert!(update(old, &new, |_| Err("404".to_string())).is_err());
+    }
+}
@@ -0,0 +1,360 @@
+//! File formats which are understood structurally by `--format`, so that just the
+//! version of the project is bumped instead of whatever the regex finds.
+
+use std::ops::Range;
+
+use crate::{
+    cargo, date, debian, dockerfile, ini, npm, pkgbuild::assignment, rpm, xml, yaml, Change,
+};
+
+/// A format known to [`Format::parse`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Format {
+    /// Cargo.toml, `[package].version`.
+    Cargo,
+    /// package.json, the top-level `"version"`.
+    Npm,
+    /// YAML, the scalar at the dot separated path of keys (`metadata.version`).
+    Yaml(String),
+    /// PKGBUILD, `pkgver=`. The `pkgrel=` is reset to 1 when it changes.
+    Pkgbuild,
+    /// RPM spec, `Version:`. The number of `Release:` is reset to 1 when it changes and
+    /// the entry is added to `%changelog`, if any.
+    Rpmspec(Option<Entry>),
+    /// debian/changelog, the version of the first stanza. A new stanza with the entry is
+    /// added on top of it.
+    DebChangelog(Entry),
+    /// XML, the text of the element at the slash separated path (`/project/version`).
+    Xml(String),
+    /// INI, the value of `section.key` (`metadata.version` of setup.cfg), just `key` for
+    /// the keys before the first section.
+    Ini(String),
+    /// Dockerfile, the tag of the first `FROM` image with the name (`nginx` or
+    /// `docker.io/library/nginx`), of the first one with a tag if `None`.
+    DockerFrom(Option<String>),
+    /// Dockerfile, the value of `LABEL <key>=`.
+    DockerLabel(String),
+}
+
+/// An entry added to the changelog of the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// `Name <email>`.
+    pub author: String,
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub message: String,
+}
+
+impl Format {
+    /// Returns the format called `name`, `None` if there is no such format. The path of
+    /// `yaml` defaults to `version`, that of `xml` to `/project/version` (Maven) and the
+    /// key of `ini` to `metadata.version` (setup.cfg), that of `dockerfile-label` to
+    /// `version`. The author and time of the
+    /// `debchangelog` entry need to be filled in.
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "cargo" => Some(Format::Cargo),
+            "npm" => Some(Format::Npm),
+            "yaml" => Some(Format::Yaml("version".to_string())),
+            "xml" => Some(Format::Xml("/project/version".to_string())),
+            "ini" => Some(Format::Ini("metadata.version".to_string())),
+            "dockerfile-from" => Some(Format::DockerFrom(None)),
+            "dockerfile-label" => Some(Format::DockerLabel("version".to_string())),
+            "pkgbuild" => Some(Format::Pkgbuild),
+            "rpmspec" => Some(Format::Rpmspec(None)),
+            "debchangelog" => Some(Format::DebChangelog(Entry {
+                author: String::new(),
+                time: 0,
+                message: "New upstream release.".to_string(),
+            })),
+            _ => None,
+        }
+    }
+
+    /// Returns the location of the version in `content`, without the quotes.
+    pub fn version_span(&self, content: &str) -> Result<Range<usize>, String> {
+        match self {
+            Format::Cargo => cargo::Manifest::parse(content)?
+                .version_span()
+                .ok_or_else(|| "there is no [package].version".to_string()),
+            Format::Npm => npm::Package::parse(content)?
+                .version_span()
+                .ok_or_else(|| "there is no top-level \"version\"".to_string()),
+            Format::Yaml(path) => yaml::parse(content)
+                .into_iter()
+                .find(|entry| entry.path.join(".") == *path)
+                .map(|entry| entry.span)
+                .ok_or_else(|| format!("there is no {path}")),
+            Format::Pkgbuild => {
+                assignment(content, "pkgver").ok_or_else(|| "there is no pkgver=".to_string())
+            }
+            Format::Rpmspec(_) => {
+                rpm::tag(content, "Version").ok_or_else(|| "there is no Version:".to_string())
+            }
+            Format::Xml(path) => {
+                let keys: Vec<_> = path.split('/').filter(|key| !key.is_empty()).collect();
+                xml::text_span(content, &keys)?.ok_or_else(|| format!("there is no {path}"))
+            }
+            Format::Ini(path) => {
+                let (section, key) = path.rsplit_once('.').unwrap_or(("", path));
+                let entry = ini::parse(content)
+                    .into_iter()
+                    .find(|entry| entry.section == section && entry.key == key)
+                    .ok_or_else(|| format!("there is no {path}"))?;
+                let value = &content[entry.span.clone()];
+                let quoted = value.len() >= 2
+                    && (value.starts_with('"') && value.ends_with('"')
+                        || value.starts_with('\'') && value.ends_with('\''));
+                Ok(if quoted {
+                    entry.span.start + 1..entry.span.end - 1
+                } else {
+                    entry.span
+                })
+            }
+            Format::DockerFrom(image) => dockerfile::image_tag(content, image.as_deref())
+                .ok_or_else(|| match image {
+                    Some(image) => format!("there is no FROM {image}:<tag>"),
+                    None => "there is no FROM with a tag".to_string(),
+                }),
+            Format::DockerLabel(key) => {
+                dockerfile::label(content, key).ok_or_else(|| format!("there is no LABEL {key}="))
+            }
+            Format::DebChangelog(_) => debian::version_span(content)
+                .ok_or_else(|| "there is no version in the first line".to_string()),
+        }
+    }
+
+    /// Returns the version which differs between `old` and `new`, unlike
+    /// [`crate::changes`] not confused by the versions of added changelog entries.
+    pub fn changes(&self, old: &str, new: &str) -> Vec<Change> {
+        let (Ok(old_span), Ok(new_span)) = (self.version_span(old), self.version_span(new)) else {
+            return Vec::new();
+        };
+        if old[old_span.clone()] == new[new_span.clone()] {
+            return Vec::new();
+        }
+        vec![Change {
+            position: 0,
+            span: old_span.clone(),
+            old: old[old_span].to_string(),
+            new: new[new_span].to_string(),
+        }]
+    }
+
+    /// Returns `content` with the version replaced by what `bump` returns for it.
+    pub fn bump(&self, content: &str, bump: impl FnOnce(&str) -> String) -> Result<String, String> {
+        let span = self.version_span(content)?;
+        let new = bump(&content[span.clone()]);
+        if new == content[span.clone()] {
+            return Ok(content.to_string());
+        }
+        if let Format::DebChangelog(entry) = self {
+            let date = date::rfc2822(entry.time);
+            return Ok(debian::prepend(
+                content,
+                &new,
+                &entry.message,
+                &entry.author,
+                &date,
+            ));
+        }
+        let result = splice(content, span, &new);
+
+        let result = match self.release(&result) {
+            Some(span) => splice(&result, span, "1"),
+            None => result,
+        };
+        match self {
+            Format::Rpmspec(Some(entry)) => {
+                let release = self.release(&result).map_or("1", |span| &result[span]);
+                let entry = format!(
+                    "* {} {} - {new}-{release}\n- {}\n\n",
+                    date::rpm(entry.time),
+                    entry.author,
+                    entry.message
+                );
+                rpm::add_changelog(&result, &entry)
+            }
+            _ => Ok(result),
+        }
+    }
+
+    /// Returns the location of the release number, which starts at 1 for every version.
+    fn release(&self, content: &str) -> Option<Range<usize>> {
+        match self {
+            Format::Pkgbuild => assignment(content, "pkgrel"),
+            Format::Rpmspec(_) => rpm::release(content),
+            _ => None,
+        }
+    }
+
+    /// Returns `content` with just the release incremented (`pkgrel=2.1` to `pkgrel=3`,
+    /// `Release: 1%{?dist}` to `Release: 2%{?dist}`).
+    pub fn inc_rel(&self, content: &str) -> Result<String, String> {
+        if !matches!(self, Format::Pkgbuild | Format::Rpmspec(_)) {
+            return Err("only PKGBUILD and RPM spec files have a release".to_string());
+        }
+        let span = self.release(content).ok_or("there is no release")?;
+        let rel = &content[span.clone()];
+        let major = rel.split_once('.').map_or(rel, |(major, _)| major);
+        let rel = major
+            .parse::<u64>()
+            .ok()
+            .and_then(|rel| rel.checked_add(1))
+            .ok_or_else(|| format!("invalid release {rel}"))?;
+        Ok(splice(content, span, &rel.to_string()))
+    }
+}
+
+fn splice(content: &str, span: Range<usize>, with: &str) -> String {
+    format!("{}{with}{}", &content[..span.start], &content[span.end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo() {
+        let content = r#"[package]
+name = "foo"
+version = "1.0.0"
+documentation = "https://docs.rs/foo/1.0.0"
+
+[dependencies]
+bar = "1.0.0"
+"#;
+        assert_eq!(
+            Format::Cargo.bump(content, |v| crate::inc(
+                v,
+                crate::Position::Nth(0),
+                crate::Version::Minor,
+                1
+            )
+            .unwrap()),
+            Ok(content.replacen("1.0.0", "1.1.0", 1))
+        );
+        assert!(Format::Cargo.version_span("[workspace]\n").is_err());
+    }
+
+    #[test]
+    fn npm() {
+        let content = r#"{
+  "name": "foo",
+  "dependencies": { "version": "1.0.0" },
+  "version": "1.0.0"
+}"#;
+        let span = Format::Npm.version_span(content).unwrap();
+        assert_eq!(span.start, content.rfind("1.0.0").unwrap());
+        assert_eq!(&content[span], "1.0.0");
+        assert!(Format::Npm.version_span("[]").is_err());
+    }
+
+    #[test]
+    fn yaml() {
+        let content = "version: 1.0.0\nmetadata:\n  version: \"2.0.0\"\n";
+        let format = Format::Yaml("metadata.version".to_string());
+        assert_eq!(
+            format.bump(content, |_| "2.1.0".to_string()),
+            Ok("version: 1.0.0\nmetadata:\n  version: \"2.1.0\"\n".to_string())
+        );
+        assert!(Format::Yaml("metadata".to_string())
+            .version_span(content)
+            .is_err());
+    }
+
+    #[test]
+    fn ini() {
+        let content = "[options]\nversion = 1.0.0\n[metadata]\nname = foo\nversion = \"1.2.3\"\n";
+        let span = Format::Ini("metadata.version".to_string())
+            .version_span(content)
+            .unwrap();
+        assert_eq!(span.start, content.rfind("1.2.3").unwrap());
+        assert_eq!(&content[span], "1.2.3");
+        assert!(Format::Ini("version".to_string())
+            .version_span(content)
+            .is_err());
+    }
+
+    #[test]
+    fn pkgbuild() {
+        let content =
+            "pkgname=foo\npkgver=1.0.0 # upstream\npkgrel='3'\nsource=(\"foo-1.0.0.tar.gz\")\n";
+        let bump =
+            |v: &str| crate::inc(v, crate::Position::Nth(0), crate::Version::Patch, 1).unwrap();
+        assert_eq!(
+            Format::Pkgbuild.bump(content, bump),
+            Ok(
+                "pkgname=foo\npkgver=1.0.1 # upstream\npkgrel='1'\nsource=(\"foo-1.0.0.tar.gz\")\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            Format::Pkgbuild.bump(content, |v| v.to_string()),
+            Ok(content.to_string())
+        );
+        assert!(Format::Pkgbuild.version_span("_pkgver=1.0.0\n").is_err());
+
+        assert_eq!(
+            Format::Pkgbuild.inc_rel("pkgver=1.0.0\npkgrel=2.1\n"),
+            Ok("pkgver=1.0.0\npkgrel=3\n".to_string())
+        );
+        assert!(Format::Pkgbuild.inc_rel("pkgrel=$rel\n").is_err());
+        assert!(Format::Cargo.inc_rel("pkgrel=1\n").is_err());
+    }
+
+    #[test]
+    fn rpmspec() {
+        let spec = "Name: foo\nVersion: 1.2.3\nRelease: 4%{?dist}\n\n%changelog\n* Thu Jan 01 1970 A <a@a> - 1.2.3-4\n- Old\n";
+        let entry = Entry {
+            author: "B <b@b>".to_string(),
+            time: 1_791_966_600,
+            message: "Update to 1.3.0".to_string(),
+        };
+        let format = Format::Rpmspec(Some(entry));
+        assert_eq!(
+            format.bump(spec, |_| "1.3.0".to_string()),
+            Ok("Name: foo\nVersion: 1.3.0\nRelease: 1%{?dist}\n\n%changelog\n* Wed Oct 14 2026 B <b@b> - 1.3.0-1\n- Update to 1.3.0\n\n* Thu Jan 01 1970 A <a@a> - 1.2.3-4\n- Old\n".to_string())
+        );
+        assert_eq!(
+            Format::Rpmspec(None).changes(spec, &spec.replace("1.2.3-4", "1.2.4-1")),
+            []
+        );
+        assert_eq!(
+            Format::Rpmspec(None).inc_rel(spec),
+            Ok(spec.replace("Release: 4", "Release: 5"))
+        );
+    }
+
+    #[test]
+    fn debchangelog() {
+        let changelog = "foo (1.2.3-2) unstable; urgency=medium\n\n  * Fix.\n\n -- A <a@a>  Thu, 01 Jan 1970 00:00:00 +0000\n";
+        let format = Format::DebChangelog(Entry {
+            author: "B <b@b>".to_string(),
+            time: 1_791_966_600,
+            message: "New upstream release.".to_string(),
+        });
+        let result = format.bump(changelog, |_| "1.2.4".to_string()).unwrap();
+        assert_eq!(
+            result,
+            format!("foo (1.2.4-1) UNRELEASED; urgency=medium\n\n  * New upstream release.\n\n -- B <b@b>  Wed, 14 Oct 2026 08:30:00 +0000\n\n{changelog}")
+        );
+        let changes = format.changes(changelog, &result);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((&*changes[0].old, &*changes[0].new), ("1.2.3", "1.2.4"));
+    }
+
+    #[test]
+    fn dockerfile() {
+        let bump =
+            |v: &str| crate::inc(v, crate::Position::Nth(0), crate::Version::Patch, 1).unwrap();
+        for variant in ["alpine", "slim", "bookworm"] {
+            let content = format!("FROM nginx:1.25.3-{variant} AS base\n");
+            assert_eq!(
+                Format::DockerFrom(Some("nginx".to_string())).bump(&content, bump),
+                Ok(content.replace("1.25.3", "1.25.4"))
+            );
+        }
+    }
+}
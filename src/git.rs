@@ -0,0 +1,121 @@
+//! Helpers for working with Git repositories.
+
+use std::{io, path::Path, process::Command};
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<Option<String>> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Runs git in `dir` and turns a failure into an error message containing its stderr.
+fn run(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    // Git reads the proxy variables by itself.
+    if let Some(proxy) = crate::http::proxy() {
+        cmd.arg("-c").arg(format!("http.proxy={proxy}"));
+    }
+    let output = cmd
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|err| format!("cannot run git: {err}"))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the version of the latest `vX.Y.Z` tag reachable from `HEAD` of the repository
+/// in `dir`, without the `v` prefix.
+pub fn latest_tag(dir: &Path) -> io::Result<Option<String>> {
+    let tag = git(
+        dir,
+        &[
+            "describe",
+            "--tags",
+            "--abbrev=0",
+            "--match",
+            "v[0-9]*.[0-9]*.[0-9]*",
+        ],
+    )?;
+
+    Ok(tag.map(|tag| tag.trim_start_matches('v').to_string()))
+}
+
+/// Returns the messages of the commits since `tag` (all of them without one), the newest
+/// first.
+pub fn messages_since(dir: &Path, tag: Option<&str>) -> Result<Vec<String>, String> {
+    let range = tag.map_or("HEAD".to_string(), |tag| format!("{tag}..HEAD"));
+    let log = run(dir, &["log", "--format=%B%x00", &range])?;
+    Ok(log
+        .split('\0')
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns `Name <email>` of the Git user configured for `dir`.
+pub fn author(dir: &Path) -> Option<String> {
+    let name = git(dir, &["config", "user.name"]).ok()??;
+    let email = git(dir, &["config", "user.email"]).ok()??;
+    Some(format!("{name} <{email}>"))
+}
+
+/// Returns the name of the checked out branch.
+pub fn current_branch(dir: &Path) -> Result<String, String> {
+    run(dir, &["symbolic-ref", "--short", "HEAD"])
+}
+
+/// Creates and checks out `branch`, keeping any local changes.
+pub fn create_branch(dir: &Path, branch: &str) -> Result<(), String> {
+    run(dir, &["checkout", "-b", branch]).map(drop)
+}
+
+pub fn checkout(dir: &Path, branch: &str) -> Result<(), String> {
+    run(dir, &["checkout", branch]).map(drop)
+}
+
+/// Stages `files` and commits them with `message`.
+pub fn commit(dir: &Path, files: &[&Path], message: &str) -> Result<(), String> {
+    let mut args = vec!["add", "--"];
+    for file in files {
+        args.push(file.to_str().ok_or("invalid file name")?);
+    }
+    run(dir, &args)?;
+
+    run(dir, &["commit", "-m", message]).map(drop)
+}
+
+/// Returns the tracked files with uncommitted changes in the repository containing `dir`.
+pub fn uncommitted(dir: &Path) -> Result<Vec<String>, String> {
+    let status = run(dir, &["status", "--porcelain", "--untracked-files=no"])?;
+    Ok(status
+        .lines()
+        // The status may start with a space, which is trimmed on the first line.
+        .filter_map(|line| line.trim_start().split_once(' '))
+        .map(|(_, path)| path.trim_start().to_string())
+        .collect())
+}
+
+/// Pushes `branch` to `remote` and sets it as the upstream.
+pub fn push(dir: &Path, remote: &str, branch: &str) -> Result<(), String> {
+    crate::http::online(&format!("git push {remote}"))?;
+    run(dir, &["push", "--set-upstream", remote, branch]).map(drop)
+}
+
+pub fn remote_url(dir: &Path, remote: &str) -> Result<String, String> {
+    run(dir, &["remote", "get-url", remote])
+}
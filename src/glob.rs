@@ -0,0 +1,155 @@
+//! Expansion of the simple wildcard patterns used by workspace manifests and on the
+//! command line.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Returns whether `s` contains a wildcard.
+pub fn is_pattern(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+/// Expands `pattern` (relative to the current directory unless absolute) to the files
+/// it matches, sorted.
+pub fn files(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    // The components up to the first wildcard are taken as they are.
+    let mut base = PathBuf::new();
+    let mut rest = Vec::new();
+    for component in Path::new(pattern).components() {
+        let component = component.as_os_str();
+        match component.to_str() {
+            Some(c) if !rest.is_empty() || is_pattern(c) => rest.push(c),
+            _ => base.push(component),
+        }
+    }
+    let dir = if base.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        &base
+    };
+
+    Ok(expand(dir, &rest.join("/"))?
+        .into_iter()
+        .filter(|path| path.is_file())
+        .map(|path| match path.strip_prefix(".") {
+            Ok(path) if base.as_os_str().is_empty() => path.to_path_buf(),
+            _ => path,
+        })
+        .collect())
+}
+
+/// Returns the files below `dir` whose names match one of `include` (any name if it's
+/// empty) and none of `exclude`, sorted. Directories matching `exclude` and hidden ones
+/// (`.git`) are not entered.
+pub fn walk(dir: &Path, include: &[String], exclude: &[String]) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if exclude.iter().any(|p| wildcard(p, name, cfg!(windows))) {
+                continue;
+            }
+
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if !name.starts_with('.') {
+                    dirs.push(entry.path());
+                }
+            } else if file_type.is_file()
+                && (include.is_empty() || include.iter().any(|p| wildcard(p, name, cfg!(windows))))
+            {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Expands `*` and `?` in the components of `pattern` relative to `dir`. On Windows,
+/// backslashes separate the components as well and names are matched ignoring case.
+pub(crate) fn expand(dir: &Path, pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let mut paths = vec![dir.to_path_buf()];
+
+    let separator = |c: char| c == '/' || cfg!(windows) && c == '\\';
+    for component in pattern
+        .split(separator)
+        .filter(|c| !c.is_empty() && *c != ".")
+    {
+        let mut next = Vec::new();
+        for path in paths {
+            if !component.contains(['*', '?']) {
+                next.push(path.join(component));
+                continue;
+            }
+
+            let Ok(entries) = fs::read_dir(&path) else {
+                continue;
+            };
+            for entry in entries {
+                let entry = entry?;
+                if entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|n| wildcard(component, n, cfg!(windows)))
+                {
+                    next.push(entry.path());
+                }
+            }
+        }
+        next.sort();
+        paths = next;
+    }
+
+    Ok(paths)
+}
+
+/// Matches `name` against `pattern`, ignoring case if `ignore_case` (as Windows does).
+fn wildcard(pattern: &str, name: &str, ignore_case: bool) -> bool {
+    let fold = |s: &str| -> Vec<char> {
+        if ignore_case {
+            s.to_lowercase().chars().collect()
+        } else {
+            s.chars().collect()
+        }
+    };
+    let pattern = fold(pattern);
+    let name = fold(name);
+
+    fn matches(p: &[char], n: &[char]) -> bool {
+        match (p.first(), n.first()) {
+            (None, None) => true,
+            (Some('*'), _) => matches(&p[1..], n) || (!n.is_empty() && matches(p, &n[1..])),
+            (Some('?'), Some(_)) => matches(&p[1..], &n[1..]),
+            (Some(a), Some(b)) if a == b => matches(&p[1..], &n[1..]),
+            _ => false,
+        }
+    }
+
+    matches(&pattern, &name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard("*", "foo", false));
+        assert!(wildcard("foo-*", "foo-bar", false));
+        assert!(wildcard("f?o", "foo", false));
+        assert!(!wildcard("foo-*", "bar-foo", false));
+        assert!(!wildcard("Crates-*", "crates-foo", false));
+        assert!(wildcard("Crates-*", "crates-foo", true));
+
+        assert!(is_pattern("pkgs/*/PKGBUILD"));
+        assert!(!is_pattern("pkgs/foo/PKGBUILD"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Running of user supplied shell commands (hooks).
+//!
+//! Hooks come from configurations shared between machines, so they are sandboxed by
+//! default: they get a scrubbed environment with only [`ALLOWED_VARS`] and the variables
+//! given to them, may only run inside of the project directory and are killed when they
+//! don't finish in time. This confines the working directory, not what the command can
+//! access. [`Sandbox::unsafe_hooks`] turns all of it off.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// The variables hooks get from the environment of verinc.
+pub const ALLOWED_VARS: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "LANG", "LC_ALL", "TERM", "TMPDIR", "TZ",
+];
+
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    /// Hooks must run in this directory or below.
+    pub root: PathBuf,
+    pub timeout: Duration,
+    /// Passes the whole environment and lifts the restrictions on the directory and the
+    /// time.
+    pub unsafe_hooks: bool,
+}
+
+impl Sandbox {
+    /// Returns a sandbox for the project in `root` with a timeout of five minutes.
+    pub fn new(root: &Path) -> Self {
+        Sandbox {
+            root: root.to_path_buf(),
+            timeout: Duration::from_secs(5 * 60),
+            unsafe_hooks: false,
+        }
+    }
+
+    /// Runs `command` with the shell in `dir` with `vars` set. Fails unless it exits
+    /// successfully.
+    pub fn run(&self, command: &str, dir: &Path, vars: &[(&str, &str)]) -> Result<(), String> {
+        let mut cmd = shell(command);
+        cmd.current_dir(dir);
+
+        if !self.unsafe_hooks {
+            let root = crate::path::canonicalize(&self.root)
+                .map_err(|err| format!("cannot resolve '{}': {err}", self.root.display()))?;
+            let dir = crate::path::canonicalize(dir)
+                .map_err(|err| format!("cannot resolve '{}': {err}", dir.display()))?;
+            if !dir.starts_with(&root) {
+                return Err(format!(
+                    "hook '{command}' would run in '{}' outside of '{}'",
+                    dir.display(),
+                    root.display()
+                ));
+            }
+
+            cmd.env_clear();
+            for var in ALLOWED_VARS {
+                if let Some(value) = env::var_os(var) {
+                    cmd.env(var, value);
+                }
+            }
+            cmd.env("PWD", &dir);
+        }
+        cmd.envs(vars.iter().copied());
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|err| format!("cannot run hook '{command}': {err}"))?;
+
+        let start = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if !self.unsafe_hooks && start.elapsed() >= self.timeout => {
+                    kill(&mut child);
+                    let _ = child.wait();
+                    return Err(format!(
+                        "hook '{command}' timed out after {}s",
+                        self.timeout.as_secs_f32()
+                    ));
+                }
+                Ok(None) => sleep(Duration::from_millis(20)),
+                Err(err) => return Err(format!("cannot run hook '{command}': {err}")),
+            }
+        };
+
+        if !status.success() {
+            return Err(format!("hook '{command}' failed with {status}"));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the shell running `command` in a process group of its own on Unix, so that
+/// [`kill`] gets whatever the command started too.
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command).process_group(0);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Kills the process group of `child`.
+#[cfg(unix)]
+fn kill(child: &mut Child) {
+    let _ = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+}
+
+#[cfg(windows)]
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sandbox() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut sandbox = Sandbox::new(root);
+
+        assert!(sandbox
+            .run("test \"$NEW\" = 1.0.1", root, &[("NEW", "1.0.1")])
+            .is_ok());
+        assert!(sandbox.run("exit 3", root, &[]).is_err());
+
+        // Cargo sets this for the tests but the hook doesn't get it.
+        assert!(sandbox
+            .run("test -z \"$CARGO_MANIFEST_DIR\"", root, &[])
+            .is_ok());
+        assert!(sandbox.run("true", Path::new("/"), &[]).is_err());
+
+        sandbox.timeout = Duration::from_millis(100);
+        let err = sandbox.run("sleep 5", root, &[]).unwrap_err();
+        assert!(err.contains("timed out"), "{err}");
+
+        // The commands started by the hook are killed too.
+        let path = env::temp_dir().join(format!("verinc-hook-{}", std::process::id()));
+        let command = format!("sleep 5 & echo $! > '{}'; wait", path.display());
+        assert!(sandbox.run(&command, root, &[]).is_err());
+        let pid = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let start = Instant::now();
+        let alive = loop {
+            let output = Command::new("ps")
+                .args(["-o", "stat=", "-p", pid.trim()])
+                .output()
+                .unwrap();
+            let stat = String::from_utf8_lossy(&output.stdout);
+            if stat.trim().is_empty() || stat.starts_with('Z') {
+                break false;
+            }
+            if start.elapsed() > Duration::from_secs(2) {
+                break true;
+            }
+            sleep(Duration::from_millis(20));
+        };
+        assert!(!alive, "{pid}");
+
+        sandbox.unsafe_hooks = true;
+        assert!(sandbox
+            .run("test -n \"$CARGO_MANIFEST_DIR\"", root, &[])
+            .is_ok());
+        assert!(sandbox.run("true", Path::new("/"), &[]).is_ok());
+    }
+}
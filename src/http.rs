@@ -0,0 +1,417 @@
+//! A tiny HTTP client on top of curl, which is available on pretty much every system we
+//! care about and already knows about proxies, certificates and redirects.
+
+use std::{
+    env,
+    hash::{BuildHasher, RandomState},
+    io::{Read, Write},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    thread::sleep,
+    time::Duration,
+};
+
+/// How many times requests which failed for a transient reason are retried.
+pub(crate) const RETRIES: u32 = 3;
+
+/// Longer `Retry-After` are not waited for, the request fails instead.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+static RECEIVED: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the number of requests made (including the retries) and the number of bytes
+/// received so far.
+pub(crate) fn stats() -> (u64, u64) {
+    (
+        REQUESTS.load(Ordering::Relaxed),
+        RECEIVED.load(Ordering::Relaxed),
+    )
+}
+
+pub(crate) fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+static PROXY: Mutex<Option<String>> = Mutex::new(None);
+
+pub(crate) fn set_proxy(proxy: Option<&str>) {
+    *PROXY.lock().unwrap() = proxy.map(str::to_string);
+}
+
+/// Returns the proxy given to [`set_proxy`].
+pub(crate) fn proxy() -> Option<String> {
+    PROXY.lock().unwrap().clone()
+}
+
+/// Returns curl with the options shared by all requests.
+fn curl() -> Command {
+    let mut cmd = Command::new("curl");
+    cmd.args([
+        "-sS",
+        "-L",
+        "-H",
+        concat!("User-Agent: verinc/", env!("CARGO_PKG_VERSION")),
+    ]);
+
+    // curl reads http_proxy, HTTPS_PROXY and NO_PROXY (in either case) by itself, except
+    // for HTTP_PROXY which it ignores for historical reasons.
+    match proxy() {
+        Some(proxy) => {
+            cmd.arg("--proxy").arg(proxy);
+        }
+        None => {
+            if let (Ok(proxy), Err(_)) = (env::var("HTTP_PROXY"), env::var("http_proxy")) {
+                cmd.env("http_proxy", proxy);
+            }
+        }
+    }
+    cmd
+}
+
+pub(crate) fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fails if the network must not be used for `what`.
+pub(crate) fn online(what: &str) -> Result<(), String> {
+    if is_offline() {
+        return Err(format!("{what}: the network is disabled (offline mode)"));
+    }
+    Ok(())
+}
+
+pub(crate) struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A failed attempt.
+pub(crate) enum Error {
+    /// Worth another attempt, e.g. a timeout or a server error.
+    Transient(String),
+    Fatal(String),
+}
+
+/// Returns the delay before the retry number `attempt` (starting at 1). The delay grows
+/// exponentially from half a second up to half a minute and is randomized by up to a half
+/// so that many clients don't come back at the same time.
+pub(crate) fn backoff(attempt: u32) -> Duration {
+    let delay = Duration::from_millis(500 << attempt.clamp(1, 7).saturating_sub(1));
+    let delay = delay.min(Duration::from_secs(30));
+    let jitter = RandomState::new().hash_one(attempt) % 1000;
+    delay - delay / 2 * jitter as u32 / 1000
+}
+
+/// Runs `attempt` until it succeeds, fails for good or `retries` retries are used up.
+pub(crate) fn retry<T>(
+    retries: u32,
+    mut attempt: impl FnMut() -> Result<T, Error>,
+) -> Result<T, String> {
+    let mut retried = 0;
+    loop {
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(Error::Transient(_)) if retried < retries && !is_offline() => {
+                retried += 1;
+                sleep(backoff(retried));
+            }
+            Err(Error::Transient(err) | Error::Fatal(err)) => return Err(err),
+        }
+    }
+}
+
+/// Returns the delay requested by a 429 or 503 response, in seconds (HTTP dates are not
+/// supported).
+fn retry_after(response: &Response) -> Option<Duration> {
+    if !matches!(response.status, 429 | 503) {
+        return None;
+    }
+    let seconds = response.header("retry-after")?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Sends a GET request, retrying failed connections, 429 and 5xx responses.
+pub(crate) fn get(url: &str, headers: &[(&str, String)]) -> Result<Response, String> {
+    send(url, true, || request("GET", url, headers, None))
+}
+
+/// Sends `body` as JSON. As the request might have been processed already, it is only
+/// retried after a 429 response.
+pub(crate) fn post(url: &str, headers: &[(&str, String)], body: &str) -> Result<Response, String> {
+    send(url, false, || request("POST", url, headers, Some(body)))
+}
+
+fn send(
+    url: &str,
+    idempotent: bool,
+    mut request: impl FnMut() -> Result<Response, String>,
+) -> Result<Response, String> {
+    online(url)?;
+    let mut retried = 0;
+    loop {
+        REQUESTS.fetch_add(1, Ordering::Relaxed);
+        let result = request();
+        if let Ok(response) = &result {
+            RECEIVED.fetch_add(response.body.len() as u64, Ordering::Relaxed);
+        }
+        let delay = match &result {
+            Ok(response) if response.status == 429 || idempotent && response.status >= 500 => {
+                retry_after(response).unwrap_or_else(|| backoff(retried + 1))
+            }
+            Err(_) if idempotent => backoff(retried + 1),
+            _ => return result,
+        };
+        if retried >= RETRIES || delay > MAX_RETRY_AFTER {
+            return result;
+        }
+        retried += 1;
+        sleep(delay);
+    }
+}
+
+/// Downloads `url` and passes its contents to `sink` chunk by chunk. Fails on HTTP errors
+/// and when more than `max_size` bytes are received. Returns the size of the download.
+/// Use with [`retry`], `sink` must start over on every attempt.
+pub(crate) fn stream(url: &str, max_size: u64, mut sink: impl FnMut(&[u8])) -> Result<u64, Error> {
+    online(url).map_err(Error::Fatal)?;
+    REQUESTS.fetch_add(1, Ordering::Relaxed);
+    let mut child = curl()
+        .args(["-f", "--max-time", "600", "--max-filesize"])
+        .arg(max_size.to_string())
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| Error::Fatal(format!("cannot run curl: {err}")))?;
+
+    let mut stdout = child.stdout.take().unwrap();
+    let mut buf = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = match stdout.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) => {
+                let _ = child.kill();
+                return Err(Error::Transient(format!("cannot read the download: {err}")));
+            }
+        };
+        size += read as u64;
+        RECEIVED.fetch_add(read as u64, Ordering::Relaxed);
+        if size > max_size {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::Fatal(format!("larger than {max_size} bytes")));
+        }
+        sink(&buf[..read]);
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| Error::Fatal(format!("cannot run curl: {err}")))?;
+    let err = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    match output.status.code() {
+        Some(0) => Ok(size),
+        // Exceeded --max-filesize.
+        Some(63) => Err(Error::Fatal(format!("larger than {max_size} bytes"))),
+        // HTTP errors, only the server errors are worth retrying.
+        Some(22) if !err.contains("error: 5") => Err(Error::Fatal(err)),
+        _ => Err(Error::Transient(err)),
+    }
+}
+
+/// Quotes `s` for a curl config file.
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Returns curl for the request and the config to write to its stdin. The headers and
+/// the body are passed in the config so that tokens don't show up in the process list.
+fn command(
+    method: &str,
+    url: &str,
+    headers: &[(&str, String)],
+    body: Option<&str>,
+) -> (Command, String) {
+    let mut cmd = curl();
+    cmd.args(["-i", "--max-time", "60", "-X", method, "--config", "-"]);
+    let mut config = String::new();
+    for (name, value) in headers {
+        config.push_str(&format!(
+            "header = {}\n",
+            quote(&format!("{name}: {value}"))
+        ));
+    }
+    if let Some(body) = body {
+        config.push_str("header = \"Content-Type: application/json\"\n");
+        config.push_str(&format!("data-raw = {}\n", quote(body)));
+    }
+    cmd.arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    (cmd, config)
+}
+
+fn request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, String)],
+    body: Option<&str>,
+) -> Result<Response, String> {
+    let (mut cmd, config) = command(method, url, headers, body);
+    let mut child = cmd
+        .spawn()
+        .map_err(|err| format!("cannot run curl: {err}"))?;
+    // Dropping stdin closes it, curl reads the config until then.
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(config.as_bytes())
+            .map_err(|err| format!("cannot run curl: {err}"))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("cannot run curl: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    parse(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| format!("{url}: invalid response"))
+}
+
+/// Parses the output of `curl -i`, skipping the headers of redirects and interim responses.
+fn parse(output: &str) -> Option<Response> {
+    let mut rest = output;
+    loop {
+        let (head, body) = rest
+            .split_once("\r\n\r\n")
+            .or_else(|| rest.split_once("\n\n"))
+            .unwrap_or((rest, ""));
+
+        let mut lines = head.lines();
+        let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .collect();
+
+        if body.starts_with("HTTP/") {
+            rest = body;
+            continue;
+        }
+
+        return Some(Response {
+            status,
+            headers,
+            body: body.to_string(),
+        });
+    }
+}
+
+/// Percent-encodes `s` for use in a query string.
+pub(crate) fn encode(s: &str) -> String {
+    let mut out = String::new();
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' | b'/' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirects() {
+        let output = "HTTP/1.1 301 Moved Permanently\r\nLocation: /b\r\n\r\nHTTP/2 200\r\ncontent-type: text/plain\r\n\r\nhello\r\n\r\nworld";
+        let response = parse(output).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("Content-Type"), Some("text/plain"));
+        assert_eq!(response.body, "hello\r\n\r\nworld");
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(
+            encode("repository:library/nginx:pull"),
+            "repository:library/nginx:pull"
+        );
+        assert_eq!(encode("a b&c"), "a%20b%26c");
+    }
+
+    #[test]
+    fn backoff_and_retry_after() {
+        for attempt in 1..10 {
+            let delay = backoff(attempt);
+            let max =
+                Duration::from_millis(500 << (attempt.min(7) - 1)).min(Duration::from_secs(30));
+            assert!(delay <= max && delay >= max / 2, "{attempt}: {delay:?}");
+        }
+
+        let mut response = Response {
+            status: 429,
+            headers: vec![("Retry-After".to_string(), "7".to_string())],
+            body: String::new(),
+        };
+        assert_eq!(retry_after(&response), Some(Duration::from_secs(7)));
+        response.status = 500;
+        assert_eq!(retry_after(&response), None);
+    }
+
+    #[test]
+    fn headers_on_stdin() {
+        let headers = [("Authorization", "Bearer secret".to_string())];
+        let (cmd, config) = command(
+            "POST",
+            "https://example.com",
+            &headers,
+            Some("{\"a\": \"\\\n\"}"),
+        );
+        assert!(cmd
+            .get_args()
+            .all(|arg| !arg.to_string_lossy().contains("secret")));
+        assert_eq!(
+            config,
+            "header = \"Authorization: Bearer secret\"\nheader = \"Content-Type: application/json\"\ndata-raw = \"{\\\"a\\\": \\\"\\\\\\n\\\"}\"\n"
+        );
+    }
+}
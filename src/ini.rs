@@ -0,0 +1,92 @@
+//! A forgiving reader of INI files (as written by Python's configparser) which remembers
+//! where every value is located in the source.
+
+use std::ops::Range;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Entry {
+    pub section: String,
+    pub key: String,
+    /// The value, continuation lines are joined with a newline.
+    pub value: String,
+    pub span: Range<usize>,
+}
+
+pub(crate) fn parse(src: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut section = String::new();
+    let mut offset = 0;
+    // Whether indented lines continue the value of the last entry.
+    let mut continued = false;
+
+    for line in src.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if continued && line.starts_with([' ', '\t']) {
+            let entry = entries.last_mut().unwrap();
+            if !entry.value.is_empty() {
+                entry.value.push('\n');
+            }
+            entry.value.push_str(trimmed);
+            entry.span.end = start + line.trim_end().len();
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continued = false;
+            continue;
+        }
+
+        let Some(idx) = line.find(['=', ':']) else {
+            continued = false;
+            continue;
+        };
+        let value = &line[idx + 1..];
+        let value_start = start + idx + 1 + value.len() - value.trim_start().len();
+        entries.push(Entry {
+            section: section.clone(),
+            key: line[..idx].trim().to_string(),
+            value: value.trim().to_string(),
+            span: value_start..value_start + value.trim().len(),
+        });
+        continued = true;
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let src = "; comment\n[a]\nx = 1\ny: two\n  lines\n\n[b:c]\nz =\n";
+        let entries = parse(src);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].section, "a");
+        assert_eq!(&src[entries[0].span.clone()], "1");
+        assert_eq!(entries[1].value, "two\nlines");
+        assert_eq!(&src[entries[1].span.clone()], "two\n  lines");
+        assert_eq!(
+            entries[2],
+            Entry {
+                section: "b:c".to_string(),
+                key: "z".to_string(),
+                value: String::new(),
+                span: src.len() - 1..src.len() - 1,
+            }
+        );
+    }
+}
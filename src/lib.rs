@@ -2,95 +2,1240 @@
 //! numbers in the given file. The primary use case for this is maintenance of my
 //! Arch Linux packages.
 
-use std::io::{stdout, IsTerminal};
+pub mod aur;
+pub mod bumpversion;
+pub mod cache;
+pub mod cargo;
+pub mod changelog;
+#[cfg(feature = "compat")]
+pub mod compat;
+pub mod config;
+pub mod conventional;
+mod date;
+mod debian;
+pub mod diff;
+pub mod digest;
+mod dockerfile;
+mod error;
+pub mod flatpak;
+pub mod forge;
+pub mod format;
+pub mod git;
+pub mod glob;
+pub mod hook;
+mod http;
+mod ini;
+mod json;
+pub mod npm;
+pub mod nvchecker;
+pub mod parallel;
+pub mod path;
+pub mod pkgbuild;
+#[cfg(feature = "wasm")]
+pub mod plugin;
+pub mod release_please;
+mod rpm;
+pub mod sbom;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod self_update;
+pub mod semver;
+pub mod signature;
+pub mod template;
+pub mod token;
+mod toml;
+pub mod undo;
+pub mod upstream;
+mod watch;
+pub mod webhook;
+mod xml;
+mod yaml;
 
-use regex::{Regex, Replacer};
+use std::{
+    borrow::Cow,
+    io::{stdout, IsTerminal},
+    ops::{Range, RangeInclusive},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
 
-#[derive(Debug)]
+use regex::{RegexBuilder, Replacer};
+
+pub use error::VerincError;
+pub use regex::Regex;
+
+#[derive(Debug, Clone)]
 pub enum Position {
     All,
     Nth(u32),
+    /// The versions from the first to the last position, both included.
+    Range(RangeInclusive<u32>),
+    /// The versions in any of the ranges, sorted by their start.
+    Set(Vec<RangeInclusive<u32>>),
+    /// The version at the position counted from the end, 1 is the last one.
+    FromEnd(u32),
 }
 
-#[derive(Debug)]
+impl Position {
+    /// Parses `all`, a position (`2`), a range of them (`1-3`), a list of both (`0,2-4`) or
+    /// a position from the end (`-1` or `last`).
+    pub fn parse(s: &str) -> Option<Position> {
+        match s {
+            "all" => return Some(Position::All),
+            "last" => return Some(Position::FromEnd(1)),
+            _ => {}
+        }
+        if let Some(n) = s.strip_prefix('-') {
+            return n.parse().ok().filter(|n| *n > 0).map(Position::FromEnd);
+        }
+        if s.contains(',') {
+            let mut set = Vec::new();
+            for part in s.split(',') {
+                match Position::parse(part)? {
+                    Position::Nth(n) => set.push(n..=n),
+                    Position::Range(range) => set.push(range),
+                    _ => return None,
+                }
+            }
+            set.sort_unstable_by_key(|range| *range.start());
+            return Some(Position::Set(set));
+        }
+        match s.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+                (start <= end).then_some(Position::Range(start..=end))
+            }
+            None => s.parse().ok().map(Position::Nth),
+        }
+    }
+
+    /// Returns the position with [`Position::FromEnd`] counted from the start given the
+    /// number of versions.
+    pub fn resolve(&self, count: u32) -> Position {
+        match self {
+            Position::FromEnd(n) => match count.checked_sub(*n) {
+                Some(n) => Position::Nth(n),
+                None => Position::Set(Vec::new()),
+            },
+            position => position.clone(),
+        }
+    }
+
+    /// Returns whether the version at `idx` is selected, [`Position::FromEnd`] has to be
+    /// [resolved](Position::resolve) first.
+    pub fn contains(&self, idx: u32) -> bool {
+        match self {
+            Position::All => true,
+            Position::Nth(n) => *n == idx,
+            Position::Range(range) => range.contains(&idx),
+            Position::Set(set) => set.iter().any(|range| range.contains(&idx)),
+            Position::FromEnd(_) => false,
+        }
+    }
+
+    /// Returns the first selected position.
+    pub fn first(&self) -> u32 {
+        match self {
+            Position::All => 0,
+            Position::Nth(n) => *n,
+            Position::Range(range) => *range.start(),
+            Position::Set(set) => set.first().map_or(0, |range| *range.start()),
+            Position::FromEnd(_) => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum Version {
     Major,
     Minor,
     Patch,
+    /// The fourth component of `1.2.3.4`, see [`Components::Four`].
+    Build,
+    /// The epoch of `1:2.3.4` (added if missing unless the version follows a `v`, which
+    /// cannot go before it), the rest is reset to zeros.
+    Epoch,
+    /// The number at the end of the pre-release, `1.0.0-rc.2` becomes `1.0.0-rc.3`.
+    /// Versions without a pre-release are kept.
+    Pre,
+}
+
+/// A version which differs between two revisions of a file.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub position: u32,
+    /// Location of the old version in the old content.
+    pub span: Range<usize>,
+    pub old: String,
+    pub new: String,
+}
+
+/// Computes the next version. The built-in scheme is [`SemVer`], others can be provided
+/// by plugins.
+pub trait VersionScheme {
+    /// Returns `current` incremented according to `version` or `None` if `current` is not
+    /// a version of this scheme.
+    fn next(&self, current: &str, version: &Version) -> Option<String>;
+}
+
+/// Bumps the version of a whole file in a specific format.
+pub trait FileFormat {
+    fn bump(&self, content: &str, version: &Version) -> Result<String, String>;
+}
+
+/// The X.Y.Z scheme used by [`inc`].
+pub struct SemVer;
+
+impl VersionScheme for SemVer {
+    fn next(&self, current: &str, version: &Version) -> Option<String> {
+        next(current, version.clone()).ok()
+    }
 }
 
+/// A matched version passed to the callback of [`inc_map`].
 #[derive(Debug)]
-struct Replace {
+pub struct Match<'a> {
+    pub position: u32,
+    pub version: &'a str,
+    /// Number of the line containing the version, starting at 1.
+    pub line: usize,
+    /// The whole line containing the version.
+    pub text: &'a str,
+}
+
+type MapFn<'a> = &'a mut dyn FnMut(&Match) -> Option<String>;
+
+enum Op<'a> {
+    Inc(Version, u64),
+    Dec(Version),
+    SetComponent(Version, u64),
+    Finalize,
+    Set(String),
+    Scheme(&'a dyn VersionScheme, Version),
+    Map(MapFn<'a>),
+}
+
+struct Replace<'a> {
     idx: u32,
+    hay: &'a str,
+    selector: &'a Selector,
     position: Position,
-    version: Version,
+    op: Op<'a>,
 }
 
-impl Replace {
-    fn new(position: Position, version: Version) -> Self {
-        Replace {
-            idx: 0,
-            position,
-            version,
+/// Increments the component of `parts` given by `version` (or the last one if there are
+/// fewer) by `step` and resets the following ones. A pre-release of the version it would
+/// be bumped to is just released (which takes one step), so `1.0.0-rc.1` becomes `1.0.0`
+/// with any of them and `1.2.0-rc.1` becomes `1.2.0` with minor and patch. Returns `None`
+/// if the component would overflow.
+fn bump(version: &Version, parts: &mut [u64], step: u64, pre: bool) -> Option<()> {
+    let Some(level) = level(version) else {
+        parts.fill(0);
+        return Some(());
+    };
+    let level = level.min(parts.len() - 1);
+
+    let (head, rest) = parts.split_at_mut(level + 1);
+    let step = if pre && rest.iter().all(|part| *part == 0) {
+        step.saturating_sub(1)
+    } else {
+        step
+    };
+    if step > 0 {
+        head[level] = head[level].checked_add(step)?;
+        rest.fill(0);
+    }
+    Some(())
+}
+
+/// Returns the index of the numeric component `version` refers to, `None` for the epoch
+/// and the pre-release.
+fn level(version: &Version) -> Option<usize> {
+    match version {
+        Version::Major => Some(0),
+        Version::Minor => Some(1),
+        Version::Patch => Some(2),
+        Version::Build => Some(3),
+        Version::Epoch | Version::Pre => None,
+    }
+}
+
+/// Returns the component `version` refers to in `caps`. Unless `exact`, a missing one
+/// falls back to the last one before it.
+fn component<'h>(
+    caps: &regex::Captures<'h>,
+    version: &Version,
+    exact: bool,
+) -> Option<regex::Match<'h>> {
+    let Some(level) = level(version) else {
+        return match version {
+            Version::Epoch => caps.name("epoch"),
+            _ => None,
+        };
+    };
+    let names = ["major", "minor", "patch", "fourth"];
+    if exact {
+        return caps.name(names[level]);
+    }
+    names[..=level]
+        .iter()
+        .rev()
+        .find_map(|name| caps.name(name))
+}
+
+/// Returns the version matched by `caps` up to `end` with `part` replaced by `value`,
+/// padded with zeros to the width of `part` if `zero_pad`.
+fn with_component(
+    hay: &str,
+    caps: &regex::Captures,
+    end: usize,
+    part: regex::Match,
+    value: u64,
+    zero_pad: bool,
+) -> String {
+    let start = bare_start(caps);
+    let width = if zero_pad { part.len() } else { 0 };
+    format!(
+        "{}{value:0width$}{}",
+        &hay[start..part.start()],
+        &hay[part.end()..end]
+    )
+}
+
+/// Returns the version matched by `caps` up to `end` with the number at the end of its
+/// pre-release incremented by `step`. A missing one counts as zero, `rc` becomes `rc.1`
+/// (or `beta` after an underscore `beta1`).
+fn next_pre(hay: &str, caps: &regex::Captures, end: usize, step: u64) -> Option<String> {
+    let pre = caps.name("pre").filter(|pre| pre.start() < end)?;
+    let pre_end = pre.end().min(end);
+    let text = &hay[pre.start()..pre_end];
+    let number = text.len() - text.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+
+    let start = bare_start(caps);
+    let rest = &hay[pre_end..end];
+    if number == 0 {
+        let separator = if hay[..pre.start()].ends_with('_') {
+            ""
+        } else {
+            "."
+        };
+        return Some(format!("{}{separator}{step}{rest}", &hay[start..pre_end]));
+    }
+    let next = text[text.len() - number..]
+        .parse::<u64>()
+        .ok()?
+        .checked_add(step)?;
+    Some(format!("{}{next}{rest}", &hay[start..pre_end - number]))
+}
+
+/// Returns the version matched by `caps` up to `end` incremented by `step` according to
+/// `version`.
+fn bumped(
+    hay: &str,
+    caps: &regex::Captures,
+    end: usize,
+    version: &Version,
+    step: u64,
+    selector: &Selector,
+) -> Option<String> {
+    if let Version::Pre = version {
+        return next_pre(hay, caps, end, step);
+    }
+
+    let mut parts = Vec::with_capacity(4);
+    let mut widths = Vec::with_capacity(4);
+    for name in ["major", "minor", "patch", "fourth"] {
+        if let Some(part) = caps.name(name) {
+            parts.push(part.as_str().parse::<u64>().ok()?);
+            widths.push(if selector.zero_pad { part.len() } else { 0 });
         }
     }
+
+    let epoch = match caps.name("epoch") {
+        Some(epoch) => Some(epoch.as_str().parse::<u64>().ok()?),
+        None => None,
+    };
+    let epoch = match version {
+        Version::Epoch => Some(epoch.unwrap_or(0).checked_add(step)?),
+        _ => epoch,
+    };
+
+    let within = |m: &regex::Match| m.start() < end;
+    let pre = caps.name("pre").is_some_and(|pre| within(&pre));
+    bump(version, &mut parts, step, pre)?;
+
+    let mut new = epoch.map_or(String::new(), |epoch| format!("{epoch}:"));
+    new += &parts
+        .iter()
+        .zip(widths)
+        .map(|(part, width)| format!("{part:0width$}"))
+        .collect::<Vec<_>>()
+        .join(".");
+    if let Some(build) = caps
+        .name("build")
+        .filter(|build| within(build) && !selector.strip_build)
+    {
+        new.push('+');
+        new.push_str(&hay[build.start()..end]);
+    }
+    Some(new)
 }
 
-impl Replacer for Replace {
+impl Replacer for Replace<'_> {
     fn replace_append(&mut self, caps: &regex::Captures<'_>, dst: &mut String) {
-        let mut major = caps["major"].parse::<u32>().unwrap();
-        let mut minor = caps["minor"].parse::<u32>().unwrap();
-        let mut patch = caps["patch"].parse::<u32>().unwrap();
+        let whole = caps.get(0).unwrap();
+        let span = whole.start()..version_end(self.hay, caps);
+        // Whatever was matched past the version is kept as is.
+        let rest = &self.hay[span.end..whole.end()];
+        let old = &self.hay[span.clone()];
+        if !self.selector.accepts(self.hay, span.clone()) {
+            return dst.push_str(whole.as_str());
+        }
+        // The operations work on the version without the `v`, which is added back.
+        let v = caps.name("v");
+        let bare = &self.hay[v.map_or(span.start, |v| v.end())..span.end];
+        let prefix = match self.selector.v_prefix {
+            VPrefix::Keep => v.map_or("", |v| v.as_str()),
+            VPrefix::Add => v.map_or("v", |v| v.as_str()),
+            VPrefix::Ignore | VPrefix::Strip => "",
+        };
 
-        if matches!(self.position, Position::Nth(n) if n == self.idx)
-            || matches!(self.position, Position::All)
-        {
-            let old_major = major;
-            let old_minor = minor;
-            let old_path = patch;
-
-            match self.version {
-                Version::Major => {
-                    major += 1;
-                    minor = 0;
-                    patch = 0;
+        // An epoch cannot go after a `v` (`v2.0.0` is not `v1:0.0.0`), so none is added.
+        let v_before =
+            !prefix.is_empty() || (v.is_none() && self.hay[..span.start].ends_with(['v', 'V']));
+
+        if self.position.contains(self.idx) {
+            let new = match &mut self.op {
+                Op::Inc(Version::Epoch, _) if v_before && caps.name("epoch").is_none() => None,
+                Op::Inc(version, step) => {
+                    bumped(self.hay, caps, span.end, version, *step, self.selector)
                 }
-                Version::Minor => {
-                    minor += 1;
-                    patch = 0;
+                Op::Dec(version) => component(caps, version, false).and_then(|part| {
+                    let value = part.as_str().parse::<u64>().ok()?.saturating_sub(1);
+                    Some(with_component(
+                        self.hay,
+                        caps,
+                        span.end,
+                        part,
+                        value,
+                        self.selector.zero_pad,
+                    ))
+                }),
+                Op::SetComponent(version, value) => component(caps, version, true).map(|part| {
+                    with_component(
+                        self.hay,
+                        caps,
+                        span.end,
+                        part,
+                        *value,
+                        self.selector.zero_pad,
+                    )
+                }),
+                Op::Finalize => caps
+                    .name("pre")
+                    .filter(|pre| pre.start() < span.end)
+                    .map(|pre| {
+                        // Without the separator in front of it.
+                        let pre_end = pre.end().min(span.end);
+                        format!(
+                            "{}{}",
+                            &self.hay[bare_start(caps)..pre.start() - 1],
+                            &self.hay[pre_end..span.end]
+                        )
+                    }),
+                Op::Set(new) => {
+                    let new = match self.selector.v_prefix {
+                        VPrefix::Ignore => new,
+                        _ => new.strip_prefix(['v', 'V']).unwrap_or(new),
+                    };
+                    // The epoch of `1:2.3.4` stays unless the new version has one.
+                    Some(match caps.name("epoch") {
+                        Some(epoch) if !new.contains(':') => format!("{}:{new}", epoch.as_str()),
+                        _ => new.to_string(),
+                    })
                 }
-                Version::Patch => patch += 1,
-            }
+                Op::Scheme(scheme, version) => scheme.next(bare, version),
+                Op::Map(f) => {
+                    let hay = self.hay;
+                    let start = span.start;
+                    let line_start = hay[..start].rfind('\n').map_or(0, |idx| idx + 1);
+                    let line_end = hay[start..].find('\n').map_or(hay.len(), |idx| start + idx);
+
+                    f(&Match {
+                        position: self.idx,
+                        version: bare,
+                        line: hay[..start].matches('\n').count() + 1,
+                        text: &hay[line_start..line_end],
+                    })
+                }
+            };
 
-            if stdout().is_terminal() {
-                println!("{old_major}.{old_minor}.{old_path} -> {major}.{minor}.{patch}");
+            match new {
+                Some(new) => {
+                    print_change(old, &format!("{prefix}{new}"));
+                    dst.push_str(prefix);
+                    dst.push_str(&new);
+                }
+                None => dst.push_str(old),
             }
+        } else {
+            dst.push_str(old);
         }
+        dst.push_str(rest);
 
         self.idx += 1;
+    }
+}
 
-        dst.push_str(&format!("{}.{}.{}", major, minor, patch));
+const MAJOR_MINOR: &str = r"(?:\b(?<epoch>\d+):)?(?<major>0|[1-9]\d*)\.(?<minor>0|[1-9]\d*)";
+const PATCH: &str = r"\.(?<patch>0|[1-9]\d*)";
+const FOURTH: &str = r"\.(?<fourth>0|[1-9]\d*)";
+const SUFFIX: &str = r"(?:[-_](?<pre>[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*))?(?:\+(?<build>[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*))?";
+
+/// How many numeric components the versions have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Components {
+    /// `MAJOR.MINOR`, [`Version::Patch`] increments the minor version.
+    Two,
+    /// `MAJOR.MINOR.PATCH`, the default.
+    Three,
+    /// `MAJOR.MINOR.PATCH.BUILD` as used on Windows, incremented by [`Version::Build`].
+    /// Dotted quads are not skipped as IP addresses then.
+    Four,
+}
+
+/// What happens to the `v` in front of versions like `v1.2.3`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VPrefix {
+    /// The `v` is not a part of the version and stays, the default.
+    Ignore,
+    /// The `v` is a part of the version and stays.
+    Keep,
+    /// The `v` is a part of the version and is added to the versions which are written.
+    Add,
+    /// The `v` is a part of the version and is removed from the versions which are
+    /// written.
+    Strip,
+}
+
+/// Returns the pattern of versions, `zero_pad` also matches components with leading
+/// zeros (`1.09.0`).
+fn pattern(components: Components, v_prefix: VPrefix, zero_pad: bool) -> String {
+    let v = match v_prefix {
+        VPrefix::Ignore => "",
+        _ => r"(?:\b(?<v>[vV]))?",
+    };
+    let pattern = match components {
+        Components::Two => format!("{v}{MAJOR_MINOR}{SUFFIX}"),
+        Components::Three => format!("{v}{MAJOR_MINOR}{PATCH}{SUFFIX}"),
+        Components::Four => format!("{v}{MAJOR_MINOR}{PATCH}{FOURTH}{SUFFIX}"),
+    };
+    if zero_pad {
+        pattern.replace(r"0|[1-9]\d*", r"\d+")
+    } else {
+        pattern
     }
 }
 
-const REGEX: &str = r"(?<major>0|[1-9]\d*)\.(?<minor>0|[1-9]\d*)\.(?<patch>0|[1-9]\d*)";
+/// Identifiers which end a pre-release or build metadata because they are file
+/// extensions, as in `foo-1.0.0-rc.1.tar.gz`.
+const EXTENSIONS: &[&str] = &[
+    "7z", "apk", "appimage", "bz2", "deb", "dmg", "exe", "gz", "jar", "msi", "rpm", "tar", "tgz",
+    "txt", "whl", "xz", "zip", "zst",
+];
 
-/// Finds a version in `hay` at `position` and increments one of its components according
-/// to `version`.
-pub fn inc(hay: &str, position: Position, version: Version) -> String {
-    Regex::new(REGEX)
+/// The pre-releases which may follow an underscore, as in `1.2.3_beta3`.
+const PRE_RELEASE: &[&str] = &["alpha", "beta", "dev", "pre", "rc"];
+
+/// Identifiers which are a part of a file name rather than a pre-release, as in
+/// `foo-1.0.0-linux-amd64.tar.gz`.
+const NOT_PRE_RELEASE: &[&str] = &[
+    "aarch64", "amd64", "arm", "arm64", "armv7", "bin", "darwin", "freebsd", "i386", "i686",
+    "linux", "macos", "musl", "src", "win32", "win64", "windows", "x64", "x86",
+];
+
+/// Returns where the version matched by `caps` ends in `hay`. The pre-release and the
+/// build metadata are left out if they look like a part of a file name instead.
+fn version_end(hay: &str, caps: &regex::Captures) -> usize {
+    let mut end = core_end(caps);
+    if let Some(pre) = caps.name("pre") {
+        let first = pre.as_str().split(['.', '-']).next().unwrap_or("");
+        let first = first.to_ascii_lowercase();
+        // 1.2.3_beta3 is common but so are 1.2.3_amd64 or VERSION_1_2_3_FOO.
+        if NOT_PRE_RELEASE.contains(&first.as_str())
+            || hay[..pre.start()].ends_with('_')
+                && !PRE_RELEASE.iter().any(|word| first.starts_with(word))
+        {
+            return end;
+        }
+        match identifiers_end(hay, pre) {
+            Some(pre_end) if pre_end == pre.end() => end = pre_end,
+            // The build metadata would have to follow right after.
+            Some(pre_end) => return pre_end,
+            None => return end,
+        }
+    }
+    if let Some(build) = caps.name("build") {
+        end = identifiers_end(hay, build).unwrap_or(end);
+    }
+    end
+}
+
+/// Returns where the version matched by `caps` starts, after the `v`.
+fn bare_start(caps: &regex::Captures) -> usize {
+    caps.name("epoch")
+        .or_else(|| caps.name("major"))
+        .unwrap()
+        .start()
+}
+
+/// Returns where the numeric components matched by `caps` end.
+fn core_end(caps: &regex::Captures) -> usize {
+    caps.name("fourth")
+        .or_else(|| caps.name("patch"))
+        .or_else(|| caps.name("minor"))
         .unwrap()
-        .replace_all(hay, Replace::new(position, version))
-        .to_string()
+        .end()
+}
+
+/// Returns where the identifiers of `part` end without the trailing file extensions, or
+/// `None` if nothing is left or they are cut off by an underscore (`x86_64`).
+fn identifiers_end(hay: &str, part: regex::Match) -> Option<usize> {
+    if hay[part.end()..].starts_with('_') {
+        return None;
+    }
+    let mut ids: Vec<_> = part.as_str().split('.').collect();
+    while ids
+        .last()
+        .is_some_and(|id| EXTENSIONS.contains(&id.to_ascii_lowercase().as_str()))
+    {
+        ids.pop();
+    }
+    (!ids.is_empty()).then(|| part.start() + ids.join(".").len())
+}
+
+/// A version found in a text.
+pub(crate) struct Found<'h> {
+    hay: &'h str,
+    span: Range<usize>,
+}
+
+impl<'h> Found<'h> {
+    pub fn start(&self) -> usize {
+        self.span.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.span.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    pub fn as_str(&self) -> &'h str {
+        &self.hay[self.span.clone()]
+    }
+}
+
+fn regex() -> &'static Regex {
+    static REGEX_CELL: OnceLock<Regex> = OnceLock::new();
+    REGEX_CELL
+        .get_or_init(|| Regex::new(&pattern(Components::Three, VPrefix::Ignore, false)).unwrap())
+}
+
+/// Bounds the memory and time spent on a single text, for running on untrusted input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limits {
+    /// Maximum size of the compiled regex in bytes, see [`RegexBuilder::size_limit`].
+    pub regex_size: usize,
+    /// Maximum size of the cache of the lazy DFA in bytes, see
+    /// [`RegexBuilder::dfa_size_limit`].
+    pub dfa_size: usize,
+    /// Maximum length of a text in bytes. Longer texts are left alone.
+    pub max_input: Option<usize>,
+}
+
+impl Default for Limits {
+    /// The defaults of the regex crate, no limit on the input.
+    fn default() -> Self {
+        Limits {
+            regex_size: 10 * (1 << 20),
+            dfa_size: 2 * (1 << 20),
+            max_input: None,
+        }
+    }
+}
+
+/// A cheap check which rules out texts without any `<digit>.<digit>`, which is most of
+/// them when going through whole trees, before running the regex.
+fn may_contain_version(hay: &str) -> bool {
+    let bytes = hay.as_bytes();
+    memchr::memchr_iter(b'.', bytes).any(|idx| {
+        idx > 0
+            && bytes[idx - 1].is_ascii_digit()
+            && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit)
+    })
+}
+
+/// Decides which of the X.Y.Z found in a text are considered to be versions. All of the
+/// functions of the crate use [`Selector::default`], the methods allow to change that.
+#[derive(Debug, Clone)]
+pub struct Selector {
+    /// Skips dotted quads like `192.168.1.10`, which would be bumped to `192.168.2.10`
+    /// otherwise. On by default.
+    pub skip_ips: bool,
+    /// Skips versions inside of `http://` and `https://` URLs.
+    pub skip_urls: bool,
+    /// Only considers the versions on these lines (starting at 1).
+    pub lines: Option<RangeInclusive<usize>>,
+    /// Only considers the versions on lines matching this regex (`^pkgver=`).
+    pub line_match: Option<Regex>,
+    /// Skips the versions on lines matching this regex (`sha256sums`).
+    pub line_exclude: Option<Regex>,
+    /// How many components the versions have. With two or four, the versions with more
+    /// of them are skipped instead of matching their beginning.
+    pub components: Components,
+    /// Whether the `v` of `v1.2.3` is a part of the version, see [`VPrefix`].
+    pub v_prefix: VPrefix,
+    /// Also matches components with leading zeros and keeps their width when they change
+    /// (`1.09.0` to `1.10.0`, `1.002.0` to `1.003.0`).
+    pub zero_pad: bool,
+    /// Drops the build metadata (`1.0.0+20240101`) of incremented versions instead of
+    /// keeping it.
+    pub strip_build: bool,
+    /// Bounds the work done per text, see [`Limits`].
+    pub limits: Limits,
+}
+
+impl Default for Selector {
+    fn default() -> Self {
+        Selector {
+            skip_ips: true,
+            skip_urls: false,
+            lines: None,
+            line_match: None,
+            line_exclude: None,
+            components: Components::Three,
+            v_prefix: VPrefix::Ignore,
+            zero_pad: false,
+            strip_build: false,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl Selector {
+    /// Returns an error if `hay` is over the input limit or if the regex doesn't fit into
+    /// the regex limits. The bumps fail the same way, the other methods treat such texts
+    /// as if they had no versions.
+    pub fn check(&self, hay: &str) -> Result<(), VerincError> {
+        self.limited(hay)?;
+        self.regex().map(|_| ())
+    }
+
+    fn limited(&self, hay: &str) -> Result<(), VerincError> {
+        match self.limits.max_input {
+            Some(max) if hay.len() > max => Err(VerincError::InputTooLarge {
+                len: hay.len(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the shared regex or compiles one if the limits or the matching options
+    /// differ from the defaults.
+    fn regex(&self) -> Result<Cow<'static, Regex>, VerincError> {
+        let defaults = Limits::default();
+        if self.limits.regex_size == defaults.regex_size
+            && self.limits.dfa_size == defaults.dfa_size
+            && self.components == Components::Three
+            && self.v_prefix == VPrefix::Ignore
+            && !self.zero_pad
+        {
+            return Ok(Cow::Borrowed(regex()));
+        }
+
+        RegexBuilder::new(&pattern(self.components, self.v_prefix, self.zero_pad))
+            .size_limit(self.limits.regex_size)
+            .dfa_size_limit(self.limits.dfa_size)
+            .build()
+            .map(Cow::Owned)
+            .map_err(VerincError::from)
+    }
+
+    /// Returns the regex if `hay` is within the limits and may contain a version.
+    fn regex_for(&self, hay: &str) -> Option<Cow<'static, Regex>> {
+        if self.limits.max_input.is_some_and(|max| hay.len() > max) || !may_contain_version(hay) {
+            return None;
+        }
+        self.regex().ok()
+    }
+
+    /// Returns true if the match at `span` of `hay` is considered a version.
+    pub fn accepts(&self, hay: &str, span: Range<usize>) -> bool {
+        self.skip_reason(hay, span).is_none()
+    }
+
+    /// Returns why the match at `span` of `hay` is not considered a version, if it isn't.
+    pub fn skip_reason(&self, hay: &str, span: Range<usize>) -> Option<&'static str> {
+        if self.skip_ips && self.components != Components::Four && is_ip(hay, span.clone()) {
+            Some("a part of an IP address")
+        } else if self.skip_urls && in_url(hay, span.clone()) {
+            Some("inside of a URL")
+        } else if self.components != Components::Three && in_longer_version(hay, span.clone()) {
+            Some("a part of a longer version")
+        } else if self
+            .lines
+            .as_ref()
+            .is_some_and(|lines| !lines.contains(&line_number(hay, span.start)))
+        {
+            Some("not on the selected lines")
+        } else if self
+            .line_match
+            .as_ref()
+            .is_some_and(|regex| !regex.is_match(line_at(hay, span.start)))
+        {
+            Some("on a line not matching the pattern")
+        } else if self
+            .line_exclude
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(line_at(hay, span.start)))
+        {
+            Some("on an excluded line")
+        } else {
+            None
+        }
+    }
+
+    /// Returns the matches in `hay` which are not considered versions along with the
+    /// reason, see [`Selector::skip_reason`].
+    pub fn skipped(&self, hay: &str) -> Vec<(Range<usize>, &'static str)> {
+        let Some(regex) = self.regex_for(hay) else {
+            return Vec::new();
+        };
+        regex
+            .captures_iter(hay)
+            .filter_map(|caps| {
+                let span = caps.get(0).unwrap().start()..version_end(hay, &caps);
+                Some((span.clone(), self.skip_reason(hay, span)?))
+            })
+            .collect()
+    }
+
+    /// Returns the accepted matches in `hay`.
+    pub(crate) fn find<'h>(&self, hay: &'h str) -> Vec<Found<'h>> {
+        let Some(regex) = self.regex_for(hay) else {
+            return Vec::new();
+        };
+        regex
+            .captures_iter(hay)
+            .map(|caps| Found {
+                hay,
+                span: caps.get(0).unwrap().start()..version_end(hay, &caps),
+            })
+            .filter(|found| self.accepts(hay, found.range()))
+            .collect()
+    }
+
+    fn replace<'a>(
+        &'a self,
+        hay: &'a str,
+        position: Position,
+        op: Op<'a>,
+    ) -> Result<String, VerincError> {
+        self.limited(hay)?;
+        let regex = self.regex()?;
+        if !may_contain_version(hay) {
+            return Ok(hay.to_string());
+        }
+        let position = match position {
+            Position::FromEnd(_) => position.resolve(self.find(hay).len() as u32),
+            position => position,
+        };
+        let replace = Replace {
+            idx: 0,
+            hay,
+            selector: self,
+            position,
+            op,
+        };
+        Ok(regex.replace_all(hay, replace).to_string())
+    }
+
+    /// See [`inc`].
+    pub fn inc(
+        &self,
+        hay: &str,
+        position: Position,
+        version: Version,
+        step: u64,
+    ) -> Result<String, VerincError> {
+        self.replace(hay, position, Op::Inc(version, step))
+    }
+
+    /// See [`dec`].
+    pub fn dec(
+        &self,
+        hay: &str,
+        position: Position,
+        version: Version,
+    ) -> Result<String, VerincError> {
+        self.replace(hay, position, Op::Dec(version))
+    }
+
+    /// See [`inc_with`].
+    pub fn inc_with(
+        &self,
+        hay: &str,
+        position: Position,
+        version: Version,
+        scheme: &dyn VersionScheme,
+    ) -> Result<String, VerincError> {
+        self.replace(hay, position, Op::Scheme(scheme, version))
+    }
+
+    /// See [`inc_map`].
+    pub fn inc_map(&self, hay: &str, position: Position, f: MapFn) -> Result<String, VerincError> {
+        self.replace(hay, position, Op::Map(f))
+    }
+
+    /// See [`inc_with_regex`].
+    pub fn inc_with_regex(
+        &self,
+        hay: &str,
+        regex: &Regex,
+        position: Position,
+        version: Version,
+        step: u64,
+    ) -> Result<String, VerincError> {
+        if level(&version).is_none() {
+            return Err(VerincError::CannotBump(
+                "only the components can be bumped with a custom regex".to_string(),
+            ));
+        }
+        if !regex.capture_names().any(|name| name == Some("major")) {
+            return Err(VerincError::Regex(
+                "the regex has no major group".to_string(),
+            ));
+        }
+        self.limited(hay)?;
+
+        let names = ["major", "minor", "patch", "fourth"];
+        let found: Vec<_> = regex
+            .captures_iter(hay)
+            .filter_map(|caps| {
+                let groups: Vec<_> = names.iter().map_while(|name| caps.name(name)).collect();
+                let span = groups.first()?.start()..groups.last()?.end();
+                self.accepts(hay, span).then_some(groups)
+            })
+            .collect();
+        let position = position.resolve(found.len() as u32);
+
+        let mut out = String::with_capacity(hay.len());
+        let mut last = 0;
+        for (idx, groups) in found.iter().enumerate() {
+            if !position.contains(idx as u32) {
+                continue;
+            }
+            let mut parts = groups
+                .iter()
+                .map(|group| group.as_str().parse::<u64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| VerincError::InvalidVersion(groups[0].as_str().to_string()))?;
+            if bump(&version, &mut parts, step, false).is_none() {
+                continue;
+            }
+            let (start, end) = (groups[0].start(), groups[groups.len() - 1].end());
+            let mut new = String::new();
+            let mut between = start;
+            for (group, part) in groups.iter().zip(parts) {
+                let width = if self.zero_pad { group.len() } else { 0 };
+                new.push_str(&hay[between..group.start()]);
+                new.push_str(&format!("{part:0width$}"));
+                between = group.end();
+            }
+            print_change(&hay[start..end], &new);
+            out.push_str(&hay[last..start]);
+            out.push_str(&new);
+            last = end;
+        }
+        out.push_str(&hay[last..]);
+        Ok(out)
+    }
+
+    /// See [`next`].
+    pub fn next(&self, current: &str, version: Version) -> Result<String, VerincError> {
+        let pattern = pattern(self.components, VPrefix::Ignore, self.zero_pad);
+        let caps = Regex::new(&format!("^{pattern}$"))?
+            .captures(current)
+            .ok_or_else(|| VerincError::InvalidVersion(current.to_string()))?;
+        bumped(current, &caps, current.len(), &version, 1, self).ok_or_else(|| {
+            VerincError::CannotBump(format!("cannot bump the {version:?} of '{current}'"))
+        })
+    }
+
+    /// See [`set_component`].
+    pub fn set_component(
+        &self,
+        hay: &str,
+        position: Position,
+        version: Version,
+        value: u64,
+    ) -> Result<String, VerincError> {
+        self.replace(hay, position, Op::SetComponent(version, value))
+    }
+
+    /// See [`finalize`].
+    pub fn finalize(&self, hay: &str, position: Position) -> Result<String, VerincError> {
+        self.replace(hay, position, Op::Finalize)
+    }
+
+    /// See [`set`].
+    pub fn set(&self, hay: &str, position: Position, new: &str) -> Result<String, VerincError> {
+        self.replace(hay, position, Op::Set(new.to_string()))
+    }
+
+    /// See [`changes`].
+    pub fn changes(&self, old: &str, new: &str) -> Vec<Change> {
+        self.rewritten(old, new).unwrap_or_else(|| {
+            self.find(old)
+                .into_iter()
+                .zip(self.find(new))
+                .enumerate()
+                .filter(|(_, (old, new))| old.as_str() != new.as_str())
+                .map(|(idx, (old, new))| Change {
+                    position: idx as u32,
+                    span: old.range(),
+                    old: old.as_str().to_string(),
+                    new: new.as_str().to_string(),
+                })
+                .collect()
+        })
+    }
+
+    /// Returns what replaced each of the versions of `old` in `new`. The bumps rewrite
+    /// just the versions, so the text around them is in `new` too, whatever the new
+    /// versions are. `None` if something else changed as well.
+    fn rewritten(&self, old: &str, new: &str) -> Option<Vec<Change>> {
+        let spans = self.spans(old);
+        let mut changes = Vec::new();
+        // Where the text before the next version starts in `old` and in `new`.
+        let (mut from, mut cursor) = (0, 0);
+        for (idx, span) in spans.iter().enumerate() {
+            let rest = new[cursor..].strip_prefix(&old[from..span.start])?;
+            cursor = new.len() - rest.len();
+            let end = match spans.get(idx + 1) {
+                Some(next) => {
+                    let after = &old[span.end..next.start];
+                    cursor + rest.find(after).filter(|_| !after.is_empty())?
+                }
+                None => cursor + rest.strip_suffix(&old[span.end..])?.len(),
+            };
+            if old[span.clone()] != new[cursor..end] {
+                changes.push(Change {
+                    position: idx as u32,
+                    span: span.clone(),
+                    old: old[span.clone()].to_string(),
+                    new: new[cursor..end].to_string(),
+                });
+            }
+            (from, cursor) = (span.end, end);
+        }
+        Some(changes)
+    }
+
+    /// See [`list_versions`].
+    pub fn list_versions<'h>(&self, hay: &'h str) -> Vec<&'h str> {
+        self.find(hay).iter().map(|m| m.as_str()).collect()
+    }
+
+    /// Returns where the versions listed by [`Selector::list_versions`] are in `hay`.
+    pub fn spans(&self, hay: &str) -> Vec<Range<usize>> {
+        self.find(hay).iter().map(Found::range).collect()
+    }
+}
+
+/// Returns the number of the line of `hay` containing `offset`, starting at 1.
+fn line_number(hay: &str, offset: usize) -> usize {
+    hay.as_bytes()[..offset]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
+}
+
+/// Returns the line of `hay` containing `offset`, without the line break.
+fn line_at(hay: &str, offset: usize) -> &str {
+    let start = hay[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let end = hay[offset..]
+        .find('\n')
+        .map_or(hay.len(), |idx| offset + idx);
+    hay[start..end].trim_end_matches('\r')
+}
+
+/// Returns true if the match at `span` is a part of a dotted quad like `192.168.1.10`.
+fn is_ip(hay: &str, span: Range<usize>) -> bool {
+    let bytes = hay.as_bytes();
+    let digit = |idx: usize| bytes.get(idx).is_some_and(u8::is_ascii_digit);
+
+    let mut start = span.start;
+    while start >= 2 && bytes[start - 1] == b'.' && digit(start - 2) {
+        start -= 2;
+        while start > 0 && digit(start - 1) {
+            start -= 1;
+        }
+    }
+    let mut end = span.end;
+    while bytes.get(end) == Some(&b'.') && digit(end + 1) {
+        end += 1;
+        while digit(end) {
+            end += 1;
+        }
+    }
+
+    let parts: Vec<_> = hay[start..end].split('.').collect();
+    parts.len() == 4 && parts.iter().all(|part| part.parse::<u8>().is_ok())
+}
+
+/// Returns true if the match at `span` is a part of a version with more components, like
+/// `1.2` of `1.2.3`.
+fn in_longer_version(hay: &str, span: Range<usize>) -> bool {
+    let bytes = hay.as_bytes();
+    let digit = |idx: usize| bytes.get(idx).is_some_and(u8::is_ascii_digit);
+    span.start >= 2 && bytes[span.start - 1] == b'.' && digit(span.start - 2)
+        || bytes.get(span.end) == Some(&b'.') && digit(span.end + 1)
+}
+
+/// Returns true if the match at `span` lies inside of an HTTP(S) URL.
+fn in_url(hay: &str, span: Range<usize>) -> bool {
+    let before = &hay[..span.start];
+    let token = before
+        .rfind(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '<' | '>' | '(' | ')'))
+        .map_or(before, |idx| &before[idx + 1..]);
+    token.contains("http://") || token.contains("https://")
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Prints `old -> new` unless [quiet](set_quiet) or stdout is not a terminal.
+fn print_change(old: &str, new: &str) {
+    if !QUIET.load(Ordering::Relaxed) && stdout().is_terminal() {
+        println!("{old} -> {new}");
+    }
+}
+
+/// Stops printing `old -> new` for every replaced version when stdout is a terminal.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Makes everything which would use the network (upstream queries, downloads, webhooks,
+/// pushes and pull requests) fail right away instead. Off by default.
+pub fn set_offline(offline: bool) {
+    http::set_offline(offline);
+}
+
+/// Sends all requests (and pushes) through `proxy` instead of the one given by
+/// `HTTP_PROXY`/`HTTPS_PROXY`. `NO_PROXY` is respected either way.
+pub fn set_proxy(proxy: Option<&str>) {
+    http::set_proxy(proxy);
+}
+
+/// Returns how many HTTP requests were made (retries included) and how many bytes were
+/// received by them so far.
+pub fn network_stats() -> (u64, u64) {
+    http::stats()
+}
+
+/// Finds a version in `hay` at `position` and increments one of its components according
+/// to `version` by `step` (usually 1). Fails if `hay` is over the [`Limits`].
+pub fn inc(
+    hay: &str,
+    position: Position,
+    version: Version,
+    step: u64,
+) -> Result<String, VerincError> {
+    Selector::default().inc(hay, position, version, step)
+}
+
+/// Finds a version in `hay` at `position` and decrements one of its components according
+/// to `version`, down to zero. The other components are left alone, so only a patch bump
+/// is undone completely.
+pub fn dec(hay: &str, position: Position, version: Version) -> Result<String, VerincError> {
+    Selector::default().dec(hay, position, version)
+}
+
+/// Like [`inc`] but the new version is computed by `scheme`.
+pub fn inc_with(
+    hay: &str,
+    position: Position,
+    version: Version,
+    scheme: &dyn VersionScheme,
+) -> Result<String, VerincError> {
+    Selector::default().inc_with(hay, position, version, scheme)
+}
+
+/// Calls `f` for every version in `hay` at `position` and replaces it with the returned
+/// value. Versions for which `f` returns `None` are kept.
+pub fn inc_map(hay: &str, position: Position, f: MapFn) -> Result<String, VerincError> {
+    Selector::default().inc_map(hay, position, f)
+}
+
+/// Like [`inc`] but the versions are the matches of `regex`, which needs a `major` group
+/// and may have `minor`, `patch` and `fourth` ones. Only those groups are replaced, the
+/// rest of each match is kept. Fails if there is no `major` group or `version` is not a
+/// component.
+pub fn inc_with_regex(
+    hay: &str,
+    regex: &Regex,
+    position: Position,
+    version: Version,
+    step: u64,
+) -> Result<String, VerincError> {
+    Selector::default().inc_with_regex(hay, regex, position, version, step)
+}
+
+/// Finds a version in `hay` at `position` and replaces it with `new`.
+pub fn set(hay: &str, position: Position, new: &str) -> Result<String, VerincError> {
+    Selector::default().set(hay, position, new)
+}
+
+/// Finds a version in `hay` at `position` and sets the component given by `version` to
+/// `value`, leaving the others alone. Versions without the component are kept.
+pub fn set_component(
+    hay: &str,
+    position: Position,
+    version: Version,
+    value: u64,
+) -> Result<String, VerincError> {
+    Selector::default().set_component(hay, position, version, value)
+}
+
+/// Finds a version in `hay` at `position` and drops its pre-release, `1.3.0-rc.4` becomes
+/// `1.3.0`. The build metadata is kept.
+pub fn finalize(hay: &str, position: Position) -> Result<String, VerincError> {
+    Selector::default().finalize(hay, position)
+}
+
+/// Returns `current` incremented according to `version`. Fails if `current` is not a
+/// version or cannot be bumped that way.
+pub fn next(current: &str, version: Version) -> Result<String, VerincError> {
+    Selector::default().next(current, version)
+}
+
+/// Compares the versions found in `old` with what replaced them in `new` (e.g. the result
+/// of [`inc`]) and returns those which differ.
+pub fn changes(old: &str, new: &str) -> Vec<Change> {
+    Selector::default().changes(old, new)
 }
 
 /// Returns a list of all recognized versions in `hay`.
 pub fn list_versions(hay: &str) -> Vec<&str> {
-    Regex::new(REGEX)
-        .unwrap()
-        .find_iter(hay)
-        .map(|m| m.as_str())
-        .collect()
+    Selector::default().list_versions(hay)
 }
 
 #[cfg(test)]
@@ -100,87 +1245,117 @@ mod tests {
     #[test]
     fn no_versions() {
         assert_eq!(
-            inc("foo bar baz", Position::Nth(1), Version::Patch),
+            inc("foo bar baz", Position::Nth(1), Version::Patch, 1).unwrap(),
             "foo bar baz"
         );
     }
 
     #[test]
     fn patch() {
-        assert_eq!(inc("1.0.0", Position::Nth(0), Version::Patch), "1.0.1");
-        assert_eq!(inc("1.0.0", Position::All, Version::Patch), "1.0.1");
+        assert_eq!(
+            inc("1.0.0", Position::Nth(0), Version::Patch, 1).unwrap(),
+            "1.0.1"
+        );
+        assert_eq!(
+            inc("1.0.0", Position::All, Version::Patch, 1).unwrap(),
+            "1.0.1"
+        );
 
         assert_eq!(
-            inc("1.0.0 foo 1.0.0", Position::Nth(0), Version::Patch),
+            inc("1.0.0 foo 1.0.0", Position::Nth(0), Version::Patch, 1).unwrap(),
             "1.0.1 foo 1.0.0"
         );
         assert_eq!(
-            inc("1.0.0 1.0.0", Position::All, Version::Patch),
+            inc("1.0.0 1.0.0", Position::All, Version::Patch, 1).unwrap(),
             "1.0.1 1.0.1"
         );
 
         assert_eq!(
-            inc("1.0.0 1.0.0", Position::Nth(1), Version::Patch),
+            inc("1.0.0 1.0.0", Position::Nth(1), Version::Patch, 1).unwrap(),
             "1.0.0 1.0.1"
         );
     }
 
     #[test]
     fn minor() {
-        assert_eq!(inc("1.0.0", Position::Nth(0), Version::Minor), "1.1.0");
-        assert_eq!(inc("1.0.1", Position::Nth(0), Version::Minor), "1.1.0");
-        assert_eq!(inc("1.0.0", Position::All, Version::Minor), "1.1.0");
-        assert_eq!(inc("1.0.1", Position::All, Version::Minor), "1.1.0");
-
         assert_eq!(
-            inc("1.0.0 1.0.0", Position::Nth(0), Version::Minor),
-            "1.1.0 1.0.0"
+            inc("1.0.0", Position::Nth(0), Version::Minor, 1).unwrap(),
+            "1.1.0"
         );
         assert_eq!(
-            inc("1.0.0 1.0.0", Position::All, Version::Minor),
-            "1.1.0 1.1.0"
+            inc("1.0.1", Position::Nth(0), Version::Minor, 1).unwrap(),
+            "1.1.0"
         );
         assert_eq!(
-            inc("1.0.1 1.0.2", Position::Nth(0), Version::Minor),
-            "1.1.0 1.0.2"
+            inc("1.0.0", Position::All, Version::Minor, 1).unwrap(),
+            "1.1.0"
         );
         assert_eq!(
-            inc("1.0.2 1.0.1", Position::All, Version::Minor),
-            "1.1.0 1.1.0"
+            inc("1.0.1", Position::All, Version::Minor, 1).unwrap(),
+            "1.1.0"
         );
 
         assert_eq!(
-            inc("1.0.0 1.2.1", Position::Nth(1), Version::Minor),
+            inc("1.0.0 1.0.0", Position::Nth(0), Version::Minor, 1).unwrap(),
+            "1.1.0 1.0.0"
+        );
+        assert_eq!(
+            inc("1.0.0 1.0.0", Position::All, Version::Minor, 1).unwrap(),
+            "1.1.0 1.1.0"
+        );
+        assert_eq!(
+            inc("1.0.1 1.0.2", Position::Nth(0), Version::Minor, 1).unwrap(),
+            "1.1.0 1.0.2"
+        );
+        assert_eq!(
+            inc("1.0.2 1.0.1", Position::All, Version::Minor, 1).unwrap(),
+            "1.1.0 1.1.0"
+        );
+
+        assert_eq!(
+            inc("1.0.0 1.2.1", Position::Nth(1), Version::Minor, 1).unwrap(),
             "1.0.0 1.3.0"
         );
     }
 
     #[test]
     fn major() {
-        assert_eq!(inc("1.0.0", Position::Nth(0), Version::Major), "2.0.0");
-        assert_eq!(inc("1.0.1", Position::Nth(0), Version::Major), "2.0.0");
-        assert_eq!(inc("1.0.0", Position::All, Version::Major), "2.0.0");
-        assert_eq!(inc("1.0.1", Position::All, Version::Major), "2.0.0");
+        assert_eq!(
+            inc("1.0.0", Position::Nth(0), Version::Major, 1).unwrap(),
+            "2.0.0"
+        );
+        assert_eq!(
+            inc("1.0.1", Position::Nth(0), Version::Major, 1).unwrap(),
+            "2.0.0"
+        );
+        assert_eq!(
+            inc("1.0.0", Position::All, Version::Major, 1).unwrap(),
+            "2.0.0"
+        );
+        assert_eq!(
+            inc("1.0.1", Position::All, Version::Major, 1).unwrap(),
+            "2.0.0"
+        );
 
         assert_eq!(
-            inc("1.0.0 1.0.0", Position::Nth(0), Version::Major),
+            inc("1.0.0 1.0.0", Position::Nth(0), Version::Major, 1).unwrap(),
             "2.0.0 1.0.0"
         );
         assert_eq!(
-            inc("1.0.0 1.0.0", Position::All, Version::Major),
+            inc("1.0.0 1.0.0", Position::All, Version::Major, 1).unwrap(),
             "2.0.0 2.0.0"
         );
         assert_eq!(
-            inc("3.0.1 1.0.2", Position::Nth(0), Version::Major),
+            inc("3.0.1 1.0.2", Position::Nth(0), Version::Major, 1).unwrap(),
             "4.0.0 1.0.2"
         );
         assert_eq!(
-            inc("3.0.2 1.0.1", Position::All, Version::Major),
+            inc("3.0.2 1.0.1", Position::All, Version::Major, 1).unwrap(),
             "4.0.0 2.0.0"
         );
 
         assert_eq!(
-            inc("1.0.0 1.2.1", Position::Nth(1), Version::Major),
+            inc("1.0.0 1.2.1", Position::Nth(1), Version::Major, 1).unwrap(),
             "1.0.0 2.0.0"
         );
     }
@@ -188,7 +1363,7 @@ mod tests {
     #[test]
     fn leading_zeros() {
         assert_eq!(
-            inc("1.01.0 12.13.14", Position::Nth(0), Version::Major),
+            inc("1.01.0 12.13.14", Position::Nth(0), Version::Major, 1).unwrap(),
             "1.01.0 13.0.0"
         );
     }
@@ -199,9 +1374,739 @@ mod tests {
             inc(
                 "1.1.0\nhello\nworld\n12.13.14",
                 Position::Nth(1),
-                Version::Minor
-            ),
+                Version::Minor,
+                1
+            )
+            .unwrap(),
             "1.1.0\nhello\nworld\n12.14.0"
         );
     }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            super::set("1.0.0 1.0.0", Position::Nth(1), "2.4.0").unwrap(),
+            "1.0.0 2.4.0"
+        );
+        assert_eq!(
+            super::set("1.0.0 foo 1.2.0", Position::All, "2.4.0").unwrap(),
+            "2.4.0 foo 2.4.0"
+        );
+    }
+
+    #[test]
+    fn next() {
+        assert_eq!(super::next("1.2.3", Version::Patch).unwrap(), "1.2.4");
+        assert_eq!(super::next("1.2.3", Version::Minor).unwrap(), "1.3.0");
+        assert_eq!(super::next("1.2.3", Version::Major).unwrap(), "2.0.0");
+        assert_eq!(
+            super::next("v1.2.3", Version::Major),
+            Err(VerincError::InvalidVersion("v1.2.3".to_string()))
+        );
+        assert!(super::next("1.2", Version::Major).is_err());
+        assert!(matches!(
+            super::next("1.2.3", Version::Pre),
+            Err(VerincError::CannotBump(_))
+        ));
+    }
+
+    #[test]
+    fn changes() {
+        let old = "foo 1.0.0 bar 2.0.0";
+        let new = inc(old, Position::Nth(1), Version::Minor, 1).unwrap();
+        assert_eq!(
+            super::changes(old, &new),
+            [Change {
+                position: 1,
+                span: 14..19,
+                old: "2.0.0".to_string(),
+                new: "2.1.0".to_string(),
+            }]
+        );
+        assert!(super::changes(old, old).is_empty());
+
+        // The new version of the first one looks like two.
+        let new = "foo 1.0.0 1.0.1 bar 2.0.1";
+        assert_eq!(
+            super::changes(old, new),
+            [
+                Change {
+                    position: 0,
+                    span: 4..9,
+                    old: "1.0.0".to_string(),
+                    new: "1.0.0 1.0.1".to_string(),
+                },
+                Change {
+                    position: 1,
+                    span: 14..19,
+                    old: "2.0.0".to_string(),
+                    new: "2.0.1".to_string(),
+                },
+            ]
+        );
+        let new = "foo 1.0 bar 2.0.1";
+        assert_eq!(super::changes(old, new).len(), 2);
+        assert_eq!(super::changes(old, new)[1].new, "2.0.1");
+        // Something else changed too.
+        assert_eq!(super::changes(old, "baz 1.1.0 bar 2.0.0")[0].new, "1.1.0");
+    }
+
+    #[test]
+    fn scheme() {
+        struct Odd;
+
+        impl VersionScheme for Odd {
+            fn next(&self, current: &str, _: &Version) -> Option<String> {
+                let next = super::next(current, Version::Minor).ok()?;
+                super::next(&next, Version::Minor).ok()
+            }
+        }
+
+        assert_eq!(
+            inc_with("1.1.0 1.1.0", Position::Nth(1), Version::Minor, &Odd).unwrap(),
+            "1.1.0 1.3.0"
+        );
+        assert_eq!(
+            inc_with("1.1.0", Position::All, Version::Major, &SemVer).unwrap(),
+            "2.0.0"
+        );
+    }
+
+    #[test]
+    fn map() {
+        let mut lines = Vec::new();
+        let result = inc_map("a 1.0.0\nb 2.0.0 3.0.0", Position::All, &mut |m| {
+            lines.push((m.position, m.line, m.text.to_string()));
+            (m.line == 2).then(|| "9.9.9".to_string())
+        })
+        .unwrap();
+
+        assert_eq!(result, "a 1.0.0\nb 9.9.9 9.9.9");
+        assert_eq!(
+            lines,
+            [
+                (0, 1, "a 1.0.0".to_string()),
+                (1, 2, "b 2.0.0 3.0.0".to_string()),
+                (2, 2, "b 2.0.0 3.0.0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn ip_addresses() {
+        let hay = "listen 192.168.1.10:80 # 1.0.0\nv1.2.3.4000 10.0.0.1";
+        assert_eq!(list_versions(hay), ["1.0.0", "1.2.3"]);
+        assert_eq!(
+            inc(hay, Position::All, Version::Patch, 1).unwrap(),
+            "listen 192.168.1.10:80 # 1.0.1\nv1.2.4.4000 10.0.0.1"
+        );
+
+        let selector = Selector {
+            skip_ips: false,
+            ..Selector::default()
+        };
+        assert_eq!(
+            selector.list_versions(hay),
+            ["192.168.1", "1.0.0", "1.2.3", "10.0.0"]
+        );
+    }
+
+    #[test]
+    fn urls() {
+        let hay =
+            "pkgver=1.0.0\nsource=(\"https://example.com/v1.0.0/foo-1.0.0.tar.gz\" 'x 1.0.0')";
+        let selector = Selector {
+            skip_urls: true,
+            ..Selector::default()
+        };
+        assert_eq!(selector.list_versions(hay), ["1.0.0", "1.0.0"]);
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Minor, 1).unwrap(),
+            hay.replace("=1.0.0", "=1.1.0")
+                .replace("x 1.0.0", "x 1.1.0")
+        );
+    }
+
+    #[test]
+    fn prescan() {
+        assert!(!may_contain_version("foo. bar .1 2."));
+        assert!(may_contain_version("v1.2"));
+        assert_eq!(list_versions("1.2 and 3.4.5"), ["3.4.5"]);
+    }
+
+    #[test]
+    fn limits() {
+        let hay = "foo 1.0.0 bar";
+        let selector = Selector {
+            limits: Limits {
+                max_input: Some(8),
+                ..Limits::default()
+            },
+            ..Selector::default()
+        };
+        assert!(selector.check(hay).is_err());
+        assert!(selector.list_versions(hay).is_empty());
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Patch, 1),
+            Err(VerincError::InputTooLarge { len: 13, max: 8 })
+        );
+
+        let selector = Selector {
+            limits: Limits {
+                regex_size: 16,
+                ..Limits::default()
+            },
+            ..Selector::default()
+        };
+        assert!(selector.check(hay).is_err());
+        assert!(matches!(
+            selector.inc(hay, Position::All, Version::Patch, 1),
+            Err(VerincError::Regex(_))
+        ));
+
+        let selector = Selector {
+            limits: Limits {
+                dfa_size: 1 << 16,
+                ..Limits::default()
+            },
+            ..Selector::default()
+        };
+        assert!(selector.check(hay).is_ok());
+        assert_eq!(selector.list_versions(hay), ["1.0.0"]);
+    }
+
+    #[test]
+    fn pre_releases() {
+        let hay = "pkgver=1.2.3-rc.1 foo-1.0.0-beta.2.tar.gz 2.0.0-1";
+        assert_eq!(
+            list_versions(hay),
+            ["1.2.3-rc.1", "1.0.0-beta.2", "2.0.0-1"]
+        );
+        assert_eq!(
+            inc(hay, Position::All, Version::Patch, 1).unwrap(),
+            "pkgver=1.2.3 foo-1.0.0.tar.gz 2.0.0"
+        );
+        assert_eq!(
+            super::next("1.2.0-rc.1", Version::Minor).ok().as_deref(),
+            Some("1.2.0")
+        );
+        assert_eq!(
+            super::next("1.2.3-rc.1", Version::Minor).ok().as_deref(),
+            Some("1.3.0")
+        );
+        assert_eq!(
+            super::next("2.0.0-alpha", Version::Major).ok().as_deref(),
+            Some("2.0.0")
+        );
+        assert_eq!(
+            super::next("2.1.0-alpha", Version::Major).ok().as_deref(),
+            Some("3.0.0")
+        );
+
+        // Parts of file names.
+        let hay = "foo-1.0.0-x86_64.tar.gz foo-1.0.0-linux-amd64.zip foo-1.0.0.tar.gz";
+        assert_eq!(list_versions(hay), ["1.0.0", "1.0.0", "1.0.0"]);
+        assert_eq!(
+            inc(hay, Position::All, Version::Patch, 1).unwrap(),
+            hay.replace("1.0.0", "1.0.1")
+        );
+        assert_eq!(
+            super::set("v1.0.0-rc.2 1.0.0", Position::Nth(0), "1.0.0").unwrap(),
+            "v1.0.0 1.0.0"
+        );
+    }
+
+    #[test]
+    fn build_metadata() {
+        let hay = "2.1.0+20240101 1.0.0-rc.1+exp.sha.5114f85 foo-1.0.0+b1.tar.gz";
+        assert_eq!(
+            list_versions(hay),
+            ["2.1.0+20240101", "1.0.0-rc.1+exp.sha.5114f85", "1.0.0+b1"]
+        );
+        assert_eq!(
+            inc(hay, Position::All, Version::Patch, 1).unwrap(),
+            "2.1.1+20240101 1.0.0+exp.sha.5114f85 foo-1.0.1+b1.tar.gz"
+        );
+        assert_eq!(
+            super::next("2.1.0+20240101", Version::Minor)
+                .ok()
+                .as_deref(),
+            Some("2.2.0+20240101")
+        );
+
+        let selector = Selector {
+            strip_build: true,
+            ..Selector::default()
+        };
+        assert_eq!(
+            selector
+                .inc(
+                    "2.1.0+20240101 2.1.0+1",
+                    Position::Nth(0),
+                    Version::Patch,
+                    1
+                )
+                .unwrap(),
+            "2.1.1 2.1.0+1"
+        );
+    }
+
+    #[test]
+    fn two_components() {
+        let selector = Selector {
+            components: Components::Two,
+            ..Selector::default()
+        };
+        let hay = "version 5.2, not 1.2.3 or 192.168.1.10 but 5.9-rc1";
+        assert_eq!(selector.list_versions(hay), ["5.2", "5.9-rc1"]);
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Patch, 1).unwrap(),
+            "version 5.3, not 1.2.3 or 192.168.1.10 but 5.9"
+        );
+        assert_eq!(
+            selector
+                .inc(hay, Position::Nth(0), Version::Major, 1)
+                .unwrap(),
+            hay.replace("5.2", "6.0")
+        );
+        assert_eq!(list_versions(hay), ["1.2.3"]);
+    }
+
+    #[test]
+    fn four_components() {
+        let selector = Selector {
+            components: Components::Four,
+            ..Selector::default()
+        };
+        let hay = "FILEVERSION 1.2.3.4 and 1.0.0.0, not 1.2.3 or 1.2.3.4.5";
+        assert_eq!(selector.list_versions(hay), ["1.2.3.4", "1.0.0.0"]);
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Build, 1).unwrap(),
+            "FILEVERSION 1.2.3.5 and 1.0.0.1, not 1.2.3 or 1.2.3.4.5"
+        );
+        assert_eq!(
+            selector
+                .inc(hay, Position::Nth(0), Version::Minor, 1)
+                .unwrap(),
+            hay.replace("1.2.3.4 ", "1.3.0.0 ")
+        );
+        assert_eq!(
+            selector.next("1.2.3.4", Version::Patch).ok().as_deref(),
+            Some("1.2.4.0")
+        );
+        assert_eq!(
+            super::next("1.2.3", Version::Build).ok().as_deref(),
+            Some("1.2.4")
+        );
+    }
+
+    #[test]
+    fn epochs() {
+        let hay = "pkgver=1:2.3.4 3.0.0";
+        assert_eq!(list_versions(hay), ["1:2.3.4", "3.0.0"]);
+        assert_eq!(
+            inc(hay, Position::All, Version::Minor, 1).unwrap(),
+            "pkgver=1:2.4.0 3.1.0"
+        );
+        assert_eq!(
+            inc(hay, Position::All, Version::Epoch, 1).unwrap(),
+            "pkgver=2:0.0.0 1:0.0.0"
+        );
+        assert_eq!(
+            super::set(hay, Position::Nth(0), "2.5.0").unwrap(),
+            "pkgver=1:2.5.0 3.0.0"
+        );
+        assert_eq!(list_versions("12:30 at 8080:1.2"), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn decrement() {
+        let hay = "1.3.0 2.0.5-rc.1 0.0.0 1:2.0.0";
+        assert_eq!(
+            dec(hay, Position::All, Version::Minor).unwrap(),
+            "1.2.0 2.0.5-rc.1 0.0.0 1:2.0.0"
+        );
+        assert_eq!(
+            dec(hay, Position::All, Version::Patch).unwrap(),
+            "1.3.0 2.0.4-rc.1 0.0.0 1:2.0.0"
+        );
+        assert_eq!(
+            dec(hay, Position::All, Version::Epoch).unwrap(),
+            "1.3.0 2.0.5-rc.1 0.0.0 0:2.0.0"
+        );
+        assert_eq!(
+            dec("5.2.1", Position::All, Version::Build).unwrap(),
+            "5.2.0"
+        );
+    }
+
+    #[test]
+    fn components() {
+        let hay = "1.2.3 4.5.6-rc.1 7.8";
+        assert_eq!(
+            set_component(hay, Position::All, Version::Minor, 10).unwrap(),
+            "1.10.3 4.10.6-rc.1 7.8"
+        );
+        assert_eq!(
+            set_component(hay, Position::Nth(1), Version::Patch, 0).unwrap(),
+            "1.2.3 4.5.0-rc.1 7.8"
+        );
+        assert_eq!(
+            set_component(hay, Position::All, Version::Epoch, 1).unwrap(),
+            hay
+        );
+    }
+
+    #[test]
+    fn pre_counter() {
+        let hay = "1.0.0-rc.2 1.0.0_beta3 1.0.0-rc+b1 1.0.0 1.0.0_64";
+        assert_eq!(
+            list_versions(hay),
+            ["1.0.0-rc.2", "1.0.0_beta3", "1.0.0-rc+b1", "1.0.0", "1.0.0"]
+        );
+        assert_eq!(
+            inc(hay, Position::All, Version::Pre, 1).unwrap(),
+            "1.0.0-rc.3 1.0.0_beta4 1.0.0-rc.1+b1 1.0.0 1.0.0_64"
+        );
+        assert_eq!(
+            inc("1.2.3_beta3", Position::All, Version::Patch, 1).unwrap(),
+            "1.2.3"
+        );
+    }
+
+    #[test]
+    fn finalization() {
+        assert_eq!(
+            finalize("1.3.0-rc.4 2.0.0_beta1+b5 3.0.0", Position::All).unwrap(),
+            "1.3.0 2.0.0+b5 3.0.0"
+        );
+        assert_eq!(
+            finalize("1.3.0-rc.4 1.3.0-rc.4", Position::Nth(1)).unwrap(),
+            "1.3.0-rc.4 1.3.0"
+        );
+    }
+
+    #[test]
+    fn v_prefix() {
+        let hay = "tag v1.2.3, 1.0.0 and dev1.0.0";
+        let mut selector = Selector {
+            v_prefix: VPrefix::Keep,
+            ..Selector::default()
+        };
+        assert_eq!(selector.list_versions(hay), ["v1.2.3", "1.0.0", "1.0.0"]);
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Minor, 1).unwrap(),
+            "tag v1.3.0, 1.1.0 and dev1.1.0"
+        );
+        assert_eq!(
+            selector.set(hay, Position::Nth(0), "v2.0.0").unwrap(),
+            "tag v2.0.0, 1.0.0 and dev1.0.0"
+        );
+
+        selector.v_prefix = VPrefix::Add;
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Patch, 1).unwrap(),
+            "tag v1.2.4, v1.0.1 and devv1.0.1"
+        );
+        selector.v_prefix = VPrefix::Strip;
+        assert_eq!(
+            selector
+                .inc(hay, Position::Nth(0), Version::Patch, 1)
+                .unwrap(),
+            "tag 1.2.4, 1.0.0 and dev1.0.0"
+        );
+    }
+
+    #[test]
+    fn zero_padding() {
+        let selector = Selector {
+            zero_pad: true,
+            ..Selector::default()
+        };
+        assert_eq!(
+            selector
+                .inc("1.09.0 1.002.0", Position::All, Version::Minor, 1)
+                .unwrap(),
+            "1.10.0 1.003.0"
+        );
+        assert_eq!(
+            selector
+                .inc("2024.01.09", Position::All, Version::Minor, 1)
+                .unwrap(),
+            "2024.02.00"
+        );
+        assert_eq!(
+            selector
+                .dec("1.10.0", Position::All, Version::Minor)
+                .unwrap(),
+            "1.09.0"
+        );
+        assert_eq!(selector.next("1.99.9", Version::Minor).unwrap(), "1.100.0");
+    }
+
+    #[test]
+    fn huge_components() {
+        assert_eq!(
+            inc("1.0.20240501123456", Position::Nth(0), Version::Patch, 1).unwrap(),
+            "1.0.20240501123457"
+        );
+        assert_eq!(
+            set_component("4294967296.0.0", Position::Nth(0), Version::Minor, 5).unwrap(),
+            "4294967296.5.0"
+        );
+        // Left alone rather than wrapped around.
+        let max = format!("1.0.{}", u64::MAX);
+        assert_eq!(inc(&max, Position::Nth(0), Version::Patch, 1).unwrap(), max);
+        assert_eq!(
+            inc(
+                "1.0.99999999999999999999",
+                Position::Nth(0),
+                Version::Patch,
+                1
+            )
+            .unwrap(),
+            "1.0.99999999999999999999"
+        );
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(
+            inc("1.2.3 1.2.3", Position::Nth(0), Version::Patch, 3).unwrap(),
+            "1.2.6 1.2.3"
+        );
+        assert_eq!(
+            inc("1.2.3", Position::All, Version::Minor, 2).unwrap(),
+            "1.4.0"
+        );
+        assert_eq!(
+            inc("1.2.0-rc.1 1.0.0-rc.1", Position::All, Version::Minor, 2).unwrap(),
+            "1.3.0 1.1.0"
+        );
+        assert_eq!(
+            inc("1.0.0-rc.2 1.0.0-beta", Position::All, Version::Pre, 5).unwrap(),
+            "1.0.0-rc.7 1.0.0-beta.5"
+        );
+        assert_eq!(
+            inc("1:2.3.4", Position::All, Version::Epoch, 2).unwrap(),
+            "3:0.0.0"
+        );
+    }
+
+    #[test]
+    fn skip_reasons() {
+        let hay = "1.2.3 192.168.1.10 https://example.com/1.0.0";
+        let selector = Selector {
+            skip_urls: true,
+            ..Selector::default()
+        };
+        assert_eq!(
+            selector.skipped(hay),
+            [
+                (6..15, "a part of an IP address"),
+                (39..44, "inside of a URL")
+            ]
+        );
+        assert_eq!(selector.skip_reason(hay, 0..5), None);
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(
+            inc(
+                "1.0.0 2.0.0 3.0.0 4.0.0",
+                Position::Range(1..=2),
+                Version::Patch,
+                1
+            )
+            .unwrap(),
+            "1.0.0 2.0.1 3.0.1 4.0.0"
+        );
+        assert!(matches!(Position::parse("1-3"), Some(Position::Range(r)) if r == (1..=3)));
+        assert!(matches!(Position::parse("all"), Some(Position::All)));
+        assert!(matches!(Position::parse("2"), Some(Position::Nth(2))));
+        assert!(Position::parse("3-1").is_none());
+
+        assert_eq!(
+            inc(
+                "1.0.0 2.0.0 3.0.0 4.0.0",
+                Position::Set(vec![0..=0, 3..=3]),
+                Version::Patch,
+                1
+            )
+            .unwrap(),
+            "1.0.1 2.0.0 3.0.0 4.0.1"
+        );
+        assert!(
+            matches!(Position::parse("5,0,2-3"), Some(Position::Set(s)) if s == [0..=0, 2..=3, 5..=5])
+        );
+        assert!(Position::parse("0,all").is_none());
+
+        let hay = "1.0.0 2.0.0 3.0.0";
+        assert_eq!(
+            inc(hay, Position::FromEnd(1), Version::Patch, 1).unwrap(),
+            "1.0.0 2.0.0 3.0.1"
+        );
+        assert_eq!(
+            inc(hay, Position::FromEnd(3), Version::Patch, 1).unwrap(),
+            "1.0.1 2.0.0 3.0.0"
+        );
+        assert_eq!(
+            inc(hay, Position::FromEnd(4), Version::Patch, 1).unwrap(),
+            hay
+        );
+        assert!(matches!(
+            Position::parse("last"),
+            Some(Position::FromEnd(1))
+        ));
+        assert!(matches!(Position::parse("-2"), Some(Position::FromEnd(2))));
+        assert!(Position::parse("-0").is_none());
+        assert!(Position::parse("x").is_none());
+    }
+
+    #[test]
+    fn line_ranges() {
+        let hay = "1.0.0\n2.0.0 3.0.0\n4.0.0\n";
+        let selector = Selector {
+            lines: Some(2..=3),
+            ..Selector::default()
+        };
+        assert_eq!(selector.list_versions(hay), ["2.0.0", "3.0.0", "4.0.0"]);
+        assert_eq!(
+            selector
+                .inc(hay, Position::Nth(1), Version::Minor, 1)
+                .unwrap(),
+            "1.0.0\n2.0.0 3.1.0\n4.0.0\n"
+        );
+    }
+
+    #[test]
+    fn line_patterns() {
+        let hay = "pkgver=1.0.0\nsource=(foo-1.0.0.tar.gz)\r\n_pkgver=2.0.0\r\n";
+        let selector = Selector {
+            line_match: Some(Regex::new("(^pkgver=|gz\\)$)").unwrap()),
+            ..Selector::default()
+        };
+        assert_eq!(selector.list_versions(hay), ["1.0.0", "1.0.0"]);
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Patch, 1).unwrap(),
+            "pkgver=1.0.1\nsource=(foo-1.0.1.tar.gz)\r\n_pkgver=2.0.0\r\n"
+        );
+
+        let selector = Selector {
+            line_exclude: Some(Regex::new("^source=").unwrap()),
+            ..selector
+        };
+        assert_eq!(selector.list_versions(hay), ["1.0.0"]);
+    }
+
+    #[test]
+    fn custom_regex() {
+        let regex = Regex::new(r"v(?<major>\d+)_(?<minor>\d+)(?:_(?<patch>\d+))?\b").unwrap();
+        let hay = "foo v1_2_3 bar v2_9 baz v3";
+        assert_eq!(
+            inc_with_regex(hay, &regex, Position::All, Version::Minor, 1),
+            Ok("foo v1_3_0 bar v2_10 baz v3".to_string())
+        );
+        assert_eq!(
+            inc_with_regex(hay, &regex, Position::FromEnd(1), Version::Patch, 2),
+            Ok("foo v1_2_3 bar v2_11 baz v3".to_string())
+        );
+
+        let no_major = Regex::new(r"(\d+)\.(\d+)").unwrap();
+        assert!(inc_with_regex(hay, &no_major, Position::All, Version::Minor, 1).is_err());
+        assert!(inc_with_regex(hay, &regex, Position::All, Version::Pre, 1).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let hay = "foo 1.0.0 bar";
+        let selector = Selector {
+            limits: Limits {
+                max_input: Some(8),
+                ..Limits::default()
+            },
+            ..Selector::default()
+        };
+        let err = selector.set(hay, Position::All, "2.0.0").unwrap_err();
+        assert_eq!(err, VerincError::InputTooLarge { len: 13, max: 8 });
+        assert_eq!(err.to_string(), "input of 13 bytes exceeds the limit of 8");
+        assert_eq!(
+            String::from(err),
+            "input of 13 bytes exceeds the limit of 8"
+        );
+
+        let regex = Regex::new(r"v(?<major>\d+)_(?<minor>\d+)").unwrap();
+        assert!(matches!(
+            super::inc_with_regex("v1_2", &regex, Position::All, Version::Pre, 1),
+            Err(VerincError::CannotBump(_))
+        ));
+        assert_eq!(
+            super::inc_with_regex(
+                "v99999999999999999999_2",
+                &regex,
+                Position::All,
+                Version::Minor,
+                1
+            ),
+            Err(VerincError::InvalidVersion(
+                "99999999999999999999".to_string()
+            ))
+        );
+        let regex = Regex::new(r"v(?<minor>\d+)").unwrap();
+        let err =
+            super::inc_with_regex("v1", &regex, Position::All, Version::Minor, 1).unwrap_err();
+        assert_eq!(
+            err,
+            VerincError::Regex("the regex has no major group".to_string())
+        );
+
+        // Usable with `?` in functions returning any error.
+        let err: Box<dyn std::error::Error> = Box::new(VerincError::InvalidVersion("x".into()));
+        assert_eq!(err.to_string(), "'x' is not a version");
+        assert_eq!(
+            super::set("no version", Position::All, "1.0.0").unwrap(),
+            "no version"
+        );
+    }
+
+    #[test]
+    fn huge_position_lists() {
+        let position = Position::parse("0,0-4294967295").unwrap();
+        assert!(position.contains(4_000_000_000));
+        assert_eq!(position.first(), 0);
+        assert_eq!(
+            inc("1.0.0 2.0.0", position, Version::Patch, 1).unwrap(),
+            "1.0.1 2.0.1"
+        );
+    }
+
+    #[test]
+    fn epochs_of_v_prefixed_versions() {
+        let hay = "v2.0.0 3.0.0";
+        assert_eq!(
+            inc(hay, Position::All, Version::Epoch, 1).unwrap(),
+            "v2.0.0 1:0.0.0"
+        );
+        for v_prefix in [VPrefix::Keep, VPrefix::Add] {
+            let selector = Selector {
+                v_prefix,
+                ..Selector::default()
+            };
+            assert_eq!(
+                selector
+                    .inc("v2.0.0", Position::All, Version::Epoch, 1)
+                    .unwrap(),
+                "v2.0.0"
+            );
+        }
+        let selector = Selector {
+            v_prefix: VPrefix::Strip,
+            ..Selector::default()
+        };
+        assert_eq!(
+            selector.inc(hay, Position::All, Version::Epoch, 1).unwrap(),
+            "1:0.0.0 1:0.0.0"
+        );
+    }
 }
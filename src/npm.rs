@@ -0,0 +1,223 @@
+//! Helpers for npm/yarn package.json files and workspaces.
+
+use std::{
+    fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    glob::expand,
+    json::{self, Spanned, Value},
+};
+
+const DEPENDENCY_FIELDS: [&str; 4] = [
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// A parsed package.json.
+pub struct Package<'a> {
+    src: &'a str,
+    doc: Spanned<Value>,
+}
+
+impl<'a> Package<'a> {
+    pub fn parse(src: &'a str) -> Result<Self, String> {
+        Ok(Package {
+            src,
+            doc: json::parse(src)?,
+        })
+    }
+
+    /// Returns the top-level `name`.
+    pub fn name(&self) -> Option<&str> {
+        self.doc.value.get("name")?.value.as_str()
+    }
+
+    /// Returns the top-level `version`.
+    pub fn version(&self) -> Option<&str> {
+        self.doc.value.get("version")?.value.as_str()
+    }
+
+    /// Returns the location of the top-level `version`, without the quotes.
+    pub fn version_span(&self) -> Option<Range<usize>> {
+        let version = self.doc.value.get("version")?;
+        version.value.as_str()?;
+        Some(version.span.start + 1..version.span.end - 1)
+    }
+
+    /// Returns the `workspaces` patterns, in either the array or the `{ "packages": [] }`
+    /// form.
+    pub fn workspaces(&self) -> Option<Vec<&str>> {
+        let workspaces = &self.doc.value.get("workspaces")?.value;
+        let patterns = match workspaces.get("packages") {
+            Some(packages) => packages.value.as_array()?,
+            None => workspaces.as_array()?,
+        };
+        Some(patterns.iter().filter_map(|p| p.value.as_str()).collect())
+    }
+
+    /// Rewrites all references to the package `name` in the dependency fields to `version`.
+    /// The range operator and the `workspace:` protocol are kept, references which are
+    /// resolved by the package manager (`workspace:*`, `workspace:^`) and complex ranges are
+    /// left alone.
+    pub fn update_dependency(&self, name: &str, version: &str) -> String {
+        let mut edits = Vec::new();
+
+        for field in DEPENDENCY_FIELDS {
+            let Some(deps) = self.doc.value.get(field).and_then(|d| d.value.as_object()) else {
+                continue;
+            };
+
+            for (dep, req) in deps {
+                if dep != name {
+                    continue;
+                }
+                if let Some(new) = req.value.as_str().and_then(|r| rewrite_range(r, version)) {
+                    edits.push((req.span.clone(), format!("\"{new}\"")));
+                }
+            }
+        }
+
+        edits.sort_by_key(|(span, _)| span.start);
+
+        let mut out = String::with_capacity(self.src.len());
+        let mut last = 0;
+        for (span, new) in edits {
+            out.push_str(&self.src[last..span.start]);
+            out.push_str(&new);
+            last = span.end;
+        }
+        out.push_str(&self.src[last..]);
+        out
+    }
+}
+
+fn rewrite_range(req: &str, version: &str) -> Option<String> {
+    let (protocol, range) = match req.strip_prefix("workspace:") {
+        Some(range) => ("workspace:", range),
+        None => ("", req),
+    };
+
+    let op = range
+        .find(|c: char| c.is_ascii_digit())
+        .map(|idx| &range[..idx])?;
+
+    if range.contains([' ', '|', '*', 'x', 'X']) || !matches!(op, "" | "^" | "~" | "=") {
+        return None;
+    }
+
+    Some(format!("{protocol}{op}{version}"))
+}
+
+/// Walks up from the directory of `package` looking for the package.json that declares
+/// `workspaces`. Returns the path of that package.json.
+pub fn find_workspace_root(package: &Path) -> io::Result<Option<PathBuf>> {
+    let dir = match package.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => crate::path::canonicalize(dir)?,
+        _ => crate::path::canonicalize(Path::new("."))?,
+    };
+
+    for ancestor in dir.ancestors() {
+        let candidate = ancestor.join("package.json");
+        if !candidate.is_file() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&candidate)?;
+        if Package::parse(&content).is_ok_and(|p| p.workspaces().is_some()) {
+            return Ok(Some(candidate));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the paths of the workspace root package.json and the package.json files of all
+/// workspace packages (sorted).
+pub fn workspace_packages(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let content = fs::read_to_string(root)?;
+    let package =
+        Package::parse(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let dir = root.parent().unwrap_or(Path::new("."));
+    let patterns = package.workspaces().unwrap_or_default();
+
+    let mut excluded = Vec::new();
+    for pattern in patterns.iter().filter_map(|p| p.strip_prefix('!')) {
+        excluded.extend(expand(dir, pattern)?);
+    }
+
+    let mut packages = vec![root.to_path_buf()];
+    for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+        for member in expand(dir, pattern)? {
+            let path = member.join("package.json");
+            if !excluded.contains(&member) && path.is_file() && !packages.contains(&path) {
+                packages.push(path);
+            }
+        }
+    }
+
+    // The root comes first, the members are sorted so that the order doesn't depend on
+    // the order of the patterns or of the directory entries.
+    packages[1..].sort();
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package() {
+        let package = Package::parse(
+            r#"{ "name": "@scope/foo", "version": "1.0.0", "workspaces": { "packages": ["packages/*"] } }"#,
+        )
+        .unwrap();
+        assert_eq!(package.name(), Some("@scope/foo"));
+        assert_eq!(package.version(), Some("1.0.0"));
+        assert_eq!(package.workspaces(), Some(vec!["packages/*"]));
+    }
+
+    #[test]
+    fn update_dependency() {
+        let src = r#"{
+  "name": "app",
+  "dependencies": {
+    "foo": "^1.0.0",
+    "foobar": "^1.0.0"
+  },
+  "devDependencies": {
+    "foo": "workspace:~1.0.0"
+  },
+  "peerDependencies": {
+    "foo": ">=1.0.0 <2.0.0"
+  },
+  "optionalDependencies": {
+    "foo": "workspace:*"
+  }
+}
+"#;
+        let expected = r#"{
+  "name": "app",
+  "dependencies": {
+    "foo": "^1.1.0",
+    "foobar": "^1.0.0"
+  },
+  "devDependencies": {
+    "foo": "workspace:~1.1.0"
+  },
+  "peerDependencies": {
+    "foo": ">=1.0.0 <2.0.0"
+  },
+  "optionalDependencies": {
+    "foo": "workspace:*"
+  }
+}
+"#;
+        let package = Package::parse(src).unwrap();
+        assert_eq!(package.update_dependency("foo", "1.1.0"), expected);
+    }
+}
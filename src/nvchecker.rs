@@ -0,0 +1,103 @@
+//! Reading the results of [nvchecker](https://github.com/lilydjwg/nvchecker).
+//!
+//! The configuration lists the entries, its `[__config__]` table names the `newver` file
+//! (relative to the configuration) with the versions nvchecker found for them.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{json, toml};
+
+#[derive(Debug)]
+pub struct Nvchecker {
+    /// The names of the entries of the configuration.
+    pub entries: Vec<String>,
+    /// The new version of each entry checked so far.
+    pub newver: Vec<(String, String)>,
+}
+
+impl Nvchecker {
+    /// Reads the configuration at `path` and its `newver` file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .map_err(|err| format!("cannot read '{}': {err}", path.display()))
+        };
+        let (entries, newver) = config(&read(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let newver = versions(&read(&dir.join(newver))?)?;
+        Ok(Nvchecker { entries, newver })
+    }
+
+    /// Returns the new version of the entry `name`.
+    pub fn version(&self, name: &str) -> Option<&str> {
+        self.newver
+            .iter()
+            .find(|(entry, _)| entry == name)
+            .map(|(_, version)| version.as_str())
+    }
+}
+
+/// Returns the names of the entries of the configuration `src` and its `newver` path.
+fn config(src: &str) -> Result<(Vec<String>, PathBuf), String> {
+    let doc = toml::parse(src)?;
+    let newver = doc
+        .get(&["__config__"], "newver")
+        .and_then(|newver| newver.value.as_str())
+        .ok_or("there is no __config__.newver")?;
+    let entries = doc
+        .headers
+        .iter()
+        .filter(|header| header.path.len() == 1 && header.path[0] != "__config__")
+        .map(|header| header.path[0].clone())
+        .collect();
+    Ok((entries, PathBuf::from(newver)))
+}
+
+/// Returns the versions of a `newver` file, either `{"name": "1.2.3"}` or the format 2
+/// with `{"version": 2, "data": {"name": {"version": "1.2.3"}}}`.
+fn versions(src: &str) -> Result<Vec<(String, String)>, String> {
+    let doc = json::parse(src)?;
+    let (entries, nested) = match doc.value.get("data") {
+        Some(data) => (data.value.as_object(), true),
+        None => (doc.value.as_object(), false),
+    };
+    let entries = entries.ok_or("invalid newver file")?;
+    Ok(entries
+        .iter()
+        .filter_map(|(name, value)| {
+            let version = if nested {
+                value.value.get("version")?.value.as_str()?
+            } else {
+                value.value.as_str()?
+            };
+            Some((name.clone(), version.to_string()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results() {
+        let (entries, newver) = config(
+            "[__config__]\noldver = \"old_ver.json\"\nnewver = \"new_ver.json\"\n\n[foo]\nsource = \"github\"\n\n[python-bar]\nsource = \"pypi\"\n",
+        )
+        .unwrap();
+        assert_eq!(entries, ["foo", "python-bar"]);
+        assert_eq!(newver, Path::new("new_ver.json"));
+        assert!(config("[foo]\nsource = \"github\"\n").is_err());
+
+        let v2 = r#"{"version": 2, "data": {"foo": {"version": "1.2.3", "revision": "abc"}}}"#;
+        assert_eq!(
+            versions(v2),
+            Ok(vec![("foo".to_string(), "1.2.3".to_string())])
+        );
+        let v1 = r#"{"foo": "1.2.3", "bar": "0.1"}"#;
+        assert_eq!(versions(v1).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,90 @@
+//! Path handling which behaves on Windows the same way it does elsewhere.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Paths longer than this need the extended-length prefix on Windows.
+const MAX_PATH: usize = 260;
+
+const RESERVED: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Like [`Path::canonicalize`] but without the `\\?\` prefix added on Windows, so that the
+/// result can be compared with and stripped from ordinary paths.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    Ok(simplify(&path.canonicalize()?))
+}
+
+/// Turns the extended-length paths `\\?\C:\foo` and `\\?\UNC\server\share\foo` into
+/// `C:\foo` and `\\server\share\foo`, unless they are too long for that.
+pub fn simplify(path: &Path) -> PathBuf {
+    let Some(s) = path.to_str() else {
+        return path.to_path_buf();
+    };
+
+    let simple = if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{rest}")
+    } else {
+        match s.strip_prefix(r"\\?\") {
+            Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest.to_string(),
+            _ => return path.to_path_buf(),
+        }
+    };
+
+    if simple.len() < MAX_PATH {
+        PathBuf::from(simple)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Returns true if the file name of `path` is a device name reserved by Windows (`CON`,
+/// `NUL`, `COM1`, ...), which is the case with any extension and in any case.
+pub fn is_reserved(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    // On Windows, `C:\foo\nul` has the file name `nul`, elsewhere both separators count.
+    let name = name.rsplit(['\\', '/']).next().unwrap_or(name);
+    let stem = name.split('.').next().unwrap_or(name).trim_end_matches(' ');
+    RESERVED.iter().any(|r| r.eq_ignore_ascii_case(stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_length() {
+        assert_eq!(
+            simplify(Path::new(r"\\?\C:\Users\foo\Cargo.toml")),
+            Path::new(r"C:\Users\foo\Cargo.toml")
+        );
+        assert_eq!(
+            simplify(Path::new(r"\\?\UNC\server\share\Cargo.toml")),
+            Path::new(r"\\server\share\Cargo.toml")
+        );
+        assert_eq!(
+            simplify(Path::new(r"\\?\Volume{1234}\foo")),
+            Path::new(r"\\?\Volume{1234}\foo")
+        );
+        assert_eq!(simplify(Path::new("/home/foo")), Path::new("/home/foo"));
+
+        let long = format!(r"\\?\C:\{}", "a".repeat(300));
+        assert_eq!(simplify(Path::new(&long)), Path::new(&long));
+    }
+
+    #[test]
+    fn reserved() {
+        assert!(is_reserved(Path::new("nul")));
+        assert!(is_reserved(Path::new("dir/CON.txt")));
+        assert!(is_reserved(Path::new(r"C:\dir\Com1 .tar.gz")));
+        assert!(!is_reserved(Path::new("console.txt")));
+        assert!(!is_reserved(Path::new("COM10")));
+        assert!(!is_reserved(Path::new("nul/Cargo.toml")));
+    }
+}
@@ -0,0 +1,473 @@
+//! Keeping the checksums of PKGBUILD sources in sync with their URLs, and comparing the
+//! versions of PKGBUILDs as pacman does.
+//!
+//! After `pkgver` was bumped, the sources whose URL (with the variables expanded)
+//! changed are downloaded and their entries of `sha256sums`, `sha512sums` and `b2sums`
+//! are replaced. Local files, VCS sources and `SKIP` are left alone.
+
+use std::{cmp::Ordering, ops::Range};
+
+use crate::{
+    digest::{self, Digests, Limits},
+    parallel,
+};
+
+/// The checksum arrays which are updated.
+const SUMS: [&str; 3] = ["sha256sums", "sha512sums", "b2sums"];
+
+/// An element of an array, with its value unquoted.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Element {
+    pub value: String,
+    /// Span of the value, without the quotes.
+    pub span: Range<usize>,
+}
+
+/// Returns the location of the value of the first top-level variable `name` in
+/// `content`, without the quotes.
+pub(crate) fn assignment(content: &str, name: &str) -> Option<Range<usize>> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let Some(value) = line
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('='))
+        else {
+            continue;
+        };
+        if value.starts_with('(') {
+            continue;
+        }
+        let start = start + name.len() + 1;
+        let span = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let len = value[1..].find(quote)?;
+                start + 1..start + 1 + len
+            }
+            _ => {
+                let len = value
+                    .find(|c: char| c.is_whitespace() || c == ';' || c == '#')
+                    .unwrap_or(value.len());
+                start..start + len
+            }
+        };
+        return Some(span);
+    }
+    None
+}
+
+/// Returns the elements of the first top-level array `name` in `content`, `None` if there
+/// is no such array.
+pub(crate) fn array(content: &str, name: &str) -> Option<Vec<Element>> {
+    let prefix = format!("{name}=(");
+    let start = if content.starts_with(&prefix) {
+        prefix.len()
+    } else {
+        content.find(&format!("\n{prefix}"))? + 1 + prefix.len()
+    };
+
+    let mut elements = Vec::new();
+    let mut chars = content[start..].char_indices().map(|(i, c)| (start + i, c));
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            ')' => return Some(elements),
+            '#' => {
+                chars.find(|(_, c)| *c == '\n');
+            }
+            '"' | '\'' => {
+                let (end, _) = chars.find(|(_, q)| *q == c)?;
+                elements.push(Element {
+                    value: content[idx + 1..end].to_string(),
+                    span: idx + 1..end,
+                });
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                let end = content[idx..]
+                    .find(|c: char| c.is_whitespace() || c == ')')
+                    .map_or(content.len(), |len| idx + len);
+                elements.push(Element {
+                    value: content[idx..end].to_string(),
+                    span: idx..end,
+                });
+                if content[end..].starts_with(')') {
+                    return Some(elements);
+                }
+                chars.find(|(i, _)| *i + 1 >= end);
+            }
+        }
+    }
+    None
+}
+
+/// Expands `$name` and `${name}` in `value` with the top-level variables of `content`.
+fn expand(content: &str, value: &str, depth: usize) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        rest = &rest[idx + 1..];
+
+        let (name, len) = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced
+                    .find('}')
+                    .ok_or_else(|| format!("unclosed ${{ in {value}"))?;
+                (&braced[..end], end + 2)
+            }
+            None => {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                (&rest[..end], end)
+            }
+        };
+        let span = assignment(content, name)
+            .filter(|_| !name.is_empty() && depth < 8)
+            .ok_or_else(|| format!("cannot expand ${name} in {value}"))?;
+        out.push_str(&expand(content, &content[span], depth + 1)?);
+        rest = &rest[len..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Returns `[epoch:]pkgver-pkgrel` of the PKGBUILD `content`, with the variables expanded.
+pub fn full_version(content: &str) -> Result<String, String> {
+    let value = |name| match assignment(content, name) {
+        Some(span) => expand(content, &content[span], 0).map(Some),
+        None => Ok(None),
+    };
+    let pkgver = value("pkgver")?.ok_or("there is no pkgver")?;
+    let pkgrel = value("pkgrel")?.ok_or("there is no pkgrel")?;
+    Ok(match value("epoch")?.filter(|epoch| epoch != "0") {
+        Some(epoch) => format!("{epoch}:{pkgver}-{pkgrel}"),
+        None => format!("{pkgver}-{pkgrel}"),
+    })
+}
+
+/// Returns the name of the first package of the PKGBUILD `content`.
+pub fn package_name(content: &str) -> Option<String> {
+    match assignment(content, "pkgname") {
+        Some(span) => expand(content, &content[span], 0).ok(),
+        None => expand(content, &array(content, "pkgname")?.first()?.value, 0).ok(),
+    }
+}
+
+/// Compares two `[epoch:]pkgver[-pkgrel]` versions as pacman's `vercmp` does.
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (&str, &str, Option<&str>) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) if epoch.bytes().all(|b| b.is_ascii_digit()) => (epoch, rest),
+            _ => ("0", version),
+        };
+        match rest.rsplit_once('-') {
+            Some((pkgver, pkgrel)) => (epoch, pkgver, Some(pkgrel)),
+            None => (epoch, rest, None),
+        }
+    }
+    let (a_epoch, a_ver, a_rel) = split(a);
+    let (b_epoch, b_ver, b_rel) = split(b);
+
+    segments(a_epoch, b_epoch)
+        .then_with(|| segments(a_ver, b_ver))
+        .then_with(|| match (a_rel, b_rel) {
+            // A missing pkgrel matches any.
+            (Some(a), Some(b)) => segments(a, b),
+            _ => Ordering::Equal,
+        })
+}
+
+/// Compares the alternating numeric and alphabetic segments of `a` and `b` as rpmvercmp.
+fn segments(a: &str, b: &str) -> Ordering {
+    let mut a = a;
+    let mut b = b;
+    loop {
+        a = a.trim_start_matches(|c: char| !c.is_ascii_alphanumeric());
+        b = b.trim_start_matches(|c: char| !c.is_ascii_alphanumeric());
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            // The one with more segments is newer, unless they are alphabetic (1.0a < 1.0).
+            return match (a.chars().next(), b.chars().next()) {
+                (None, None) => Ordering::Equal,
+                (Some(c), None) if c.is_ascii_alphabetic() => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (None, Some(c)) if c.is_ascii_alphabetic() => Ordering::Greater,
+                (None, Some(_)) => Ordering::Less,
+                _ => unreachable!(),
+            };
+        };
+
+        let numeric = x.is_ascii_digit();
+        if numeric != y.is_ascii_digit() {
+            // Numbers are newer than letters.
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+        let end = |s: &str| {
+            s.find(|c: char| c.is_ascii_digit() != numeric || !c.is_ascii_alphanumeric())
+                .unwrap_or(s.len())
+        };
+        let (x, rest_a) = a.split_at(end(a));
+        let (y, rest_b) = b.split_at(end(b));
+        let order = if numeric {
+            let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+            x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+        } else {
+            x.cmp(y)
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+        (a, b) = (rest_a, rest_b);
+    }
+}
+
+/// Returns the URLs of the `source` array with the variables expanded, `None` for the
+/// sources which are not downloaded (local files and VCS sources).
+pub fn sources(content: &str) -> Result<Vec<Option<String>>, String> {
+    let Some(sources) = array(content, "source") else {
+        return Ok(Vec::new());
+    };
+    sources
+        .iter()
+        .map(|source| {
+            // The file name of `name::url` is just for makepkg.
+            let url = source
+                .value
+                .split_once("::")
+                .map_or(&*source.value, |(_, url)| url);
+            let url = expand(content, url, 0)?;
+            let (scheme, _) = url.split_once("://").unwrap_or(("", ""));
+            Ok(matches!(scheme, "http" | "https" | "ftp").then_some(url))
+        })
+        .collect()
+}
+
+/// Downloads the artifact at `url` and returns its checksums.
+pub fn digests(url: &str) -> Result<Digests, String> {
+    digest::fetch(url, &Limits::default())
+}
+
+/// Replaces the checksums of every source in `new` whose URL is not present in `old`
+/// with the ones returned by `hash`, which is called in parallel (see
+/// [`crate::parallel`]). Nothing is returned unless all of them succeed.
+pub fn update_sums(
+    old: &str,
+    new: &str,
+    hash: impl Fn(&str) -> Result<Digests, String> + Sync,
+) -> Result<String, String> {
+    let known = sources(old)?;
+    let sources = sources(new)?;
+    let changed: Vec<_> = sources
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, url)| Some((idx, url.as_ref()?)))
+        .filter(|(_, url)| !known.iter().flatten().any(|known| known == *url))
+        .collect();
+    let digests = parallel::map(&changed, |(_, url)| hash(url));
+
+    let mut edits = Vec::new();
+    for ((idx, url), digests) in changed.iter().zip(digests) {
+        let digests = digests.map_err(|err| format!("{url}: {err}"))?;
+        for name in SUMS {
+            let Some(sums) = array(new, name) else {
+                continue;
+            };
+            if sums.len() != sources.len() {
+                return Err(format!(
+                    "{name} has {} entries but source has {}",
+                    sums.len(),
+                    sources.len()
+                ));
+            }
+            if sums[*idx].value == "SKIP" {
+                continue;
+            }
+            let sum = match name {
+                "sha256sums" => &digests.sha256,
+                "sha512sums" => &digests.sha512,
+                _ => &digests.b2,
+            };
+            edits.push((sums[*idx].span.clone(), sum.clone()));
+        }
+    }
+    edits.sort_by_key(|(span, _)| span.start);
+
+    let mut out = String::with_capacity(new.len());
+    let mut last = 0;
+    for (span, sum) in edits {
+        out.push_str(&new[last..span.start]);
+        out.push_str(&sum);
+        last = span.end;
+    }
+    out.push_str(&new[last..]);
+    Ok(out)
+}
+
+/// Returns the `.SRCINFO` `srcinfo` with the `pkgver`, `pkgrel`, `source` and checksums
+/// of its `pkgbase` taken from `pkgbuild`, as `makepkg --printsrcinfo` would write them.
+pub fn update_srcinfo(srcinfo: &str, pkgbuild: &str) -> Result<String, String> {
+    let mut values = Vec::new();
+    for name in ["pkgver", "pkgrel"] {
+        if let Some(span) = assignment(pkgbuild, name) {
+            values.push((name, vec![expand(pkgbuild, &pkgbuild[span], 0)?], 0));
+        }
+    }
+    for name in ["source"].into_iter().chain(SUMS) {
+        if let Some(elements) = array(pkgbuild, name) {
+            let elements = elements
+                .iter()
+                .map(|element| expand(pkgbuild, &element.value, 0))
+                .collect::<Result<_, _>>()?;
+            values.push((name, elements, 0));
+        }
+    }
+
+    let mut out = String::with_capacity(srcinfo.len());
+    let mut base = true;
+    for line in srcinfo.split_inclusive('\n') {
+        base &= !line.starts_with("pkgname =");
+        let text = line.trim_end_matches(['\n', '\r']);
+        let entry = text.trim_start().split_once(" = ");
+        let Some((name, elements, count)) = entry
+            .filter(|_| base)
+            .and_then(|(key, _)| values.iter_mut().find(|(name, ..)| *name == key))
+        else {
+            out.push_str(line);
+            continue;
+        };
+
+        let Some(value) = elements.get(*count) else {
+            return Err(format!("{name} has more entries than in the PKGBUILD"));
+        };
+        *count += 1;
+        let indent = &text[..text.len() - text.trim_start().len()];
+        out.push_str(&format!("{indent}{name} = {value}{}", &line[text.len()..]));
+    }
+
+    match values
+        .iter()
+        .find(|(_, elements, count)| *count != elements.len())
+    {
+        Some((name, ..)) => Err(format!("{name} has fewer entries than in the PKGBUILD")),
+        None => Ok(out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PKGBUILD: &str = "pkgname=foo
+pkgver=1.0.0
+url='https://example.com'
+source=(\"$pkgname-$pkgver.tar.gz::$url/v${pkgver}.tar.gz\" # release
+        local.patch
+        git+https://example.com/foo.git)
+sha256sums=('aaaa'
+            'bbbb'
+            'SKIP')
+b2sums=(cccc dddd SKIP)
+";
+
+    #[test]
+    fn arrays() {
+        let sums = array(PKGBUILD, "b2sums").unwrap();
+        let values: Vec<_> = sums.iter().map(|e| e.value.as_str()).collect();
+        assert_eq!(values, ["cccc", "dddd", "SKIP"]);
+        assert_eq!(&PKGBUILD[sums[1].span.clone()], "dddd");
+        assert_eq!(
+            sources(PKGBUILD),
+            Ok(vec![
+                Some("https://example.com/v1.0.0.tar.gz".to_string()),
+                None,
+                None
+            ])
+        );
+        assert!(sources("source=($missing/foo.tar.gz)").is_err());
+    }
+
+    #[test]
+    fn updates_changed_sources() {
+        let new = PKGBUILD.replace("pkgver=1.0.0", "pkgver=1.0.1");
+        let hash = |url: &str| {
+            Ok(Digests {
+                size: 0,
+                sha256: format!("sha256 of {url}"),
+                sha512: String::new(),
+                b2: format!("b2 of {url}"),
+            })
+        };
+        let expected = new
+            .replace("aaaa", "sha256 of https://example.com/v1.0.1.tar.gz")
+            .replace("cccc", "b2 of https://example.com/v1.0.1.tar.gz");
+        assert_eq!(update_sums(PKGBUILD, &new, hash), Ok(expected));
+        assert_eq!(
+            update_sums(PKGBUILD, PKGBUILD, hash),
+            Ok(PKGBUILD.to_string())
+        );
+        assert!(update_sums(PKGBUILD, &new, |_| Err("404".to_string())).is_err());
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(
+            full_version(PKGBUILD),
+            Err("there is no pkgrel".to_string())
+        );
+        let pkgbuild = format!("{PKGBUILD}pkgrel=2\nepoch=1\n");
+        assert_eq!(full_version(&pkgbuild), Ok("1:1.0.0-2".to_string()));
+        assert_eq!(package_name(PKGBUILD).as_deref(), Some("foo"));
+        assert_eq!(
+            package_name("pkgname=(foo-git bar)").as_deref(),
+            Some("foo-git")
+        );
+
+        for (a, b, order) in [
+            ("1.0.0-1", "1.0.0-1", Ordering::Equal),
+            ("1.0.10-1", "1.0.9-1", Ordering::Greater),
+            ("1.0.0-2", "1.0.0-10", Ordering::Less),
+            ("1:0.1-1", "2.0-1", Ordering::Greater),
+            ("1.0a-1", "1.0-1", Ordering::Less),
+            ("1.0.1-1", "1.0-1", Ordering::Greater),
+            ("1.0rc1-1", "1.0-1", Ordering::Less),
+            ("1.0", "1.0-5", Ordering::Equal),
+        ] {
+            assert_eq!(vercmp(a, b), order, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn srcinfo() {
+        let srcinfo = "pkgbase = foo
+\tpkgver = 0.9.0
+\tpkgrel = 2
+\tsource = foo-0.9.0.tar.gz::https://example.com/v0.9.0.tar.gz
+\tsource = local.patch
+\tsource = git+https://example.com/foo.git
+\tsha256sums = 0000
+\tsha256sums = bbbb
+\tsha256sums = SKIP
+\tb2sums = 1111
+\tb2sums = dddd
+\tb2sums = SKIP
+
+pkgname = foo
+";
+        let expected = srcinfo
+            .replace("0.9.0", "1.0.0")
+            .replace("pkgrel = 2", "pkgrel = 1")
+            .replace("0000", "aaaa")
+            .replace("1111", "cccc");
+        let pkgbuild = PKGBUILD.replace("pkgver=1.0.0", "pkgver=1.0.0\npkgrel=1");
+        assert_eq!(update_srcinfo(srcinfo, &pkgbuild), Ok(expected));
+
+        let srcinfo = srcinfo.replace("\tsource = local.patch\n", "");
+        assert!(update_srcinfo(&srcinfo, &pkgbuild).is_err());
+    }
+}
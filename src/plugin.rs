@@ -0,0 +1,218 @@
+//! Loading of version schemes and file formats compiled to WebAssembly.
+//!
+//! A plugin is a WASM module exporting its `memory` and `verinc_alloc(len: i32) -> i32`
+//! which returns a buffer of `len` bytes for the input. Then it exports one or both of:
+//!
+//! - `verinc_format_bump(ptr: i32, len: i32, version: i32) -> i64` which receives the whole
+//!   file and returns the new content ([`FileFormat`]).
+//! - `verinc_scheme_next(ptr: i32, len: i32, version: i32) -> i64` which receives a single
+//!   matched version and returns the next one ([`VersionScheme`]).
+//!
+//! `version` is 0 for major, 1 for minor, 2 for patch, 3 for build, 4 for epoch and 5
+//! for the pre-release. The input and output are UTF-8 and the output is returned as
+//! `ptr << 32 | len`. A negative return value means failure (or not a version of the
+//! scheme).
+
+use std::{
+    cell::RefCell,
+    env,
+    path::{Path, PathBuf},
+};
+
+use wasmtime::{Engine, Instance, Module, Store};
+
+use crate::{FileFormat, Version, VersionScheme};
+
+const FORMAT: &str = "verinc_format_bump";
+const SCHEME: &str = "verinc_scheme_next";
+
+pub struct Plugin {
+    store: RefCell<Store<()>>,
+    instance: Instance,
+}
+
+impl Plugin {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Instantiates a plugin from a binary (or text) WASM module.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, bytes).map_err(|err| err.to_string())?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[]).map_err(|err| err.to_string())?;
+
+        Ok(Plugin {
+            store: RefCell::new(store),
+            instance,
+        })
+    }
+
+    fn exports(&self, name: &str) -> bool {
+        self.instance
+            .get_func(&mut *self.store.borrow_mut(), name)
+            .is_some()
+    }
+
+    /// Returns true if the plugin implements [`FileFormat`].
+    pub fn is_format(&self) -> bool {
+        self.exports(FORMAT)
+    }
+
+    /// Returns true if the plugin implements [`VersionScheme`].
+    pub fn is_scheme(&self) -> bool {
+        self.exports(SCHEME)
+    }
+
+    fn call(&self, export: &str, input: &str, version: &Version) -> Result<Option<String>, String> {
+        let mut store = self.store.borrow_mut();
+        let store = &mut *store;
+        let err = |err: wasmtime::Error| format!("{export}: {err}");
+
+        let memory = self
+            .instance
+            .get_memory(&mut *store, "memory")
+            .ok_or("the plugin does not export memory")?;
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut *store, "verinc_alloc")
+            .map_err(err)?;
+        let func = self
+            .instance
+            .get_typed_func::<(i32, i32, i32), i64>(&mut *store, export)
+            .map_err(err)?;
+
+        let len = i32::try_from(input.len()).map_err(|_| "the input is too large")?;
+        let ptr = alloc.call(&mut *store, len).map_err(err)?;
+        memory
+            .write(&mut *store, ptr as u32 as usize, input.as_bytes())
+            .map_err(|err| format!("{export}: {err}"))?;
+
+        let level = match version {
+            Version::Major => 0,
+            Version::Minor => 1,
+            Version::Patch => 2,
+            Version::Build => 3,
+            Version::Epoch => 4,
+            Version::Pre => 5,
+        };
+        let result = func.call(&mut *store, (ptr, len, level)).map_err(err)?;
+        if result < 0 {
+            return Ok(None);
+        }
+
+        let (ptr, len) = ((result >> 32) as usize, (result & 0xffff_ffff) as usize);
+        let mut buf = vec![0; len];
+        memory
+            .read(&*store, ptr, &mut buf)
+            .map_err(|err| format!("{export}: {err}"))?;
+
+        String::from_utf8(buf)
+            .map(Some)
+            .map_err(|_| format!("{export}: invalid UTF-8"))
+    }
+}
+
+impl FileFormat for Plugin {
+    fn bump(&self, content: &str, version: &Version) -> Result<String, String> {
+        self.call(FORMAT, content, version)?
+            .ok_or_else(|| "the plugin failed to bump the file".to_string())
+    }
+}
+
+impl VersionScheme for Plugin {
+    fn next(&self, current: &str, version: &Version) -> Option<String> {
+        self.call(SCHEME, current, version).ok().flatten()
+    }
+}
+
+/// Returns the directory plugins are looked up in, `$XDG_CONFIG_HOME/verinc/plugins`.
+pub fn plugins_dir() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("verinc").join("plugins"))
+}
+
+/// Resolves `name` either as a path to a module or as `<name>.wasm` in [`plugins_dir`].
+pub fn find(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    let path = plugins_dir()?.join(format!("{name}.wasm"));
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inc_with, Position};
+
+    const ALLOC: &str = r#"
+        (memory (export "memory") 1)
+        (func (export "verinc_alloc") (param i32) (result i32) i32.const 1024)
+    "#;
+
+    #[test]
+    fn format() {
+        // Returns the input as is.
+        let plugin = Plugin::from_bytes(
+            format!(
+                r#"(module {ALLOC}
+                    (func (export "verinc_format_bump") (param i32 i32 i32) (result i64)
+                        local.get 0
+                        i64.extend_i32_u
+                        i64.const 32
+                        i64.shl
+                        local.get 1
+                        i64.extend_i32_u
+                        i64.or))"#
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+        assert!(plugin.is_format());
+        assert!(!plugin.is_scheme());
+        assert_eq!(
+            plugin.bump("foo 1.0.0", &Version::Patch).unwrap(),
+            "foo 1.0.0"
+        );
+    }
+
+    #[test]
+    fn scheme() {
+        // Bumps everything to 4.2.0, fails for major.
+        let plugin = Plugin::from_bytes(
+            format!(
+                r#"(module {ALLOC}
+                    (data (i32.const 16) "4.2.0")
+                    (func (export "verinc_scheme_next") (param i32 i32 i32) (result i64)
+                        local.get 2
+                        i32.eqz
+                        if
+                            i64.const -1
+                            return
+                        end
+                        i64.const 68719476741))"#
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+        assert!(plugin.is_scheme());
+        assert_eq!(
+            inc_with("1.0.0 2.0.0", Position::All, Version::Minor, &plugin).unwrap(),
+            "4.2.0 4.2.0"
+        );
+        assert_eq!(
+            inc_with("1.0.0", Position::All, Version::Major, &plugin).unwrap(),
+            "1.0.0"
+        );
+    }
+}
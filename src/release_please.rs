@@ -0,0 +1,100 @@
+//! Helpers for release-please style version manifests (`.release-please-manifest.json`),
+//! which map package paths to their current version.
+
+use std::path::{Component, Path};
+
+use crate::json::{self, Spanned, Value};
+
+/// A parsed version manifest.
+pub struct Manifest<'a> {
+    src: &'a str,
+    doc: Spanned<Value>,
+}
+
+impl<'a> Manifest<'a> {
+    pub fn parse(src: &'a str) -> Result<Self, String> {
+        let doc = json::parse(src)?;
+        if doc.value.as_object().is_none() {
+            return Err("expected an object".to_string());
+        }
+        Ok(Manifest { src, doc })
+    }
+
+    /// Returns the version recorded for `package`.
+    pub fn version(&self, package: &str) -> Option<&str> {
+        self.doc.value.get(package)?.value.as_str()
+    }
+
+    /// Returns the package whose path is the closest ancestor of `file`, which has to be
+    /// relative to the directory of the manifest. The root package is called `.`.
+    pub fn package_for(&self, file: &Path) -> Option<&str> {
+        let file: Vec<Component> = file.components().collect();
+
+        self.doc
+            .value
+            .as_object()?
+            .iter()
+            .map(|(package, _)| {
+                let path: Vec<Component> = Path::new(package)
+                    .components()
+                    .filter(|c| *c != Component::CurDir)
+                    .collect();
+                (package, path)
+            })
+            .filter(|(_, path)| file.starts_with(path))
+            .max_by_key(|(_, path)| path.len())
+            .map(|(package, _)| package.as_str())
+    }
+
+    /// Sets the version of `package`, keeping the rest of the manifest as is.
+    pub fn set(&self, package: &str, version: &str) -> Option<String> {
+        let span = self.doc.value.get(package)?.span.clone();
+        Some(format!(
+            "{}\"{}\"{}",
+            &self.src[..span.start],
+            version,
+            &self.src[span.end..]
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{
+  ".": "1.2.3",
+  "packages/foo": "0.1.0",
+  "packages/foo/bar": "0.0.1"
+}
+"#;
+
+    #[test]
+    fn package_for() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        assert_eq!(manifest.package_for(Path::new("Cargo.toml")), Some("."));
+        assert_eq!(
+            manifest.package_for(Path::new("packages/foo/package.json")),
+            Some("packages/foo")
+        );
+        assert_eq!(
+            manifest.package_for(Path::new("packages/foo/bar/package.json")),
+            Some("packages/foo/bar")
+        );
+        assert_eq!(
+            manifest.package_for(Path::new("packages/foobar/package.json")),
+            Some(".")
+        );
+    }
+
+    #[test]
+    fn set() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        assert_eq!(manifest.version("packages/foo"), Some("0.1.0"));
+        assert_eq!(
+            manifest.set("packages/foo", "0.2.0").unwrap(),
+            MANIFEST.replace("\"0.1.0\"", "\"0.2.0\"")
+        );
+        assert!(manifest.set("packages/baz", "0.2.0").is_none());
+    }
+}
@@ -0,0 +1,69 @@
+//! Reading the tags of RPM spec files and adding `%changelog` entries.
+
+use std::ops::Range;
+
+/// Returns the location of the value of the first `name:` tag (ignoring case) of the
+/// preamble of `content`.
+pub(crate) fn tag(content: &str, name: &str) -> Option<Range<usize>> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let Some((tag, value)) = line.split_once(':') else {
+            continue;
+        };
+        if !tag.eq_ignore_ascii_case(name) {
+            continue;
+        }
+        let value_start = start + tag.len() + 1 + value.len() - value.trim_start().len();
+        return Some(value_start..start + line.trim_end().len());
+    }
+    None
+}
+
+/// Returns the location of the number at the beginning of `Release:` (the `1` of
+/// `1%{?dist}`).
+pub(crate) fn release(content: &str) -> Option<Range<usize>> {
+    let span = tag(content, "Release")?;
+    let len = content[span.clone()]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(span.len());
+    (len > 0).then_some(span.start..span.start + len)
+}
+
+/// Returns `content` with `entry` inserted at the top of `%changelog`.
+pub(crate) fn add_changelog(content: &str, entry: &str) -> Result<String, String> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "%changelog" {
+            let mut out = content[..offset].to_string();
+            if !line.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(entry);
+            out.push_str(&content[offset..]);
+            return Ok(out);
+        }
+    }
+    Err("there is no %changelog".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags() {
+        let spec = "Name: foo\nversion:  1.2.3 \nRelease: 3%{?dist}\n\n%changelog\n* old\n";
+        assert_eq!(&spec[tag(spec, "Version").unwrap()], "1.2.3");
+        assert_eq!(&spec[release(spec).unwrap()], "3");
+        assert_eq!(release("Release: %autorelease\n"), None);
+        assert_eq!(
+            add_changelog(spec, "* new\n\n").unwrap(),
+            spec.replace("* old", "* new\n\n* old")
+        );
+        assert!(add_changelog("Name: foo\n", "* new\n").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Updating the version of the described component in CycloneDX (JSON) and SPDX (JSON or
+//! tag-value) documents.
+
+use std::ops::Range;
+
+use crate::json::{self, Spanned, Value};
+
+/// A parsed SBOM together with the location of the version of its main component.
+pub struct Sbom<'a> {
+    src: &'a str,
+    version: Option<(Range<usize>, String)>,
+    quoted: bool,
+}
+
+impl<'a> Sbom<'a> {
+    pub fn parse(src: &'a str) -> Result<Self, String> {
+        if src.trim_start().starts_with('{') {
+            let doc = json::parse(src)?;
+            let version =
+                if doc.value.get("bomFormat").and_then(|f| f.value.as_str()) == Some("CycloneDX") {
+                    cyclonedx(&doc)
+                } else if doc.value.get("spdxVersion").is_some() {
+                    spdx_json(&doc)
+                } else {
+                    return Err("neither a CycloneDX nor an SPDX document".to_string());
+                };
+
+            let version = version.map(|v| (v.span.clone(), v.value.as_str().unwrap().to_string()));
+            Ok(Sbom {
+                src,
+                version,
+                quoted: true,
+            })
+        } else if src.lines().any(|line| line.starts_with("SPDXVersion:")) {
+            Ok(Sbom {
+                src,
+                version: spdx_tag_value(src),
+                quoted: false,
+            })
+        } else {
+            Err("neither a CycloneDX nor an SPDX document".to_string())
+        }
+    }
+
+    /// Returns the version of the main component.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_ref().map(|(_, version)| version.as_str())
+    }
+
+    /// Returns the document with the version of the main component set to `version`.
+    pub fn set(&self, version: &str) -> Option<String> {
+        let (span, _) = self.version.as_ref()?;
+        let version = if self.quoted {
+            json::quote(version)
+        } else {
+            version.to_string()
+        };
+
+        Some(format!(
+            "{}{}{}",
+            &self.src[..span.start],
+            version,
+            &self.src[span.end..]
+        ))
+    }
+}
+
+/// `metadata.component.version`
+fn cyclonedx(doc: &Spanned<Value>) -> Option<&Spanned<Value>> {
+    let version = doc
+        .value
+        .get("metadata")?
+        .value
+        .get("component")?
+        .value
+        .get("version")?;
+    version.value.as_str().map(|_| version)
+}
+
+/// `versionInfo` of the package the document describes, or of the first package.
+fn spdx_json(doc: &Spanned<Value>) -> Option<&Spanned<Value>> {
+    let packages = doc.value.get("packages")?.value.as_array()?;
+    let described = doc
+        .value
+        .get("documentDescribes")
+        .and_then(|d| d.value.as_array())
+        .and_then(|d| d.first())
+        .and_then(|d| d.value.as_str());
+
+    let package = packages
+        .iter()
+        .find(|p| {
+            described.is_some()
+                && p.value.get("SPDXID").and_then(|id| id.value.as_str()) == described
+        })
+        .or_else(|| packages.first())?;
+
+    let version = package.value.get("versionInfo")?;
+    version.value.as_str().map(|_| version)
+}
+
+/// `PackageVersion` of the package the document describes, or of the first package.
+fn spdx_tag_value(src: &str) -> Option<(Range<usize>, String)> {
+    let described = src.lines().find_map(|line| {
+        let rest = line.strip_prefix("Relationship:")?.trim();
+        let (doc, rest) = rest.split_once(' ')?;
+        let target = rest.strip_prefix("DESCRIBES ")?;
+        (doc == "SPDXRef-DOCUMENT").then(|| target.trim())
+    });
+
+    let mut offset = 0;
+    let mut current = None;
+    let mut first = None;
+    for line in src.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        if let Some(id) = line.strip_prefix("SPDXID:") {
+            current = Some(id.trim());
+        }
+        let Some(version) = line.strip_prefix("PackageVersion:") else {
+            continue;
+        };
+
+        let value = version.trim();
+        let value_start =
+            start + "PackageVersion:".len() + (version.len() - version.trim_start().len());
+        let found = (value_start..value_start + value.len(), value.to_string());
+
+        if described.is_some() && current == described {
+            return Some(found);
+        }
+        first.get_or_insert(found);
+    }
+
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cyclonedx() {
+        let src = r#"{
+  "bomFormat": "CycloneDX",
+  "metadata": { "component": { "name": "foo", "version": "1.0.0" } },
+  "components": [{ "name": "bar", "version": "1.0.0" }]
+}"#;
+        let sbom = Sbom::parse(src).unwrap();
+        assert_eq!(sbom.version(), Some("1.0.0"));
+        assert_eq!(
+            sbom.set("1.1.0").unwrap(),
+            src.replacen("\"1.0.0\"", "\"1.1.0\"", 1)
+        );
+    }
+
+    #[test]
+    fn spdx_json() {
+        let src = r#"{
+  "spdxVersion": "SPDX-2.3",
+  "documentDescribes": ["SPDXRef-foo"],
+  "packages": [
+    { "SPDXID": "SPDXRef-bar", "versionInfo": "2.0.0" },
+    { "SPDXID": "SPDXRef-foo", "versionInfo": "1.0.0" }
+  ]
+}"#;
+        let sbom = Sbom::parse(src).unwrap();
+        assert_eq!(sbom.version(), Some("1.0.0"));
+        assert_eq!(sbom.set("1.1.0").unwrap(), src.replace("1.0.0", "1.1.0"));
+    }
+
+    #[test]
+    fn spdx_tag_value() {
+        let src = "SPDXVersion: SPDX-2.3\nRelationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-foo\n\nPackageName: bar\nSPDXID: SPDXRef-bar\nPackageVersion: 2.0.0\n\nPackageName: foo\nSPDXID: SPDXRef-foo\nPackageVersion:  1.0.0\n";
+        let sbom = Sbom::parse(src).unwrap();
+        assert_eq!(sbom.version(), Some("1.0.0"));
+        assert_eq!(sbom.set("1.1.0").unwrap(), src.replace("1.0.0", "1.1.0"));
+    }
+
+    #[test]
+    fn unknown() {
+        assert!(Sbom::parse("{}").is_err());
+        assert!(Sbom::parse("foo").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! Selection and transformation of versions by [rhai](https://rhai.rs) scripts.
+//!
+//! A script may define any of the following functions:
+//!
+//! - `select(m)` returns whether the matched version should be bumped.
+//! - `transform(m)` returns the new version, `m.next` holds the one verinc would use.
+//!   Returning nothing keeps `m.next`.
+//! - `after(changes)` is called once the file has been written.
+//!
+//! `m` is a map with `position`, `version`, `line` (starting at 1), `text` (the whole
+//! line) and `next`. Every change is a map with `position`, `old` and `new`.
+
+use std::path::Path;
+
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+use crate::{Change, Match, Position, Selector, Version};
+
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let engine = Engine::new();
+        let ast = engine
+            .compile_file(path.to_path_buf())
+            .map_err(|err| err.to_string())?;
+        Ok(Script { engine, ast })
+    }
+
+    pub fn compile(src: &str) -> Result<Self, String> {
+        let engine = Engine::new();
+        let ast = engine.compile(src).map_err(|err| err.to_string())?;
+        Ok(Script { engine, ast })
+    }
+
+    fn defines(&self, name: &str) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name)
+    }
+
+    fn call<T: Clone + 'static>(&self, name: &str, arg: Dynamic) -> Result<T, String> {
+        self.engine
+            .call_fn::<T>(&mut Scope::new(), &self.ast, name, (arg,))
+            .map_err(|err| format!("{name}: {err}"))
+    }
+
+    fn replace(&self, m: &Match, next: String) -> Result<Option<String>, String> {
+        let mut map = Map::new();
+        map.insert("position".into(), (m.position as i64).into());
+        map.insert("version".into(), m.version.to_string().into());
+        map.insert("line".into(), (m.line as i64).into());
+        map.insert("text".into(), m.text.to_string().into());
+        map.insert("next".into(), next.clone().into());
+        let map = Dynamic::from_map(map);
+
+        if self.defines("select") && !self.call::<bool>("select", map.clone())? {
+            return Ok(None);
+        }
+
+        if !self.defines("transform") {
+            return Ok(Some(next));
+        }
+
+        let new = self.call::<Dynamic>("transform", map)?;
+        if new.is_unit() {
+            return Ok(Some(next));
+        }
+        new.into_string()
+            .map(Some)
+            .map_err(|ty| format!("transform: expected a string, got {ty}"))
+    }
+
+    /// Like [`inc`](crate::inc) but lets the script decide which of the versions at
+    /// `position` are bumped and to what.
+    pub fn inc(
+        &self,
+        hay: &str,
+        selector: &Selector,
+        position: Position,
+        version: Version,
+    ) -> Result<String, String> {
+        let mut failure = None;
+
+        let result = selector.inc_map(hay, position, &mut |m| {
+            if failure.is_some() {
+                return None;
+            }
+
+            let next = selector.next(m.version, version.clone()).ok()?;
+            self.replace(m, next).unwrap_or_else(|err| {
+                failure = Some(err);
+                None
+            })
+        });
+
+        match failure {
+            Some(err) => Err(err),
+            None => Ok(result?),
+        }
+    }
+
+    /// Calls the `after` hook, if any.
+    pub fn after(&self, changes: &[Change]) -> Result<(), String> {
+        if !self.defines("after") {
+            return Ok(());
+        }
+
+        let changes: Array = changes
+            .iter()
+            .map(|change| {
+                let mut map = Map::new();
+                map.insert("position".into(), (change.position as i64).into());
+                map.insert("old".into(), change.old.clone().into());
+                map.insert("new".into(), change.new.clone().into());
+                Dynamic::from_map(map)
+            })
+            .collect();
+
+        self.call::<Dynamic>("after", Dynamic::from_array(changes))
+            .map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_and_transform() {
+        let script = Script::compile(
+            r#"
+            fn select(m) { m.text.starts_with("pkgver=") }
+            fn transform(m) { if m.line == 3 { "0.0.0" } }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            script
+                .inc(
+                    "url=1.0.0\npkgver=1.0.0\npkgver=2.0.0",
+                    &Selector::default(),
+                    Position::All,
+                    Version::Minor
+                )
+                .unwrap(),
+            "url=1.0.0\npkgver=1.1.0\npkgver=0.0.0"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let script = Script::compile("fn transform(m) { 42 }").unwrap();
+        assert!(script
+            .inc("1.0.0", &Selector::default(), Position::All, Version::Patch)
+            .is_err());
+
+        let script =
+            Script::compile("fn after(changes) { if changes.len() != 1 { throw \"no\" } }")
+                .unwrap();
+        let change = Change {
+            position: 0,
+            span: 0..5,
+            old: "1.0.0".to_string(),
+            new: "1.0.1".to_string(),
+        };
+        assert!(script.after(&[change]).is_ok());
+        assert!(script.after(&[]).is_err());
+    }
+}
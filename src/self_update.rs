@@ -0,0 +1,202 @@
+//! Updating of a verinc binary installed outside of a package manager from the releases
+//! of the project on GitHub.
+//!
+//! Every release carries a binary per platform named `verinc-<arch>-<os>` (see
+//! [`asset_name`]), along with `<binary>.sha256` holding its checksum and optionally a
+//! detached signature.
+
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    digest::Limits,
+    http, json,
+    token::{self, Service},
+    upstream,
+};
+
+/// The GitHub repository the releases are published in.
+pub const REPOSITORY: &str = "jiripospisil/verinc";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// The version without the `v` prefix of the tag.
+    pub version: String,
+    /// URL of the binary for this platform.
+    pub url: String,
+    /// URL of the checksum of the binary.
+    pub checksum_url: String,
+}
+
+/// Returns the name of the binary for the platform this verinc was built for, e.g.
+/// `verinc-x86_64-linux`.
+pub fn asset_name() -> String {
+    let suffix = if cfg!(windows) { ".exe" } else { "" };
+    format!("verinc-{}-{}{suffix}", env::consts::ARCH, env::consts::OS)
+}
+
+/// Returns the newest release along with the binary for this platform.
+pub fn latest() -> Result<Release, String> {
+    let url = format!("https://api.github.com/repos/{REPOSITORY}/releases/latest");
+    // Anonymous requests work too, a token only raises the rate limit.
+    let mut headers = vec![("Accept", "application/vnd.github+json".to_string())];
+    if let Ok(token) = token::get(Service::GitHub) {
+        headers.push(("Authorization", format!("Bearer {token}")));
+    }
+
+    let response = http::get(&url, &headers)?;
+    if response.status != 200 {
+        return Err(format!("{url}: HTTP {}", response.status));
+    }
+    release(&response.body, &asset_name())
+}
+
+fn release(body: &str, name: &str) -> Result<Release, String> {
+    let doc = json::parse(body)?;
+    let tag = doc
+        .value
+        .get("tag_name")
+        .and_then(|tag| tag.value.as_str())
+        .ok_or("the release has no tag")?;
+
+    let assets: Vec<_> = doc
+        .value
+        .get("assets")
+        .and_then(|assets| assets.value.as_array())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|asset| {
+            let name = asset.value.get("name")?.value.as_str()?;
+            let url = asset.value.get("browser_download_url")?.value.as_str()?;
+            Some((name, url))
+        })
+        .collect();
+    let find = |name: &str| {
+        assets
+            .iter()
+            .find(|(asset, _)| *asset == name)
+            .map(|(_, url)| url.to_string())
+    };
+
+    Ok(Release {
+        version: tag.strip_prefix('v').unwrap_or(tag).to_string(),
+        url: find(name).ok_or_else(|| format!("release {tag} has no binary {name}"))?,
+        checksum_url: find(&format!("{name}.sha256"))
+            .ok_or_else(|| format!("release {tag} has no checksum of {name}"))?,
+    })
+}
+
+/// Returns whether `version` is newer than `current`.
+pub fn is_newer(version: &str, current: &str) -> bool {
+    version != current && upstream::newest([current, version]) == Some(version)
+}
+
+/// Downloads the checksum of the binary of `release`.
+pub fn checksum(release: &Release) -> Result<String, String> {
+    let response = http::get(&release.checksum_url, &[])?;
+    if response.status != 200 {
+        return Err(format!(
+            "{}: HTTP {}",
+            release.checksum_url, response.status
+        ));
+    }
+    parse_checksum(&response.body)
+        .ok_or_else(|| format!("{}: no sha256 checksum", release.checksum_url))
+}
+
+/// Returns the checksum of a `sha256sum` line (or of a bare checksum).
+fn parse_checksum(content: &str) -> Option<String> {
+    let checksum = content.split_whitespace().next()?;
+    (checksum.len() == 64 && checksum.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| checksum.to_ascii_lowercase())
+}
+
+/// Downloads `url` to `path` and returns its sha256.
+pub fn download(url: &str, path: &Path) -> Result<String, String> {
+    let limits = Limits::default();
+    http::retry(limits.retries, || {
+        let mut file = File::create(path).map_err(|err| {
+            http::Error::Fatal(format!("cannot create '{}': {err}", path.display()))
+        })?;
+
+        let mut hasher = Sha256::new();
+        let mut result = Ok(());
+        http::stream(url, limits.max_size, |chunk| {
+            hasher.update(chunk);
+            if result.is_ok() {
+                result = file.write_all(chunk);
+            }
+        })?;
+        result.map_err(|err| {
+            http::Error::Fatal(format!("cannot write '{}': {err}", path.display()))
+        })?;
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect())
+    })
+    .map_err(|err| format!("{url}: {err}"))
+}
+
+/// Replaces the executable `exe` with `new`, which should be in the same directory so
+/// that the rename is atomic. The permissions of `exe` are kept.
+pub fn replace(new: &Path, exe: &Path) -> Result<(), String> {
+    let permissions = fs::metadata(exe)
+        .map_err(|err| format!("cannot read '{}': {err}", exe.display()))?
+        .permissions();
+    fs::set_permissions(new, permissions)
+        .map_err(|err| format!("cannot set the permissions of '{}': {err}", new.display()))?;
+
+    // A running executable cannot be overwritten on Windows but it can be moved away.
+    if cfg!(windows) {
+        let old = exe.with_extension("old.exe");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old)
+            .map_err(|err| format!("cannot move '{}' away: {err}", exe.display()))?;
+    }
+    fs::rename(new, exe).map_err(|err| format!("cannot replace '{}': {err}", exe.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases() {
+        let body = r#"{
+            "tag_name": "v0.3.0",
+            "assets": [
+                {"name": "verinc-x86_64-linux", "browser_download_url": "https://example.com/bin"},
+                {"name": "verinc-x86_64-linux.sha256", "browser_download_url": "https://example.com/sum"}
+            ]
+        }"#;
+        assert_eq!(
+            release(body, "verinc-x86_64-linux").unwrap(),
+            Release {
+                version: "0.3.0".to_string(),
+                url: "https://example.com/bin".to_string(),
+                checksum_url: "https://example.com/sum".to_string(),
+            }
+        );
+        assert!(release(body, "verinc-aarch64-macos").is_err());
+
+        assert!(is_newer("0.3.0", "0.2.9"));
+        assert!(!is_newer("0.2.9", "0.2.9"));
+        assert!(!is_newer("0.10.0", "0.11.0"));
+        assert!(!is_newer("nightly", "0.2.9"));
+
+        let sum = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(
+            parse_checksum(&format!("{sum}  verinc-x86_64-linux\n")),
+            Some(sum.to_ascii_lowercase())
+        );
+        assert_eq!(parse_checksum("<html>"), None);
+    }
+}
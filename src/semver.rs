@@ -0,0 +1,218 @@
+//! Parsed `X.Y.Z[-pre][+build]` versions, for working with versions rather than the
+//! text they're found in. The component to bump is [`crate::Version`].
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::VerincError;
+
+/// A version as described by [Semantic Versioning](https://semver.org).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// The pre-release without the `-`, like `rc.1`.
+    pub pre: Option<String>,
+    /// The build metadata without the `+`.
+    pub build: Option<String>,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: None,
+            build: None,
+        }
+    }
+
+    /// Returns the version bumped the same way [`crate::inc`] bumps it in a text, so
+    /// `1.3.0-rc.1` becomes `1.3.0` with [`crate::Version::Minor`] and `1.3.0-rc.2`
+    /// with [`crate::Version::Pre`]. The build metadata is kept.
+    pub fn bump(&self, level: crate::Version) -> Result<Version, VerincError> {
+        crate::next(&self.to_string(), level)?.parse()
+    }
+}
+
+impl FromStr for Version {
+    type Err = VerincError;
+
+    /// Parses a version without a `v` in front.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || VerincError::InvalidVersion(s.to_string());
+        let (rest, build) = match s.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (s, None),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (rest, None),
+        };
+
+        let number = |part: &str| {
+            let valid = !part.is_empty()
+                && part.bytes().all(|b| b.is_ascii_digit())
+                && (part == "0" || !part.starts_with('0'));
+            valid.then(|| part.parse().ok()).flatten()
+        };
+        let mut parts = core.split('.').map(number);
+        let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        let identifiers = |text: &str, numeric_zeros: bool| {
+            text.split('.').all(|id| {
+                !id.is_empty()
+                    && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                    && (numeric_zeros
+                        || id == "0"
+                        || !id.starts_with('0')
+                        || !id.bytes().all(|b| b.is_ascii_digit()))
+            })
+        };
+        if pre.is_some_and(|pre| !identifiers(pre, false))
+            || build.is_some_and(|build| !identifiers(build, true))
+        {
+            return Err(invalid());
+        }
+
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre: pre.map(str::to_string),
+            build: build.map(str::to_string),
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{build}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    /// The precedence of Semantic Versioning, a pre-release comes before the release.
+    /// The build metadata doesn't take part in it, it only orders versions which would
+    /// be equal otherwise.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => pre_cmp(a, b),
+            })
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares the pre-releases identifier by identifier, numeric ones by their value and
+/// before the alphanumeric ones.
+fn pre_cmp(a: &str, b: &str) -> Ordering {
+    let numeric = |id: &str| id.parse::<u64>().ok();
+    let (mut a, mut b) = (a.split('.'), b.split('.'));
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (numeric(x), numeric(y)) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => x.cmp(y),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() {
+        let version: Version = "1.2.3-rc.1+build.5".parse().unwrap();
+        assert_eq!(
+            version,
+            Version {
+                pre: Some("rc.1".to_string()),
+                build: Some("build.5".to_string()),
+                ..Version::new(1, 2, 3)
+            }
+        );
+        assert_eq!(version.to_string(), "1.2.3-rc.1+build.5");
+        for invalid in [
+            "1.2", "1.2.3.4", "v1.2.3", "01.2.3", "1.2.3-", "1.2.3-01", "1.2.x",
+        ] {
+            assert!(invalid.parse::<Version>().is_err(), "{invalid}");
+        }
+
+        let mut sorted: Vec<Version> = [
+            "1.0.0",
+            "1.0.0-rc.1",
+            "1.0.0-beta.11",
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-beta.2",
+            "0.9.10",
+            "1.0.0-alpha.beta",
+            "0.9.9",
+        ]
+        .iter()
+        .map(|v| v.parse().unwrap())
+        .collect();
+        sorted.sort();
+        let sorted: Vec<_> = sorted.iter().map(Version::to_string).collect();
+        assert_eq!(
+            sorted,
+            [
+                "0.9.9",
+                "0.9.10",
+                "1.0.0-alpha",
+                "1.0.0-alpha.1",
+                "1.0.0-alpha.beta",
+                "1.0.0-beta.2",
+                "1.0.0-beta.11",
+                "1.0.0-rc.1",
+                "1.0.0",
+            ]
+        );
+
+        let bump = |v: &str, level| {
+            v.parse::<Version>()
+                .unwrap()
+                .bump(level)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(bump("1.2.3", crate::Version::Minor), "1.3.0");
+        assert_eq!(bump("1.3.0-rc.1", crate::Version::Minor), "1.3.0");
+        assert_eq!(bump("1.3.0-rc.1", crate::Version::Pre), "1.3.0-rc.2");
+        assert_eq!(bump("1.2.3+abc", crate::Version::Major), "2.0.0+abc");
+        assert!(Version::new(1, 0, 0).bump(crate::Version::Epoch).is_err());
+    }
+}
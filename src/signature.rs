@@ -0,0 +1,237 @@
+//! Verification of detached signatures of upstream release artifacts with GnuPG, minisign
+//! or signify.
+
+use std::{
+    env,
+    fs::{self, DirBuilder, OpenOptions},
+    hash::{BuildHasher, RandomState},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use crate::{digest::Limits, http, parallel};
+
+/// How the signature is checked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verifier {
+    /// `gpg --verify`, against the default keyring or the given one.
+    Gpg { keyring: Option<PathBuf> },
+    /// `minisign -V` with a public key (either the key itself or a file containing it).
+    Minisign { key: String },
+    /// `signify -V` with a public key file.
+    Signify { key: PathBuf },
+}
+
+impl Verifier {
+    /// Parses `gpg`, `gpg:<keyring>`, `minisign:<key>` or `signify:<key file>`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (tool, key) = match spec.split_once(':') {
+            Some((tool, key)) => (tool, Some(key)),
+            None => (spec, None),
+        };
+
+        match (tool, key) {
+            ("gpg", keyring) => Ok(Verifier::Gpg {
+                keyring: keyring.map(PathBuf::from),
+            }),
+            ("minisign", Some(key)) => Ok(Verifier::Minisign {
+                key: key.to_string(),
+            }),
+            ("signify", Some(key)) => Ok(Verifier::Signify {
+                key: PathBuf::from(key),
+            }),
+            ("minisign" | "signify", None) => Err(format!("missing public key in '{spec}'")),
+            _ => Err(format!("unknown signature verifier '{tool}'")),
+        }
+    }
+
+    /// The conventional extension of signatures checked by this verifier.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Verifier::Gpg { .. } => ".asc",
+            Verifier::Minisign { .. } => ".minisig",
+            Verifier::Signify { .. } => ".sig",
+        }
+    }
+
+    fn command(&self, artifact: &Path, signature: &Path) -> Command {
+        match self {
+            Verifier::Gpg { keyring } => {
+                let mut cmd = Command::new("gpg");
+                cmd.args(["--batch", "--quiet"]);
+                if let Some(keyring) = keyring {
+                    cmd.arg("--no-default-keyring")
+                        .arg("--keyring")
+                        .arg(keyring);
+                }
+                cmd.arg("--verify").arg(signature).arg(artifact);
+                cmd
+            }
+            Verifier::Minisign { key } => {
+                let mut cmd = Command::new("minisign");
+                cmd.arg("-V").arg("-q").arg("-m").arg(artifact);
+                cmd.arg("-x").arg(signature);
+                if Path::new(key).is_file() {
+                    cmd.arg("-p").arg(key);
+                } else {
+                    cmd.arg("-P").arg(key);
+                }
+                cmd
+            }
+            Verifier::Signify { key } => {
+                let mut cmd = Command::new("signify");
+                cmd.arg("-V").arg("-q").arg("-p").arg(key);
+                cmd.arg("-x").arg(signature).arg("-m").arg(artifact);
+                cmd
+            }
+        }
+    }
+}
+
+/// A file in its own private directory in the temporary directory, both are removed when
+/// dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn download(url: &str, name: &str) -> Result<Self, String> {
+        let dir =
+            private_dir().map_err(|err| format!("cannot create a temporary directory: {err}"))?;
+        let temp = TempFile(dir.join(name));
+        let limits = Limits::default();
+
+        http::retry(limits.retries, || {
+            // Nobody else can write into the directory, only a previous attempt left the file.
+            let _ = fs::remove_file(&temp.0);
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temp.0)
+                .map_err(|err| {
+                    http::Error::Fatal(format!("cannot create '{}': {err}", temp.0.display()))
+                })?;
+
+            let mut result = Ok(());
+            http::stream(url, limits.max_size, |chunk| {
+                if result.is_ok() {
+                    result = file.write_all(chunk);
+                }
+            })?;
+            result.map_err(|err| {
+                http::Error::Fatal(format!("cannot write '{}': {err}", temp.0.display()))
+            })
+        })
+        .map_err(|err| format!("{url}: {err}"))?;
+
+        Ok(temp)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+        if let Some(dir) = self.0.parent() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Creates a randomly named directory in the temporary directory accessible only by the
+/// current user.
+fn private_dir() -> io::Result<PathBuf> {
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+
+    let state = RandomState::new();
+    let mut attempt = 0u32;
+    loop {
+        let random = state.hash_one(attempt);
+        let dir = env::temp_dir().join(format!("verinc-{}-{random:016x}", process::id()));
+        match builder.create(&dir) {
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            result => return result.map(|()| dir),
+        }
+    }
+}
+
+/// Downloads the artifact at `url` and its signature at `signature_url` and checks the
+/// signature with `verifier`.
+pub fn verify(url: &str, signature_url: &str, verifier: &Verifier) -> Result<(), String> {
+    let downloads = [(url, "artifact"), (signature_url, "signature")];
+    let mut downloads =
+        parallel::map(&downloads, |(url, name)| TempFile::download(url, name)).into_iter();
+    let artifact = downloads.next().unwrap()?;
+    let signature = downloads.next().unwrap()?;
+    check(url, &artifact.0, &signature.0, verifier)
+}
+
+/// Downloads the signature at `signature_url` of the local file `path` and checks it with
+/// `verifier`.
+pub fn verify_file(path: &Path, signature_url: &str, verifier: &Verifier) -> Result<(), String> {
+    let signature = TempFile::download(signature_url, "signature")?;
+    check(&path.display().to_string(), path, &signature.0, verifier)
+}
+
+fn check(name: &str, artifact: &Path, signature: &Path, verifier: &Verifier) -> Result<(), String> {
+    let output = verifier
+        .command(artifact, signature)
+        .output()
+        .map_err(|err| format!("cannot verify the signature: {err}"))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!(
+            "bad signature of {name}: {}",
+            stderr
+                .trim()
+                .lines()
+                .last()
+                .unwrap_or("verification failed")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs() {
+        assert_eq!(
+            Verifier::parse("gpg").unwrap(),
+            Verifier::Gpg { keyring: None }
+        );
+        assert_eq!(
+            Verifier::parse("gpg:keys.gpg").unwrap(),
+            Verifier::Gpg {
+                keyring: Some(PathBuf::from("keys.gpg"))
+            }
+        );
+        assert_eq!(
+            Verifier::parse("minisign:RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3")
+                .unwrap()
+                .extension(),
+            ".minisig"
+        );
+        assert!(Verifier::parse("signify").is_err());
+        assert!(Verifier::parse("pgp").is_err());
+    }
+
+    #[test]
+    fn private_dirs() {
+        let (first, second) = (private_dir().unwrap(), private_dir().unwrap());
+        assert_ne!(first, second);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&first).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        drop(TempFile(first.join("signature")));
+        assert!(!first.exists());
+        fs::remove_dir(second).unwrap();
+    }
+}
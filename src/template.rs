@@ -0,0 +1,48 @@
+//! Rendering of the small `{name}` templates used for commit messages, pull requests and
+//! notifications.
+
+/// Replaces `{name}` in `template` with the value of the variable `name`. Unknown
+/// placeholders are left as they are.
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let var = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (end, value))
+        });
+
+        match var {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables() {
+        let vars = [("old", "1.0.0"), ("new", "1.1.0")];
+        assert_eq!(render("bump {old} to {new}", &vars), "bump 1.0.0 to 1.1.0");
+        assert_eq!(render("{new}{new}", &vars), "1.1.01.1.0");
+        assert_eq!(render("{unknown} {new} {", &vars), "{unknown} 1.1.0 {");
+    }
+}
@@ -0,0 +1,434 @@
+//! A minimal TOML reader which remembers where every value is located in the source so that
+//! values can be rewritten in place without disturbing the rest of the document.
+
+use std::ops::Range;
+
+#[derive(Debug)]
+pub(crate) struct Spanned<T> {
+    pub value: T,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    /// Floats, booleans, dates and times. Only the span is kept.
+    Other,
+    Array(Vec<Spanned<Value>>),
+    Table(Vec<(Vec<String>, Spanned<Value>)>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Looks up `key` in an inline table.
+    pub fn get(&self, key: &str) -> Option<&Spanned<Value>> {
+        match self {
+            Value::Table(entries) => entries
+                .iter()
+                .find(|(k, _)| k.len() == 1 && k[0] == key)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Header {
+    pub path: Vec<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Entry {
+    /// Index into `Document::headers`, `None` for keys before the first header.
+    pub header: Option<usize>,
+    pub key: Vec<String>,
+    pub value: Spanned<Value>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Document {
+    pub headers: Vec<Header>,
+    pub entries: Vec<Entry>,
+}
+
+impl Document {
+    /// Returns the table path an entry belongs to.
+    pub fn table(&self, entry: &Entry) -> &[String] {
+        entry
+            .header
+            .map(|idx| self.headers[idx].path.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Finds `key` in the (non-array) table `table`.
+    pub fn get(&self, table: &[&str], key: &str) -> Option<&Spanned<Value>> {
+        self.entries
+            .iter()
+            .find(|e| self.table(e) == table && e.key.len() == 1 && e.key[0] == key)
+            .map(|e| &e.value)
+    }
+}
+
+pub(crate) fn parse(src: &str) -> Result<Document, String> {
+    let mut parser = Parser {
+        src,
+        bytes: src.as_bytes(),
+        pos: 0,
+    };
+    parser.document()
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn document(&mut self) -> Result<Document, String> {
+        let mut doc = Document {
+            headers: Vec::new(),
+            entries: Vec::new(),
+        };
+
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => return Ok(doc),
+                Some(b'[') => {
+                    let array = self.bytes.get(self.pos + 1) == Some(&b'[');
+                    self.pos += if array { 2 } else { 1 };
+                    self.skip_ws();
+                    let path = self.key()?;
+                    self.skip_ws();
+                    self.expect(b']')?;
+                    if array {
+                        self.expect(b']')?;
+                    }
+                    doc.headers.push(Header { path });
+                }
+                Some(_) => {
+                    let key = self.key()?;
+                    self.skip_ws();
+                    self.expect(b'=')?;
+                    self.skip_ws();
+                    let value = self.value()?;
+                    doc.entries.push(Entry {
+                        header: doc.headers.len().checked_sub(1),
+                        key,
+                        value,
+                    });
+                }
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn error(&self, msg: &str) -> String {
+        let line = self.src[..self.pos].matches('\n').count() + 1;
+        format!("line {line}: {msg}")
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some(b'#') {
+            while !matches!(self.peek(), None | Some(b'\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Skips whitespace, comments and newlines.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_ws();
+            self.skip_comment();
+            match self.peek() {
+                Some(b'\n' | b'\r') => self.pos += 1,
+                _ => return,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_ws();
+        self.skip_comment();
+        match self.peek() {
+            None | Some(b'\n' | b'\r') => Ok(()),
+            _ => Err(self.error("expected end of line")),
+        }
+    }
+
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_ws();
+            let part = match self.peek() {
+                Some(b'"') => self.basic_string()?,
+                Some(b'\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected key"));
+                    }
+                    self.src[start..self.pos].to_string()
+                }
+            };
+            parts.push(part);
+            self.skip_ws();
+            if self.peek() == Some(b'.') {
+                self.pos += 1;
+            } else {
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Spanned<Value>, String> {
+        let start = self.pos;
+        let value = match self.peek() {
+            Some(b'"') if self.src[self.pos..].starts_with("\"\"\"") => {
+                Value::String(self.multiline_string("\"\"\"")?)
+            }
+            Some(b'\'') if self.src[self.pos..].starts_with("'''") => {
+                Value::String(self.multiline_string("'''")?)
+            }
+            Some(b'"') => Value::String(self.basic_string()?),
+            Some(b'\'') => Value::String(self.literal_string()?),
+            Some(b'[') => self.array()?,
+            Some(b'{') => self.inline_table()?,
+            _ => {
+                self.bare();
+                // Date and time may be separated by a space.
+                if self.pos - start == 10
+                    && self.bytes[start + 4] == b'-'
+                    && self.peek() == Some(b' ')
+                    && self.bytes.get(self.pos + 1).is_some_and(u8::is_ascii_digit)
+                {
+                    self.pos += 1;
+                    self.bare();
+                }
+                if start == self.pos {
+                    return Err(self.error("expected value"));
+                }
+                match self.src[start..self.pos].replace('_', "").parse() {
+                    Ok(n) => Value::Integer(n),
+                    Err(_) => Value::Other,
+                }
+            }
+        };
+
+        Ok(Spanned {
+            value,
+            span: start..self.pos,
+        })
+    }
+
+    fn bare(&mut self) {
+        while !matches!(
+            self.peek(),
+            None | Some(b' ' | b'\t' | b',' | b']' | b'}' | b'#' | b'\n' | b'\r')
+        ) {
+            self.pos += 1;
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let Some(ch) = self.src[self.pos..].chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += ch.len_utf8();
+            match ch {
+                '"' => return Ok(out),
+                '\n' => return Err(self.error("unterminated string")),
+                '\\' => out.push(self.escape()?),
+                _ => out.push(ch),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        let Some(ch) = self.peek() else {
+            return Err(self.error("unterminated string"));
+        };
+        self.pos += 1;
+        Ok(match ch {
+            b'b' => '\u{8}',
+            b't' => '\t',
+            b'n' => '\n',
+            b'f' => '\u{c}',
+            b'r' => '\r',
+            b'"' => '"',
+            b'\\' => '\\',
+            b'u' | b'U' => {
+                let len = if ch == b'u' { 4 } else { 8 };
+                let hex = self
+                    .src
+                    .get(self.pos..self.pos + len)
+                    .ok_or_else(|| self.error("invalid escape"))?;
+                self.pos += len;
+                u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid escape"))?
+            }
+            _ => return Err(self.error("invalid escape")),
+        })
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.expect(b'\'')?;
+        let start = self.pos;
+        while !matches!(self.peek(), None | Some(b'\'' | b'\n')) {
+            self.pos += 1;
+        }
+        let out = self.src[start..self.pos].to_string();
+        self.expect(b'\'')?;
+        Ok(out)
+    }
+
+    fn multiline_string(&mut self, delim: &str) -> Result<String, String> {
+        self.pos += delim.len();
+        let Some(len) = self.src[self.pos..].find(delim) else {
+            return Err(self.error("unterminated string"));
+        };
+        let raw = &self.src[self.pos..self.pos + len];
+        self.pos += len + delim.len();
+        let raw = raw.strip_prefix('\n').unwrap_or(raw);
+        Ok(raw.to_string())
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {}
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        self.expect(b'{')?;
+        let mut entries = Vec::new();
+        loop {
+            self.skip_ws();
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                return Ok(Value::Table(entries));
+            }
+            let key = self.key()?;
+            self.skip_ws();
+            self.expect(b'=')?;
+            self.skip_ws();
+            entries.push((key, self.value()?));
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {}
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans() {
+        let src = "name = \"foo\" # comment\n[dependencies]\nbar = { version = \"1.0\", path = \"../bar\" }\n";
+        let doc = parse(src).unwrap();
+
+        let name = doc.get(&[], "name").unwrap();
+        assert_eq!(name.value.as_str(), Some("foo"));
+        assert_eq!(&src[name.span.clone()], "\"foo\"");
+
+        let bar = doc.get(&["dependencies"], "bar").unwrap();
+        let version = bar.value.get("version").unwrap();
+        assert_eq!(version.value.as_str(), Some("1.0"));
+        assert_eq!(&src[version.span.clone()], "\"1.0\"");
+    }
+
+    #[test]
+    fn arrays_and_headers() {
+        let src = "[workspace]\nmembers = [\n  \"a\", # first\n  'b/*',\n]\n\n[[bin]]\nname = \"x\"\n[[bin]]\nname = \"y\"\n";
+        let doc = parse(src).unwrap();
+
+        let Value::Array(members) = &doc.get(&["workspace"], "members").unwrap().value else {
+            panic!("not an array");
+        };
+        let members: Vec<_> = members.iter().filter_map(|m| m.value.as_str()).collect();
+        assert_eq!(members, ["a", "b/*"]);
+
+        assert_eq!(doc.headers.len(), 3);
+        assert_eq!(doc.entries[2].header, Some(2));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse("name = ").is_err());
+        assert!(parse("name = \"foo").is_err());
+        assert!(parse("[package\n").is_err());
+        assert!(parse("a = 1 b = 2").is_err());
+        assert!(parse("when = 1979-05-27 07:32:00Z").is_ok());
+        assert_eq!(
+            parse("n = 1_000")
+                .unwrap()
+                .get(&[], "n")
+                .unwrap()
+                .value
+                .as_integer(),
+            Some(1000)
+        );
+    }
+}
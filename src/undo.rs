@@ -0,0 +1,239 @@
+//! The undo log of the bumps, `.verinc/undo` in the current directory.
+//!
+//! Each line records a part of a written file: the bump it belongs to, the (absolute)
+//! path, the byte span of the new text and the old and the new text. Only the last
+//! few bumps are kept.
+
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::diff;
+
+/// The log, relative to the current directory.
+pub const PATH: &str = ".verinc/undo";
+
+/// How many bumps are kept in the log.
+const KEEP: usize = 10;
+
+/// A part of a file replaced by a bump.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub bump: String,
+    pub file: PathBuf,
+    /// The span of `new` in the written file.
+    pub span: Range<usize>,
+    pub old: String,
+    pub new: String,
+}
+
+/// Returns the edits turning `old` into `new` in `file`.
+pub fn edits(bump: &str, file: &Path, old: &str, new: &str) -> Vec<Edit> {
+    diff::hunks(old, new)
+        .into_iter()
+        .map(|(old_range, new_range)| {
+            let (a, b) = (&old[old_range], &new[new_range.clone()]);
+            // Just the part of the lines which changed.
+            let prefix = common(a.chars(), b.chars());
+            let suffix = common(a[prefix..].chars().rev(), b[prefix..].chars().rev());
+            Edit {
+                bump: bump.to_string(),
+                file: file.to_path_buf(),
+                span: new_range.start + prefix..new_range.end - suffix,
+                old: a[prefix..a.len() - suffix].to_string(),
+                new: b[prefix..b.len() - suffix].to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the length in bytes of the common start of `a` and `b`.
+fn common(a: impl Iterator<Item = char>, b: impl Iterator<Item = char>) -> usize {
+    a.zip(b)
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| x.len_utf8())
+        .sum()
+}
+
+/// Returns `content` with the `edits` (of one file, in order) reverted. Fails if any of
+/// the new texts is not there anymore.
+pub fn revert(content: &str, edits: &[&Edit]) -> Result<String, String> {
+    let mut out = content.to_string();
+    for edit in edits.iter().rev() {
+        if out.get(edit.span.clone()) != Some(edit.new.as_str()) {
+            return Err(format!(
+                "'{}' has changed since the bump",
+                edit.file.display()
+            ));
+        }
+        out.replace_range(edit.span.clone(), &edit.old);
+    }
+    Ok(out)
+}
+
+/// The undo log at `path`.
+#[derive(Debug, Default)]
+pub struct Log {
+    path: PathBuf,
+    edits: Vec<Edit>,
+}
+
+impl Log {
+    /// Reads the log at [`PATH`] in `dir`, which is empty if there is none yet.
+    pub fn open(dir: &Path) -> Result<Log, String> {
+        let path = dir.join(PATH);
+        let edits = match fs::read_to_string(&path) {
+            Ok(src) => parse(&src).ok_or_else(|| format!("'{}' is corrupt", path.display()))?,
+            Err(_) => Vec::new(),
+        };
+        Ok(Log { path, edits })
+    }
+
+    /// Adds the `edits` of a bump, dropping the oldest bumps above the limit.
+    pub fn record(&mut self, edits: Vec<Edit>) {
+        self.edits.extend(edits);
+        let mut bumps: Vec<_> = self.edits.iter().map(|edit| &edit.bump).collect();
+        bumps.dedup();
+        if let Some(oldest) = bumps
+            .len()
+            .checked_sub(KEEP + 1)
+            .map(|idx| bumps[idx].clone())
+        {
+            let first = self
+                .edits
+                .iter()
+                .rposition(|edit| edit.bump == oldest)
+                .unwrap();
+            self.edits.drain(..=first);
+        }
+    }
+
+    /// Removes and returns the edits of the last bump.
+    pub fn pop(&mut self) -> Vec<Edit> {
+        let Some(last) = self.edits.last().map(|edit| edit.bump.clone()) else {
+            return Vec::new();
+        };
+        let first = self
+            .edits
+            .iter()
+            .rposition(|edit| edit.bump != last)
+            .map_or(0, |idx| idx + 1);
+        self.edits.split_off(first)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let write = || {
+            let dir = self.path.parent().unwrap_or(Path::new(""));
+            if !dir.exists() {
+                fs::create_dir_all(dir)?;
+                // Keeps the log out of the repository.
+                fs::write(dir.join(".gitignore"), "*\n")?;
+            }
+            fs::write(&self.path, render(&self.edits))
+        };
+        write().map_err(|err| format!("cannot write '{}': {err}", self.path.display()))
+    }
+}
+
+fn render(edits: &[Edit]) -> String {
+    edits
+        .iter()
+        .map(|edit| {
+            format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\n",
+                escape(&edit.bump),
+                escape(&edit.file.to_string_lossy()),
+                edit.span.start,
+                edit.span.end,
+                escape(&edit.old),
+                escape(&edit.new)
+            )
+        })
+        .collect()
+}
+
+fn parse(src: &str) -> Option<Vec<Edit>> {
+    src.lines()
+        .map(|line| {
+            let fields: Vec<_> = line.split('\t').collect();
+            let [bump, file, start, end, old, new] = fields[..] else {
+                return None;
+            };
+            Some(Edit {
+                bump: unescape(bump)?,
+                file: PathBuf::from(unescape(file)?),
+                span: start.parse().ok()?..end.parse().ok()?,
+                old: unescape(old)?,
+                new: unescape(new)?,
+            })
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_and_revert() {
+        let old = "pkgver=1.0.0\npkgrel=3\n\nsource=(foo-1.0.0.tar.gz)\n";
+        let new = "pkgver=1.1.0\npkgrel=1\n\nsource=(foo-1.1.0.tar.gz)\n";
+        let found = edits("1", Path::new("/PKGBUILD"), old, new);
+        let spans: Vec<_> = found
+            .iter()
+            .map(|edit| (&new[edit.span.clone()], &*edit.old))
+            .collect();
+        assert_eq!(spans, [("1.0\npkgrel=1", "0.0\npkgrel=3"), ("1", "0")]);
+        let all: Vec<_> = found.iter().collect();
+        assert_eq!(revert(new, &all).unwrap(), old);
+        assert!(revert(old, &all).is_err());
+
+        let edits = edits("1", Path::new("/a"), "é\tx", "é\ty\nz");
+        assert_eq!(edits[0].old, "x");
+        assert_eq!(parse(&render(&edits)), Some(edits));
+    }
+
+    #[test]
+    fn log() {
+        let mut log = Log::default();
+        for bump in 0..KEEP + 2 {
+            let bump = bump.to_string();
+            log.record(edits(&bump, Path::new("/a"), "1.0.0\n", "1.0.1\n"));
+            log.record(edits(&bump, Path::new("/b"), "1.0.0\n", "1.0.1\n"));
+        }
+        assert_eq!(log.edits.len(), 2 * KEEP);
+        let last = log.pop();
+        assert_eq!(last.len(), 2);
+        assert_eq!(last[0].bump, (KEEP + 1).to_string());
+        assert_eq!(log.pop()[1].file, Path::new("/b"));
+        assert_eq!(log.edits.len(), 2 * (KEEP - 2));
+    }
+}
@@ -0,0 +1,565 @@
+//! Lookup of the newest version released by an upstream project.
+//!
+//! Upstreams are given as `<backend>:<name>`, e.g. `docker:library/nginx`,
+//! `debian:debian/watch`, `uscan:.`, `github:owner/repo`, `crates:serde` or
+//! `pypi:requests`.
+
+use std::{path::Path, process::Command};
+
+use crate::{
+    cache::Cache,
+    http::{self, Response},
+    json,
+    token::{self, Service},
+    watch,
+};
+
+/// Returns the newest X.Y.Z version published by `spec`. Docker tags are only taken from
+/// the variant of `current`, e.g. `1.25.3-alpine` asks for the newest `X.Y.Z-alpine`
+/// tag, the other backends ignore it.
+pub fn latest(spec: &str, current: &str) -> Result<String, String> {
+    let Some((backend, name)) = spec.split_once(':') else {
+        return Err(format!(
+            "invalid upstream '{spec}', expected <backend>:<name>"
+        ));
+    };
+
+    match backend {
+        "docker" => docker(name, current),
+        "debian" => debian(name),
+        "uscan" => uscan(name),
+        "github" => github(name),
+        "crates" => crates(name),
+        "pypi" => pypi(name),
+        _ => Err(format!("unknown upstream backend '{backend}'")),
+    }
+}
+
+/// Like [`latest`] but answers from `cache` if it has a fresh entry for `spec`. When
+/// offline, entries of any age are used.
+pub fn latest_cached(spec: &str, current: &str, cache: &Cache) -> Result<String, String> {
+    // The paths of the Debian backends are relative to the current directory.
+    let key = match spec.split_once(':') {
+        Some((backend @ ("debian" | "uscan"), path)) => {
+            match crate::path::canonicalize(Path::new(path)) {
+                Ok(path) => format!("{backend}:{}", path.display()),
+                Err(_) => return latest(spec, current),
+            }
+        }
+        Some(("docker", _)) if !variant(current).is_empty() => {
+            format!("{spec} {}", variant(current))
+        }
+        _ => spec.to_string(),
+    };
+
+    if let Some(version) = cache.get(&key, false) {
+        return Ok(version);
+    }
+    match latest(spec, current) {
+        Ok(version) => {
+            cache.put(&key, &version);
+            Ok(version)
+        }
+        Err(err) if http::is_offline() => cache.get(&key, true).ok_or(err),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the newest of the given versions. Anything which is not a plain `X.Y.Z` with
+/// an optional `v` prefix is ignored. The prefix is dropped from the result.
+pub(crate) fn newest<'a>(versions: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    versions
+        .into_iter()
+        .map(|version| version.strip_prefix('v').unwrap_or(version))
+        .filter_map(|version| {
+            let mut parts = version.split('.').map(|p| {
+                (!p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()))
+                    .then(|| p.parse::<u64>().ok())
+                    .flatten()
+            });
+            let key = (parts.next()??, parts.next()??, parts.next()??);
+            parts.next().is_none().then_some((key, version))
+        })
+        .max_by_key(|(key, _)| *key)
+        .map(|(_, version)| version)
+}
+
+/// Returns the variant of the Docker tag `tag`, e.g. `-alpine` of `1.25.3-alpine`.
+fn variant(tag: &str) -> &str {
+    &tag[crate::dockerfile::variant_start(tag)..]
+}
+
+/// Returns the newest of the Docker `tags` with the given variant, the variant included.
+fn newest_tag<'a>(tags: impl IntoIterator<Item = &'a str>, variant: &str) -> Option<String> {
+    let versions = tags
+        .into_iter()
+        .filter_map(|tag| tag.strip_suffix(variant))
+        .filter(|version| !version.contains('-'));
+    newest(versions).map(|version| format!("{version}{variant}"))
+}
+
+/// Splits an image reference into the registry host and the repository.
+fn image(name: &str) -> (&str, String) {
+    match name.split_once('/') {
+        Some((host, repository)) if host.contains(['.', ':']) || host == "localhost" => {
+            let host = if host == "docker.io" {
+                "registry-1.docker.io"
+            } else {
+                host
+            };
+            (host, repository.to_string())
+        }
+        Some(_) => ("registry-1.docker.io", name.to_string()),
+        None => ("registry-1.docker.io", format!("library/{name}")),
+    }
+}
+
+/// Lists the tags of an image using the OCI distribution API and returns the newest one
+/// of the variant of `current`.
+fn docker(name: &str, current: &str) -> Result<String, String> {
+    let (registry, repository) = image(name);
+    let mut url = format!("https://{registry}/v2/{repository}/tags/list");
+    // A configured token is used as is, otherwise an anonymous one is requested.
+    let mut token = token::get(Service::Registry).ok();
+    let mut tags = Vec::new();
+
+    loop {
+        let headers: Vec<_> = token
+            .iter()
+            .map(|token| ("Authorization", format!("Bearer {token}")))
+            .collect();
+        let response = http::get(&url, &headers)?;
+
+        if response.status == 401 && token.is_none() {
+            token = Some(registry_token(&response)?);
+            continue;
+        }
+        if response.status != 200 {
+            return Err(format!("{url}: HTTP {}", response.status));
+        }
+
+        let doc = json::parse(&response.body)?;
+        if let Some(items) = doc.value.get("tags").and_then(|t| t.value.as_array()) {
+            tags.extend(
+                items
+                    .iter()
+                    .filter_map(|t| t.value.as_str().map(str::to_string)),
+            );
+        }
+
+        match response.header("link").and_then(next_link) {
+            Some(next) if next.starts_with('/') => url = format!("https://{registry}{next}"),
+            Some(next) => url = next.to_string(),
+            None => break,
+        }
+    }
+
+    let variant = variant(current);
+    newest_tag(tags.iter().map(String::as_str), variant)
+        .ok_or_else(|| format!("no X.Y.Z{variant} tags found for '{name}'"))
+}
+
+/// Evaluates a Debian watch file (or the one of the package in a directory).
+fn debian(path: &str) -> Result<String, String> {
+    let versions = watch::evaluate(Path::new(path))?;
+    newest(versions.iter().map(String::as_str))
+        .map(str::to_string)
+        .ok_or_else(|| format!("no X.Y.Z versions found by '{path}'"))
+}
+
+/// Asks uscan for the newest upstream version of the Debian package in `dir`.
+fn uscan(dir: &str) -> Result<String, String> {
+    http::online("uscan")?;
+    let output = Command::new("uscan")
+        .args(["--report", "--dehs"])
+        .current_dir(dir)
+        .output()
+        .map_err(|err| format!("cannot run uscan: {err}"))?;
+
+    dehs(&String::from_utf8_lossy(&output.stdout))
+        .map(str::to_string)
+        .ok_or_else(|| {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            format!("uscan found no upstream version: {}", stderr.trim())
+        })
+}
+
+/// Returns the newest release of the GitHub repository `repo` (`owner/name`), or its
+/// newest tag if none of the releases has an X.Y.Z tag. Drafts and pre-releases are
+/// skipped.
+fn github(repo: &str) -> Result<String, String> {
+    if repo.split('/').count() != 2 || repo.split('/').any(str::is_empty) {
+        return Err(format!(
+            "invalid GitHub repository '{repo}', expected owner/name"
+        ));
+    }
+    // Anonymous requests work too, a token only raises the rate limit.
+    let mut headers = vec![("Accept", "application/vnd.github+json".to_string())];
+    if let Ok(token) = token::get(Service::GitHub) {
+        headers.push(("Authorization", format!("Bearer {token}")));
+    }
+    let list = |what: &str| {
+        let url = format!("https://api.github.com/repos/{repo}/{what}?per_page=100");
+        let response = http::get(&url, &headers)?;
+        if response.status != 200 {
+            return Err(format!("{url}: HTTP {}", response.status));
+        }
+        Ok(response.body)
+    };
+
+    let releases = releases(&list("releases")?)?;
+    if let Some(newest) = newest(releases.iter().map(String::as_str)) {
+        return Ok(newest.to_string());
+    }
+    let doc = json::parse(&list("tags")?)?;
+    let tags = doc.value.as_array().unwrap_or_default();
+    newest(
+        tags.iter()
+            .filter_map(|tag| tag.value.get("name")?.value.as_str()),
+    )
+    .map(str::to_string)
+    .ok_or_else(|| format!("no X.Y.Z releases or tags found for '{repo}'"))
+}
+
+/// Returns the tags of the published releases in the GitHub API response `body`.
+fn releases(body: &str) -> Result<Vec<String>, String> {
+    let doc = json::parse(body)?;
+    // Booleans only have a span.
+    let is_true = |release: &json::Value, key: &str| {
+        release
+            .get(key)
+            .is_some_and(|value| &body[value.span.clone()] == "true")
+    };
+    Ok(doc
+        .value
+        .as_array()
+        .unwrap_or_default()
+        .iter()
+        .filter(|release| {
+            !is_true(&release.value, "draft") && !is_true(&release.value, "prerelease")
+        })
+        .filter_map(|release| release.value.get("tag_name")?.value.as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns the newest version of the crate `name` published on crates.io which was not
+/// yanked. The sparse index is used, it is what Cargo reads as well.
+fn crates(name: &str) -> Result<String, String> {
+    let path = index_path(name).ok_or_else(|| format!("invalid crate name '{name}'"))?;
+    let url = format!("https://index.crates.io/{path}");
+    let response = http::get(&url, &[])?;
+    if response.status == 404 {
+        return Err(format!("there is no crate '{name}' on crates.io"));
+    }
+    if response.status != 200 {
+        return Err(format!("{url}: HTTP {}", response.status));
+    }
+
+    let versions = index_versions(&response.body)?;
+    newest(versions.iter().map(String::as_str))
+        .map(str::to_string)
+        .ok_or_else(|| format!("no X.Y.Z versions of '{name}' found"))
+}
+
+/// Returns the path of the crate `name` in the index, `None` if it's not a valid name.
+fn index_path(name: &str) -> Option<String> {
+    if name.is_empty()
+        || !name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return None;
+    }
+    let name = name.to_ascii_lowercase();
+    Some(match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    })
+}
+
+/// Returns the versions of an index file which were not yanked, one JSON object per line.
+fn index_versions(body: &str) -> Result<Vec<String>, String> {
+    let mut versions = Vec::new();
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let doc = json::parse(line)?;
+        let yanked = doc
+            .value
+            .get("yanked")
+            .is_some_and(|value| &line[value.span.clone()] == "true");
+        if let Some(version) = doc.value.get("vers").and_then(|v| v.value.as_str()) {
+            if !yanked {
+                versions.push(version.to_string());
+            }
+        }
+    }
+    Ok(versions)
+}
+
+/// Returns the newest release of the project `name` on PyPI which has files and not all
+/// of them were yanked.
+fn pypi(name: &str) -> Result<String, String> {
+    if name.is_empty()
+        || !name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b))
+    {
+        return Err(format!("invalid PyPI project name '{name}'"));
+    }
+    let url = format!("https://pypi.org/pypi/{name}/json");
+    let response = http::get(&url, &[])?;
+    if response.status == 404 {
+        return Err(format!("there is no project '{name}' on PyPI"));
+    }
+    if response.status != 200 {
+        return Err(format!("{url}: HTTP {}", response.status));
+    }
+
+    let versions = pypi_releases(&response.body)?;
+    newest(versions.iter().map(String::as_str))
+        .map(str::to_string)
+        .ok_or_else(|| format!("no X.Y.Z releases of '{name}' found"))
+}
+
+/// Returns the releases in the PyPI JSON API response `body` with files which were not
+/// yanked.
+fn pypi_releases(body: &str) -> Result<Vec<String>, String> {
+    let doc = json::parse(body)?;
+    let releases = doc
+        .value
+        .get("releases")
+        .and_then(|releases| releases.value.as_object())
+        .unwrap_or_default();
+    Ok(releases
+        .iter()
+        .filter(|(_, files)| {
+            files
+                .value
+                .as_array()
+                .unwrap_or_default()
+                .iter()
+                .any(|file| {
+                    // Booleans only have a span.
+                    file.value
+                        .get("yanked")
+                        .is_none_or(|yanked| &body[yanked.span.clone()] != "true")
+                })
+        })
+        .map(|(version, _)| version.clone())
+        .collect())
+}
+
+/// Returns `<upstream-version>` of uscan's DEHS report.
+fn dehs(report: &str) -> Option<&str> {
+    let (_, rest) = report.split_once("<upstream-version>")?;
+    let (version, _) = rest.split_once("</upstream-version>")?;
+    Some(version.trim()).filter(|v| !v.is_empty())
+}
+
+/// Requests an anonymous token as described by the `WWW-Authenticate` header.
+fn registry_token(response: &Response) -> Result<String, String> {
+    let Some(challenge) = response
+        .header("www-authenticate")
+        .and_then(|h| h.strip_prefix("Bearer "))
+    else {
+        return Err("the registry requires unsupported authentication".to_string());
+    };
+
+    let params = auth_params(challenge);
+    let realm = params
+        .iter()
+        .find(|(k, _)| k == "realm")
+        .map(|(_, v)| v.as_str())
+        .ok_or("the registry did not provide a token realm")?;
+    let query: Vec<_> = params
+        .iter()
+        .filter(|(k, _)| k != "realm")
+        .map(|(k, v)| format!("{k}={}", http::encode(v)))
+        .collect();
+
+    let response = http::get(&format!("{realm}?{}", query.join("&")), &[])?;
+    if response.status != 200 {
+        return Err(format!("{realm}: HTTP {}", response.status));
+    }
+
+    let doc = json::parse(&response.body)?;
+    doc.value
+        .get("token")
+        .or_else(|| doc.value.get("access_token"))
+        .and_then(|t| t.value.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| format!("{realm}: missing token"))
+}
+
+/// Parses `key="value",key2="value2"` parameters of an authentication challenge.
+fn auth_params(challenge: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut rest = challenge.trim();
+
+    while let Some((key, value)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let value = value.trim_start();
+        let (value, next) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+        params.push((key, value.to_string()));
+        rest = next;
+    }
+
+    params
+}
+
+/// Returns the target of the `rel="next"` link in a `Link` header.
+fn next_link(header: &str) -> Option<&str> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        params
+            .contains("rel=\"next\"")
+            .then(|| target.trim().trim_start_matches('<').trim_end_matches('>'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_version() {
+        let tags = [
+            "latest",
+            "1.25",
+            "1.9.2",
+            "1.25.3-alpine",
+            "v1.25.3",
+            "1.10.0",
+            "01.x.0",
+        ];
+        assert_eq!(newest(tags), Some("1.25.3"));
+        assert_eq!(newest(["latest", "stable"]), None);
+    }
+
+    #[test]
+    fn images() {
+        assert_eq!(
+            image("nginx"),
+            ("registry-1.docker.io", "library/nginx".to_string())
+        );
+        assert_eq!(
+            image("library/nginx"),
+            ("registry-1.docker.io", "library/nginx".to_string())
+        );
+        assert_eq!(
+            image("ghcr.io/owner/image"),
+            ("ghcr.io", "owner/image".to_string())
+        );
+        assert_eq!(
+            image("docker.io/grafana/grafana"),
+            ("registry-1.docker.io", "grafana/grafana".to_string())
+        );
+    }
+
+    #[test]
+    fn challenge() {
+        let params = auth_params(
+            r#"realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/nginx:pull""#,
+        );
+        assert_eq!(
+            params,
+            [
+                (
+                    "realm".to_string(),
+                    "https://auth.docker.io/token".to_string()
+                ),
+                ("service".to_string(), "registry.docker.io".to_string()),
+                (
+                    "scope".to_string(),
+                    "repository:library/nginx:pull".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn dehs_report() {
+        let report = "<dehs>\n<package>foo</package>\n<debian-uversion>1.0.0</debian-uversion>\n<upstream-version>1.2.0</upstream-version>\n<status>newer package available</status>\n</dehs>\n";
+        assert_eq!(dehs(report), Some("1.2.0"));
+        assert_eq!(dehs("<dehs></dehs>"), None);
+    }
+
+    #[test]
+    fn github_releases() {
+        let body = r#"[
+            {"tag_name": "v2.0.0-rc.1", "draft": false, "prerelease": true},
+            {"tag_name": "v1.3.0", "draft": true, "prerelease": false},
+            {"tag_name": "v1.2.0", "draft": false, "prerelease": false}
+        ]"#;
+        assert_eq!(releases(body), Ok(vec!["v1.2.0".to_string()]));
+        assert!(github("nope").is_err());
+    }
+
+    #[test]
+    fn crates_index() {
+        assert_eq!(index_path("a").as_deref(), Some("1/a"));
+        assert_eq!(index_path("cc").as_deref(), Some("2/cc"));
+        assert_eq!(index_path("syn").as_deref(), Some("3/s/syn"));
+        assert_eq!(
+            index_path("Serde_JSON").as_deref(),
+            Some("se/rd/serde_json")
+        );
+        assert_eq!(index_path("../x"), None);
+
+        let body = "{\"name\":\"foo\",\"vers\":\"1.0.0\",\"yanked\":false}\n{\"name\":\"foo\",\"vers\":\"1.1.0\",\"yanked\":true}\n";
+        assert_eq!(index_versions(body), Ok(vec!["1.0.0".to_string()]));
+    }
+
+    #[test]
+    fn pypi_files() {
+        let body = r#"{"info": {"version": "2.0.0"}, "releases": {
+            "1.0.0": [{"yanked": false}],
+            "2.0.0": [{"yanked": true}],
+            "2.1.0": [],
+            "2.1.0rc1": [{"yanked": false}]
+        }}"#;
+        assert_eq!(
+            pypi_releases(body),
+            Ok(vec!["1.0.0".to_string(), "2.1.0rc1".to_string()])
+        );
+        assert!(pypi("../x").is_err());
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            next_link(r#"</v2/library/nginx/tags/list?last=1.2&n=100>; rel="next""#),
+            Some("/v2/library/nginx/tags/list?last=1.2&n=100")
+        );
+        assert_eq!(next_link(r#"</foo>; rel="prev""#), None);
+    }
+
+    #[test]
+    fn variants() {
+        let tags = [
+            "1.25.3",
+            "1.27.0",
+            "1.25.3-alpine",
+            "v1.26.1-alpine",
+            "1.27.0-alpine-slim",
+            "1.26.0-bookworm",
+        ];
+        assert_eq!(variant("1.25.3-alpine"), "-alpine");
+        assert_eq!(variant("1.25.3"), "");
+        assert_eq!(newest_tag(tags, ""), Some("1.27.0".to_string()));
+        assert_eq!(
+            newest_tag(tags, "-alpine"),
+            Some("1.26.1-alpine".to_string())
+        );
+        assert_eq!(
+            newest_tag(tags, "-alpine-slim"),
+            Some("1.27.0-alpine-slim".to_string())
+        );
+        assert_eq!(newest_tag(tags, "-slim"), None);
+    }
+}
@@ -0,0 +1,205 @@
+//! Evaluation of Debian watch files (`debian/watch`), as understood by uscan.
+//!
+//! Only what is needed to find the newest upstream version is supported: the URL and the
+//! pattern of each line, the `@PACKAGE@`, `@ANY_VERSION@` and extension substitutions and
+//! the `searchmode` option. Mangling options are ignored.
+
+use std::{fs, path::Path};
+
+use regex::Regex;
+
+use crate::{http, parallel};
+
+const ANY_VERSION: &str = r"[-_]?[Vv]?(\d[\-+\.:\~\da-zA-Z]*)";
+const ARCHIVE_EXT: &str = r"(?i)(?:\.(?:tar\.xz|tar\.bz2|tar\.gz|tar\.zstd?|zip|tgz|tbz|txz))";
+const SIGNATURE_EXT: &str = r"(?i)(?:\.(?:tar\.xz|tar\.bz2|tar\.gz|tar\.zstd?|zip|tgz|tbz|txz))(?:\.(?:asc|pgp|gpg|sig|sign))";
+const DEB_EXT: &str = r"[\+~](debian|dfsg|ds|deb)(\.)?(\d+)?$";
+
+/// A line of a watch file.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Watch {
+    pub url: String,
+    pub pattern: String,
+    /// `searchmode=plain`, the pattern is matched against the whole page instead of links.
+    pub plain: bool,
+}
+
+/// Parses the watch file `src`, substituting `package` for `@PACKAGE@`.
+pub(crate) fn parse(src: &str, package: &str) -> Result<Vec<Watch>, String> {
+    let joined = src.replace("\\\n", " ");
+    let mut watches = Vec::new();
+
+    for (idx, line) in joined.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("version=") {
+            continue;
+        }
+
+        let line = line.replace("@PACKAGE@", package);
+        let (opts, rest) = match line.strip_prefix("opts=") {
+            Some(rest) => match rest.strip_prefix('"') {
+                Some(quoted) => quoted
+                    .split_once('"')
+                    .ok_or_else(|| format!("line {}: unterminated opts", idx + 1))?,
+                None => rest.split_once(char::is_whitespace).unwrap_or((rest, "")),
+            },
+            None => ("", line.as_str()),
+        };
+
+        let mut fields = rest.split_whitespace();
+        let Some(url) = fields.next() else {
+            return Err(format!("line {}: missing URL", idx + 1));
+        };
+        let (url, pattern) = match fields.next() {
+            Some(pattern) => (url.to_string(), pattern.to_string()),
+            None => match url.rsplit_once('/') {
+                Some((dir, pattern)) if pattern.contains('(') => {
+                    (format!("{dir}/"), pattern.to_string())
+                }
+                _ => return Err(format!("line {}: missing pattern", idx + 1)),
+            },
+        };
+
+        let pattern = pattern
+            .replace("@ANY_VERSION@", ANY_VERSION)
+            .replace("@ARCHIVE_EXT@", ARCHIVE_EXT)
+            .replace("@SIGNATURE_EXT@", SIGNATURE_EXT)
+            .replace("@DEB_EXT@", DEB_EXT);
+        let plain = opts.split(',').any(|opt| opt.trim() == "searchmode=plain");
+
+        watches.push(Watch {
+            url,
+            pattern,
+            plain,
+        });
+    }
+
+    Ok(watches)
+}
+
+/// Returns the versions on `page` (fetched from `url`) which match the watch pattern. The
+/// captured groups are joined with a dot, like uscan does.
+pub(crate) fn versions(watch: &Watch, page: &str) -> Result<Vec<String>, String> {
+    let pattern = Regex::new(&format!("^(?:{})$", watch.pattern))
+        .map_err(|err| format!("invalid pattern '{}': {err}", watch.pattern))?;
+    let captured = |caps: regex::Captures| {
+        let parts: Vec<_> = caps.iter().skip(1).flatten().map(|m| m.as_str()).collect();
+        (!parts.is_empty()).then(|| parts.join("."))
+    };
+
+    if watch.plain {
+        let pattern = Regex::new(&watch.pattern)
+            .map_err(|err| format!("invalid pattern '{}': {err}", watch.pattern))?;
+        return Ok(pattern.captures_iter(page).filter_map(captured).collect());
+    }
+
+    let href = Regex::new(r#"(?i)<a\s[^>]*href\s*=\s*["']([^"']+)["']"#).unwrap();
+    let base = watch.url.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    Ok(href
+        .captures_iter(page)
+        .filter_map(|caps| {
+            let link = caps.get(1)?.as_str();
+            let relative = link.strip_prefix(base).map(|l| l.trim_start_matches('/'));
+            let name = link.rsplit('/').next();
+            [Some(link), relative, name]
+                .into_iter()
+                .flatten()
+                .find_map(|candidate| pattern.captures(candidate))
+                .and_then(captured)
+        })
+        .collect())
+}
+
+/// Returns the name of the source package from the `changelog` next to the watch file.
+fn package(dir: &Path) -> String {
+    fs::read_to_string(dir.join("changelog"))
+        .ok()
+        .and_then(|changelog| {
+            let line = changelog.lines().next()?;
+            Some(line.split_whitespace().next()?.to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Evaluates the watch file at `path` (or `path/debian/watch` if it is a directory) and
+/// returns all versions found.
+pub(crate) fn evaluate(path: &Path) -> Result<Vec<String>, String> {
+    let path = if path.is_dir() {
+        path.join("debian").join("watch")
+    } else {
+        path.to_path_buf()
+    };
+    let src = fs::read_to_string(&path)
+        .map_err(|err| format!("cannot read '{}': {err}", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    let watches = parse(&src, &package(dir))?;
+    let pages = parallel::map(&watches, |watch| {
+        let response = http::get(&watch.url, &[])?;
+        if response.status != 200 {
+            return Err(format!("{}: HTTP {}", watch.url, response.status));
+        }
+        versions(watch, &response.body)
+    });
+
+    let mut found = Vec::new();
+    for versions in pages {
+        found.extend(versions?);
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let src = "version=4\n# comment\nopts=\"searchmode=plain, pgpmode=none\" \\\n  https://example.com/releases .*/@PACKAGE@@ANY_VERSION@@ARCHIVE_EXT@\nhttps://example.com/dl/foo-(\\d\\S+)\\.tar\\.gz\n";
+        let watches = parse(src, "foo").unwrap();
+        assert_eq!(watches.len(), 2);
+        assert_eq!(watches[0].url, "https://example.com/releases");
+        assert!(watches[0].pattern.starts_with(".*/foo[-_]?[Vv]?"));
+        assert!(watches[0].plain);
+        assert_eq!(
+            watches[1],
+            Watch {
+                url: "https://example.com/dl/".to_string(),
+                pattern: "foo-(\\d\\S+)\\.tar\\.gz".to_string(),
+                plain: false,
+            }
+        );
+
+        assert!(parse("https://example.com/\n", "foo").is_err());
+    }
+
+    #[test]
+    fn links() {
+        let watch = Watch {
+            url: "https://example.com/dl/".to_string(),
+            pattern: "foo-(\\d+)\\.(\\d+)\\.(\\d+)\\.tar\\.gz".to_string(),
+            plain: false,
+        };
+        let page = r#"<a href="foo-1.2.3.tar.gz">x</a> <A HREF='/dl/foo-1.10.0.tar.gz'>
+            <a class="x" href="https://example.com/dl/foo-1.9.0.tar.gz"> <a href="bar-2.0.0.tar.gz">"#;
+        assert_eq!(
+            versions(&watch, page).unwrap(),
+            ["1.2.3", "1.10.0", "1.9.0"]
+        );
+
+        let watch = Watch {
+            plain: true,
+            pattern: r#""tag_name":\s*"v([\d.]+)""#.to_string(),
+            ..watch
+        };
+        assert_eq!(
+            versions(
+                &watch,
+                r#"[{"tag_name": "v2.0.1"}, {"tag_name": "v2.1.0"}]"#
+            )
+            .unwrap(),
+            ["2.0.1", "2.1.0"]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Notifications about bumps sent to webhooks.
+
+use crate::{http, json, Change};
+
+/// Posts the JSON `payload` to `url`.
+pub fn send(url: &str, payload: &str) -> Result<(), String> {
+    let response = http::post(url, &[], payload)?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("{url}: HTTP {}", response.status));
+    }
+    Ok(())
+}
+
+/// Escapes `s` so that it can be placed inside a JSON string in a template.
+pub fn escape(s: &str) -> String {
+    let quoted = json::quote(s);
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Returns `changes` as a JSON array.
+pub fn changes(changes: &[Change]) -> String {
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|change| {
+            format!(
+                "{{\"position\":{},\"old\":{},\"new\":{}}}",
+                change.position,
+                json::quote(&change.old),
+                json::quote(&change.new)
+            )
+        })
+        .collect();
+    format!("[{}]", changes.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload() {
+        assert_eq!(escape("a \"b\"\n"), "a \\\"b\\\"\\n");
+
+        let change = Change {
+            position: 1,
+            span: 0..5,
+            old: "1.0.0".to_string(),
+            new: "1.0.1".to_string(),
+        };
+        assert_eq!(
+            changes(&[change]),
+            r#"[{"position":1,"old":"1.0.0","new":"1.0.1"}]"#
+        );
+        assert_eq!(changes(&[]), "[]");
+    }
+}
@@ -0,0 +1,116 @@
+//! Just enough of XML to find the text of an element by its path, as needed for the
+//! `<version>` of Maven POMs.
+
+use std::ops::Range;
+
+/// Returns the location of the (trimmed) text of the first element at `path`, the local
+/// names of the elements from the root (`["project", "version"]`). Elements which
+/// contain other elements have no text.
+pub(crate) fn text_span(src: &str, path: &[&str]) -> Result<Option<Range<usize>>, String> {
+    let mut stack: Vec<&str> = Vec::new();
+    // Where the text of the innermost element starts, `None` once it has a child.
+    let mut text = None;
+    let mut pos = 0;
+
+    while let Some(idx) = src[pos..].find('<') {
+        let start = pos + idx;
+        let rest = &src[start..];
+
+        let skip = [
+            ("<!--", "-->"),
+            ("<![CDATA[", "]]>"),
+            ("<?", "?>"),
+            ("<!", ">"),
+        ]
+        .into_iter()
+        .find(|(open, _)| rest.starts_with(open));
+        if let Some((open, close)) = skip {
+            let end = rest[open.len()..]
+                .find(close)
+                .ok_or_else(|| format!("unclosed {open}"))?;
+            pos = start + open.len() + end + close.len();
+            continue;
+        }
+
+        let end = start + tag_end(rest).ok_or("unclosed tag")?;
+        let tag = &src[start + 1..end - 1];
+        pos = end;
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = local(name.trim());
+            if stack.last() != Some(&name) {
+                return Err(format!("unexpected </{name}>"));
+            }
+            if let Some(text) = text.take() {
+                if stack == path {
+                    let value: &str = &src[text..start];
+                    let trimmed = value.trim_start();
+                    let text = text + value.len() - trimmed.len();
+                    return Ok(Some(text..text + trimmed.trim_end().len()));
+                }
+            }
+            stack.pop();
+            continue;
+        }
+
+        let name = local(
+            tag.split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or(""),
+        );
+        if !tag.ends_with('/') {
+            stack.push(name);
+            text = Some(end);
+        } else {
+            text = None;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the offset after the `>` ending the tag at the start of `src`, skipping
+/// quoted attribute values.
+fn tag_end(src: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in src.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(idx + 1),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Drops the namespace prefix of `name`.
+fn local(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elements() {
+        let pom = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- <version>0.0.0</version> -->
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <parent><version>1.0.0</version></parent>
+  <dependencies>
+    <dependency><version>2.0.0</version></dependency>
+  </dependencies>
+  <empty/>
+  <m:version attr="a>b"> 3.0.0 </m:version>
+</project>"#;
+        let text = |path| text_span(pom, path).unwrap().map(|span| &pom[span]);
+        assert_eq!(text(&["project", "version"]), Some("3.0.0"));
+        assert_eq!(text(&["project", "parent", "version"]), Some("1.0.0"));
+        assert_eq!(text(&["project", "dependencies"]), None);
+        assert_eq!(text(&["version"]), None);
+        assert!(text_span("<a></b>", &["a"]).is_err());
+    }
+}
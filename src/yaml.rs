@@ -0,0 +1,162 @@
+//! A reader of the block mappings of YAML files which remembers where every scalar
+//! value is located in the source. Sequences, flow collections and multi-line scalars
+//! are skipped, which is enough to find the version of a manifest.
+
+use std::ops::Range;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Entry {
+    /// The keys of the mappings leading to the value.
+    pub path: Vec<String>,
+    /// Location of the value, without the quotes.
+    pub span: Range<usize>,
+}
+
+pub(crate) fn parse(src: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    // The indentation of the enclosing mappings and their keys, `None` for sequence items.
+    let mut parents: Vec<(usize, Option<String>)> = Vec::new();
+    // Lines indented more than this belong to a block scalar (`|` or `>`).
+    let mut block = None;
+    let mut offset = 0;
+
+    for line in src.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if block.is_some_and(|block| indent > block) {
+            continue;
+        }
+        block = None;
+        if indent == 0 && (trimmed.starts_with("---") || trimmed.starts_with("...")) {
+            parents.clear();
+            continue;
+        }
+
+        while parents.last().is_some_and(|(i, _)| *i >= indent) {
+            parents.pop();
+        }
+        if trimmed == "-" || trimmed.starts_with("- ") {
+            parents.push((indent, None));
+            continue;
+        }
+
+        let Some((key, rest)) = key(trimmed) else {
+            continue;
+        };
+        let value_start = line.len() - rest.len();
+        let value = strip_comment(rest);
+        let trimmed_value = value.trim_start();
+        let value_start = value_start + value.len() - trimmed_value.len();
+
+        if trimmed_value.is_empty() {
+            parents.push((indent, Some(key)));
+            continue;
+        }
+        if trimmed_value.starts_with(['|', '>']) {
+            block = Some(indent);
+            continue;
+        }
+        if trimmed_value.starts_with(['{', '[', '&', '*', '!']) {
+            continue;
+        }
+
+        let quoted = trimmed_value.len() >= 2
+            && (trimmed_value.starts_with('"') && trimmed_value.ends_with('"')
+                || trimmed_value.starts_with('\'') && trimmed_value.ends_with('\''));
+        let span = if quoted {
+            start + value_start + 1..start + value_start + trimmed_value.len() - 1
+        } else {
+            start + value_start..start + value_start + trimmed_value.len()
+        };
+
+        let mut path = Vec::new();
+        for (_, parent) in &parents {
+            match parent {
+                Some(parent) => path.push(parent.clone()),
+                // Values inside of sequences have no path.
+                None => {
+                    path.clear();
+                    break;
+                }
+            }
+        }
+        if path.len() == parents.len() {
+            path.push(key);
+            entries.push(Entry { path, span });
+        }
+    }
+
+    entries
+}
+
+/// Splits `line` into the key of a mapping entry and the rest after the colon.
+fn key(line: &str) -> Option<(String, &str)> {
+    if let Some(quote) = line.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let end = line[1..].find(quote)? + 1;
+        let rest = line[end + 1..].trim_start().strip_prefix(':')?;
+        return Some((line[1..end].to_string(), rest));
+    }
+
+    let idx = line
+        .match_indices(':')
+        .map(|(idx, _)| idx)
+        .find(|&idx| line[idx + 1..].is_empty() || line[idx + 1..].starts_with([' ', '\t']))?;
+    Some((line[..idx].trim_end().to_string(), &line[idx + 1..]))
+}
+
+/// Removes a `# comment` from the end of a plain or quoted `value`.
+fn strip_comment(value: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (idx, c) in value.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if (c == '"' || c == '\'') && prev.is_whitespace() => quote = Some(c),
+            None if c == '#' && prev.is_whitespace() => return value[..idx].trim_end(),
+            None => {}
+        }
+        prev = c;
+    }
+    value.trim_end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let src = "name: foo # the name
+version: '1.0.0'
+metadata:
+  labels:
+    - version: 2.0.0
+  \"version\": \"3.0.0\"
+script: |
+  version: 4.0.0
+---
+url: https://example.com/5.0.0
+";
+        let entries: Vec<_> = parse(src)
+            .into_iter()
+            .map(|e| (e.path.join("."), &src[e.span]))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("name".to_string(), "foo"),
+                ("version".to_string(), "1.0.0"),
+                ("metadata.version".to_string(), "3.0.0"),
+                ("url".to_string(), "https://example.com/5.0.0"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! Runs the verinc binary on files in temporary directories.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+const VERINC: &str = env!("CARGO_BIN_EXE_verinc");
+
+/// Returns an empty temporary directory for the test `name`.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("verinc-test-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs verinc with `args` in `dir`, killing it if it doesn't finish in time.
+fn verinc(dir: &Path, args: &[&str]) -> Output {
+    let mut child = Command::new(VERINC)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let start = Instant::now();
+    while child.try_wait().unwrap().is_none() {
+        if start.elapsed() > Duration::from_secs(30) {
+            child.kill().unwrap();
+            panic!("verinc {args:?} did not finish");
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    child.wait_with_output().unwrap()
+}
+
+fn read(dir: &Path, name: &str) -> String {
+    fs::read_to_string(dir.join(name)).unwrap()
+}
+
+/// Runs git with `args` in `dir` and returns its output.
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?}: {output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Returns a temporary Git repository for the test `name` with `files` committed.
+fn repository(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = temp_dir(name);
+    git(&dir, &["init", "-q"]);
+    git(&dir, &["config", "user.name", "Test"]);
+    git(&dir, &["config", "user.email", "test@example.com"]);
+    for (name, content) in files {
+        fs::write(dir.join(name), content).unwrap();
+    }
+    git(&dir, &["add", "."]);
+    git(&dir, &["commit", "-q", "-m", "init"]);
+    dir
+}
+
+#[test]
+fn hooks_run_verinc_on_the_same_file() {
+    let dir = temp_dir("hooks");
+    fs::write(dir.join("f.txt"), "1.0.0\n").unwrap();
+
+    let hook = format!("{VERINC} --minor f.txt");
+    let output = verinc(&dir, &["--post-hook", &hook, "f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.1.0\n");
+
+    let output = verinc(&dir, &["--pre-hook", &hook, "f.txt"]);
+    assert_eq!(output.status.code(), Some(6), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.2.0\n");
+}
+
+#[test]
+fn json_errors_name_the_file() {
+    let dir = temp_dir("json-errors");
+    fs::write(dir.join("a.txt"), "1.0.0\n").unwrap();
+    fs::write(dir.join("b.txt"), "none\n").unwrap();
+
+    let output = verinc(&dir, &["--json-errors", "--no-undo", "*.txt"]);
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("\"file\": \"b.txt\""), "{stderr}");
+    assert_eq!(read(&dir, "a.txt"), "1.0.1\n");
+}
+
+#[test]
+fn globs_bump_the_other_files() {
+    let dir = temp_dir("globs");
+    fs::write(dir.join("a.txt"), "1.0.0\n").unwrap();
+    fs::write(dir.join("b.txt"), "none\n").unwrap();
+    fs::write(dir.join("c.txt"), "2.0.0\n").unwrap();
+
+    let output = verinc(&dir, &["--no-undo", "*.txt"]);
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No version found in 'b.txt'"), "{stderr}");
+    assert_eq!(read(&dir, "a.txt"), "1.0.1\n");
+    assert_eq!(read(&dir, "b.txt"), "none\n");
+    assert_eq!(read(&dir, "c.txt"), "2.0.1\n");
+}
+
+#[test]
+fn recursive_bumps_the_other_files() {
+    let dir = temp_dir("recursive");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::create_dir_all(dir.join("vendor")).unwrap();
+    fs::write(dir.join("a.txt"), "1.0.0 2.0.0\n").unwrap();
+    fs::write(dir.join("none.txt"), "none\n").unwrap();
+    fs::write(dir.join("sub/b.txt"), "1.0.0\n").unwrap();
+    fs::write(dir.join("sub/c.txt"), "3.0.0 4.0.0\n").unwrap();
+    fs::write(dir.join("sub/c.lock"), "3.0.0 4.0.0\n").unwrap();
+    fs::write(dir.join("vendor/d.txt"), "5.0.0 6.0.0\n").unwrap();
+
+    let args = [
+        "--no-undo",
+        "-r",
+        ".",
+        "--exclude",
+        "vendor",
+        "--exclude",
+        "*.lock",
+    ];
+    let output = verinc(&dir, &[&args[..], &["-p", "1"]].concat());
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No version at position 1"), "{stderr}");
+    assert!(stderr.contains("Changed 2 of 3 files"), "{stderr}");
+    assert_eq!(read(&dir, "a.txt"), "1.0.0 2.0.1\n");
+    assert_eq!(read(&dir, "sub/b.txt"), "1.0.0\n");
+    assert_eq!(read(&dir, "sub/c.txt"), "3.0.0 4.0.1\n");
+    assert_eq!(read(&dir, "sub/c.lock"), "3.0.0 4.0.0\n");
+    assert_eq!(read(&dir, "vendor/d.txt"), "5.0.0 6.0.0\n");
+
+    let output = verinc(&dir, &args);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "a.txt"), "1.0.1 2.0.1\n");
+    assert_eq!(read(&dir, "sub/b.txt"), "1.0.1\n");
+    assert_eq!(read(&dir, "none.txt"), "none\n");
+    assert_eq!(read(&dir, "vendor/d.txt"), "5.0.0 6.0.0\n");
+}
+
+#[test]
+fn writes_through_the_locked_file() {
+    let dir = temp_dir("locked");
+    fs::write(dir.join("f.txt"), "10.0.0 and the rest\n").unwrap();
+
+    let output = verinc(&dir, &["--no-undo", "--backup", "--set", "9.0.0", "f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "9.0.0 and the rest\n");
+    assert_eq!(read(&dir, "f.txt.bak"), "10.0.0 and the rest\n");
+
+    let mut permissions = fs::metadata(dir.join("f.txt")).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(dir.join("f.txt"), permissions).unwrap();
+    let output = verinc(&dir, &["--no-undo", "--force", "f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "9.0.1 and the rest\n");
+    assert!(fs::metadata(dir.join("f.txt"))
+        .unwrap()
+        .permissions()
+        .readonly());
+}
+
+#[test]
+fn failed_bumps_are_not_undone() {
+    let dir = temp_dir("undo");
+    fs::write(dir.join("f.txt"), "1.0.0\n").unwrap();
+
+    let output = verinc(&dir, &["f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    // The backup cannot be written, so neither is the file.
+    let output = verinc(&dir, &["--backup-suffix", "/x", "f.txt"]);
+    assert_eq!(output.status.code(), Some(3), "{output:?}");
+
+    let output = verinc(&dir, &["undo"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.0.0\n");
+    let output = verinc(&dir, &["undo"]);
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+}
+
+#[test]
+fn sync_writes_the_new_version_into_the_other_files() {
+    let dir = temp_dir("sync");
+    fs::write(dir.join("Cargo.toml"), "[package]\nversion = \"1.2.3\"\n").unwrap();
+    fs::write(dir.join("README.md"), "Install 1.2.3 (needs 2.0.0)\n").unwrap();
+    fs::write(dir.join("docs.md"), "See 2.0.0 and 1.2.3\n").unwrap();
+
+    let args = ["sync", "--minor", "--no-undo", "--format", "cargo"];
+    let output = verinc(&dir, &[&args[..], &["Cargo.toml", "README.md"]].concat());
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "Cargo.toml"), "[package]\nversion = \"1.3.0\"\n");
+    assert_eq!(read(&dir, "README.md"), "Install 1.3.0 (needs 2.0.0)\n");
+
+    // The docs have another version at position 0, nothing is written.
+    let output = verinc(&dir, &[&args[..], &["Cargo.toml", "docs.md"]].concat());
+    assert_eq!(output.status.code(), Some(6), "{output:?}");
+    assert_eq!(read(&dir, "Cargo.toml"), "[package]\nversion = \"1.3.0\"\n");
+    assert_eq!(read(&dir, "docs.md"), "See 2.0.0 and 1.2.3\n");
+
+    let output = verinc(
+        &dir,
+        &[
+            &args[..],
+            &["-p", "1", "Cargo.toml", "README.md", "docs.md"],
+        ]
+        .concat(),
+    );
+    assert_eq!(output.status.code(), Some(6), "{output:?}");
+    fs::write(dir.join("docs.md"), "See 2.0.0 and 1.3.0\n").unwrap();
+    let output = verinc(
+        &dir,
+        &["sync", "--no-undo", "-p", "1", "Cargo.toml", "docs.md"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "Cargo.toml"), "[package]\nversion = \"1.3.1\"\n");
+    assert_eq!(read(&dir, "docs.md"), "See 2.0.0 and 1.3.1\n");
+}
+
+#[test]
+fn commit_the_bump() {
+    let dir = repository("commit", &[("f.txt", "1.0.0\n"), ("g.txt", "2.0.0\n")]);
+
+    let args = [
+        "--minor",
+        "--commit",
+        "--commit-message",
+        "Release {new} ({old})",
+    ];
+    let output = verinc(&dir, &[&args[..], &["f.txt"]].concat());
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.1.0\n");
+    assert_eq!(
+        git(&dir, &["log", "-1", "--format=%s"]),
+        "Release 1.1.0 (1.0.0)\n"
+    );
+    assert_eq!(git(&dir, &["show", "--format=", "--name-only"]), "f.txt\n");
+    assert_eq!(git(&dir, &["status", "--porcelain"]), "");
+
+    let output = verinc(&dir, &["--commit", "--dry-run", "g.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Would commit with message 'chore: bump to 2.0.1'"),
+        "{stdout}"
+    );
+    assert_eq!(git(&dir, &["rev-list", "--count", "HEAD"]), "2\n");
+}
+
+#[test]
+fn require_a_clean_repository() {
+    let dir = repository("clean", &[("f.txt", "1.0.0\n"), ("g.txt", "x\n")]);
+    fs::write(dir.join("untracked.txt"), "y\n").unwrap();
+
+    let output = verinc(&dir, &["--require-clean", "--no-undo", "f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.0.1\n");
+
+    git(&dir, &["commit", "-q", "-am", "bump"]);
+    fs::write(dir.join("g.txt"), "changed\n").unwrap();
+    for args in [&["--require-clean"][..], &["--commit"]] {
+        let output = verinc(&dir, &[args, &["--no-undo", "f.txt"]].concat());
+        assert_eq!(output.status.code(), Some(6), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("uncommitted changes (g.txt)"), "{stderr}");
+        assert_eq!(read(&dir, "f.txt"), "1.0.1\n");
+    }
+
+    // Just looking doesn't need a clean repository.
+    let output = verinc(&dir, &["--require-clean", "--stdout", "f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[test]
+fn hooks_get_the_versions() {
+    let dir = temp_dir("hook-vars");
+    fs::write(dir.join("f.txt"), "1.0.0\n").unwrap();
+    let config = "[hooks]\npre = 'echo \"pre $OLD_VERSION $NEW_VERSION $FILE\" > hooks.log'\n";
+    fs::write(dir.join(".verinc.toml"), config).unwrap();
+
+    let hook = "echo \"post $(cat f.txt)\" >> hooks.log";
+    let output = verinc(
+        &dir,
+        &["--no-undo", "--minor", "--post-hook", hook, "f.txt"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let file = fs::canonicalize(dir.join("f.txt")).unwrap();
+    assert_eq!(
+        read(&dir, "hooks.log"),
+        format!("pre 1.0.0 1.1.0 {}\npost 1.1.0\n", file.display())
+    );
+
+    // A failed pre hook aborts the bump, a failed post hook comes too late for that.
+    let output = verinc(&dir, &["--no-undo", "--pre-hook", "exit 1", "f.txt"]);
+    assert_eq!(output.status.code(), Some(6), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.1.0\n");
+    let output = verinc(&dir, &["--no-undo", "--post-hook", "exit 1", "f.txt"]);
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.1.1\n");
+
+    let output = verinc(&dir, &["--dry-run", "--post-hook", "exit 1", "f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Would run the post hook 'exit 1'"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn hooks_stay_in_the_project() {
+    let dir = temp_dir("hook-dir");
+    fs::create_dir_all(dir.join("project/web")).unwrap();
+    fs::write(dir.join("project/f.txt"), "1.0.0\n").unwrap();
+    let config = "[hooks]\ndir = \"web\"\npre = \"pwd > ../hooks.log\"\n";
+    fs::write(dir.join("project/.verinc.toml"), config).unwrap();
+
+    let output = verinc(&dir, &["--no-undo", "project/f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    let web = fs::canonicalize(dir.join("project/web")).unwrap();
+    assert_eq!(
+        read(&dir, "project/hooks.log"),
+        format!("{}\n", web.display())
+    );
+
+    let config = "[hooks]\ndir = \"..\"\npre = \"touch hooked\"\n";
+    fs::write(dir.join("project/.verinc.toml"), config).unwrap();
+    let output = verinc(&dir, &["--no-undo", "project/f.txt"]);
+    assert_eq!(output.status.code(), Some(6), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("outside of"), "{stderr}");
+    assert!(!dir.join("hooked").exists());
+    assert_eq!(read(&dir, "project/f.txt"), "1.0.1\n");
+
+    let output = verinc(&dir, &["--no-undo", "--unsafe-hooks", "project/f.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.join("hooked").exists());
+}
+
+#[test]
+fn watch_bumps_the_changed_files() {
+    let dir = temp_dir("watch");
+    fs::write(dir.join("f.txt"), "1.0.0\n").unwrap();
+    let mut child = Command::new(VERINC)
+        .args(["--no-undo", "--watch", "f.txt"])
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let wait_for = |content: &str| {
+        let start = Instant::now();
+        while read(&dir, "f.txt") != content && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(50));
+        }
+        read(&dir, "f.txt")
+    };
+
+    let first = wait_for("1.0.1\n");
+    // Let the watcher take its first look at the file.
+    thread::sleep(Duration::from_secs(1));
+    fs::write(dir.join("f.txt"), "2.0.0 edited\n").unwrap();
+    let second = wait_for("2.0.1 edited\n");
+    // Its own write doesn't make it bump the file again.
+    thread::sleep(Duration::from_secs(2));
+    let third = read(&dir, "f.txt");
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(first, "1.0.1\n");
+    assert_eq!(second, "2.0.1 edited\n");
+    assert_eq!(third, "2.0.1 edited\n");
+}
+
+#[test]
+fn durable_writes_keep_a_journal() {
+    let dir = temp_dir("durable");
+    fs::write(dir.join("f.txt"), "1.0.0\n").unwrap();
+    fs::write(dir.join("g.txt"), "2.0.0\n").unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\nversion = \"1.0.0\"\n").unwrap();
+    let config =
+        "[[file]]\npath = \"f.txt\"\n\n[[file]]\npath = \"Cargo.toml\"\nformat = \"cargo\"\n";
+    fs::write(dir.join(".verinc.toml"), config).unwrap();
+
+    #[cfg(unix)]
+    let before = {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        fs::set_permissions(dir.join("g.txt"), fs::Permissions::from_mode(0o640)).unwrap();
+        fs::metadata(dir.join("g.txt")).unwrap().ino()
+    };
+    let output = verinc(&dir, &["--durable", "--no-undo", "g.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "g.txt"), "2.0.1\n");
+    assert!(!dir.join("g.txt.verinc-tmp").exists());
+    // Replaced by a renamed copy instead of being truncated in place.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        let metadata = fs::metadata(dir.join("g.txt")).unwrap();
+        assert_ne!(metadata.ino(), before);
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+    }
+    let output = verinc(&dir, &["bump-all", "--durable", "--no-undo"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.0.1\n");
+    assert_eq!(read(&dir, "Cargo.toml"), "[package]\nversion = \"1.0.1\"\n");
+    assert!(!dir.join(".verinc-journal").exists());
+
+    // Left behind by an interrupted run.
+    let journal = "# verinc: files of an unfinished run\n- - f.txt\n";
+    fs::write(dir.join(".verinc-journal"), journal).unwrap();
+    for args in [
+        &["--durable", "--no-undo", "g.txt"][..],
+        &["bump-all", "--durable"],
+    ] {
+        let output = verinc(&dir, args);
+        assert_eq!(output.status.code(), Some(6), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("f.txt: changed since"), "{stderr}");
+        assert_eq!(read(&dir, "f.txt"), "1.0.1\n");
+        assert_eq!(read(&dir, "g.txt"), "2.0.1\n");
+    }
+}
+
+#[test]
+fn exit_status_tells_the_failure() {
+    let dir = temp_dir("exit");
+    fs::write(dir.join("f.txt"), "1.0.0\n").unwrap();
+    fs::write(dir.join("none.txt"), "none\n").unwrap();
+    fs::write(dir.join("ro.txt"), "1.0.0\n").unwrap();
+    let mut permissions = fs::metadata(dir.join("ro.txt")).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(dir.join("ro.txt"), permissions).unwrap();
+
+    let cases: [(&[&str], i32, &str); 7] = [
+        (&["--bogus", "f.txt"], 2, "usage"),
+        (&["missing.txt"], 3, "io"),
+        (&["--set", "1.x", "f.txt"], 4, "parse"),
+        (&["--format", "cargo", "f.txt"], 4, "parse"),
+        (&["none.txt"], 5, "no_match"),
+        (&["ro.txt"], 6, "guard"),
+        (
+            &["--offline", "--upstream", "crates:serde", "f.txt"],
+            7,
+            "network",
+        ),
+    ];
+    for (args, code, kind) in cases {
+        let output = verinc(&dir, &[&["--json-errors", "--no-undo"], args].concat());
+        assert_eq!(output.status.code(), Some(code), "{args:?}: {output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let expected = format!("{{\"error\": {{\"kind\": \"{kind}\", \"code\": {code}, ");
+        assert!(stderr.starts_with(&expected), "{args:?}: {stderr}");
+    }
+    assert_eq!(read(&dir, "f.txt"), "1.0.0\n");
+    assert_eq!(read(&dir, "ro.txt"), "1.0.0\n");
+
+    let output = verinc(&dir, &["--no-undo", "f.txt"]);
+    assert_eq!(output.status.code(), Some(0), "{output:?}");
+}
+
+#[test]
+fn json_errors_of_bump_all_name_the_file() {
+    let dir = temp_dir("json-errors-all");
+    fs::write(dir.join("a.txt"), "1.0.0\n").unwrap();
+    fs::write(dir.join("b.txt"), "none\n").unwrap();
+    let config = "[[file]]\npath = \"a.txt\"\n\n[[file]]\npath = \"b.txt\"\nposition = 1\n";
+    fs::write(dir.join(".verinc.toml"), config).unwrap();
+
+    let output = verinc(&dir, &["bump-all", "--json-errors"]);
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("\"file\": \"b.txt\", \"position\": 1"),
+        "{stderr}"
+    );
+    assert_eq!(read(&dir, "a.txt"), "1.0.0\n");
+}
+
+#[test]
+fn bump_all_prints_the_bumps_unless_quiet() {
+    let dir = temp_dir("bump-all-quiet");
+    fs::write(dir.join("f.txt"), "1.0.0\n").unwrap();
+    fs::write(dir.join(".verinc.toml"), "[[file]]\npath = \"f.txt\"\n").unwrap();
+
+    let output = verinc(&dir, &["bump-all", "--no-undo"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "f.txt: 1.0.0 -> 1.0.1\n"
+    );
+
+    let output = verinc(&dir, &["bump-all", "--no-undo", "-q"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stdout.is_empty(), "{output:?}");
+    assert_eq!(read(&dir, "f.txt"), "1.0.2\n");
+}